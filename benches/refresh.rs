@@ -0,0 +1,119 @@
+// benches/refresh.rs
+
+//! Cost of refreshing and of the GUI's filter-and-sort pipeline as the process count
+//! grows. Run with `cargo bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use linux_task_manager::data_structures::{
+    sort_process_tree, sort_processes, CommandDisplay, ProcessInfo, SortField, SortOrder,
+};
+use linux_task_manager::filter::{FilterMode, ProcessFilter, TermMatch};
+use linux_task_manager::process_handler::ProcessHandler;
+use std::time::Duration;
+
+const SIZES: [usize; 3] = [100, 1_000, 10_000];
+
+const COMMANDS: [&str; 6] = ["chrome", "firefox", "bash", "sshd", "kworker/0:1", "postgres"];
+
+/// A plausible process list: a handful of command names, parents forming a shallow
+/// tree and varied usage, so sorts and filters do representative work.
+fn synthetic_processes(count: usize) -> Vec<ProcessInfo> {
+    (0..count)
+        .map(|i| {
+            let pid = i as i32 + 1;
+            let command = COMMANDS[i % COMMANDS.len()];
+            ProcessInfo {
+                pid,
+                ppid: (pid > 1).then_some(pid / 4 + 1),
+                user: "1000".to_string(),
+                uid: Some(1000),
+                cpu_usage: (i * 37 % 400) as f32 / 4.0,
+                observed_avg_cpu: (i * 13 % 100) as f32,
+                allowed_cpus: 8,
+                memory_usage: (i as u64 * 7919 % 4096) * 1024 * 1024,
+                memory_percent: (i * 7 % 100) as f32,
+                peak_memory: (i as u64 * 7919 % 8192) * 1024 * 1024,
+                virtual_memory: i as u64 * 4096 * 1024,
+                status: "Sleeping".to_string(),
+                command: command.to_string(),
+                exe: format!("/usr/bin/{}", command),
+                cmdline: format!("/usr/bin/{} --instance {}", command, i),
+                threads: (i % 64) as u32 + 1,
+                run_time_secs: i as u64 * 11,
+                start_time: i as u64 * 100,
+                efficiency: 0.1,
+                disk_read: i as u64 * 512,
+                disk_write: i as u64 * 256,
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Filters and sorts a copy of `processes` the way the GUI does on each refresh.
+fn filter_and_sort(processes: &[ProcessInfo], filter: &ProcessFilter) -> Vec<ProcessInfo> {
+    let mut filtered: Vec<ProcessInfo> =
+        processes.iter().filter(|p| filter.matches(p)).cloned().collect();
+    sort_processes(&mut filtered, SortField::CPU, SortOrder::Descending, CommandDisplay::Name);
+    filtered
+}
+
+fn bench_filter_and_sort(c: &mut Criterion) {
+    let mut group = c.benchmark_group("filter_and_sort");
+    let filters = [
+        ("substring", ProcessFilter::new("chr", FilterMode::Substring)),
+        ("glob", ProcessFilter::new("*fox*", FilterMode::Glob)),
+        (
+            "any_terms",
+            ProcessFilter::with_terms("chrome sshd", FilterMode::Substring, TermMatch::Any),
+        ),
+    ];
+    for size in SIZES {
+        let processes = synthetic_processes(size);
+        for (name, filter) in &filters {
+            group.bench_with_input(BenchmarkId::new(*name, size), &processes, |b, processes| {
+                b.iter(|| filter_and_sort(black_box(processes), filter))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_sort(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sort");
+    for size in SIZES {
+        let processes = synthetic_processes(size);
+        group.bench_with_input(BenchmarkId::new("flat", size), &processes, |b, processes| {
+            b.iter(|| {
+                let mut processes = processes.clone();
+                let mode = CommandDisplay::Name;
+                sort_processes(&mut processes, SortField::Memory, SortOrder::Descending, mode);
+                processes
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("tree", size), &processes, |b, processes| {
+            b.iter(|| {
+                let mut processes = processes.clone();
+                let mode = CommandDisplay::Name;
+                sort_process_tree(&mut processes, SortField::CPU, SortOrder::Descending, mode)
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Refreshes of the real system, so results depend on the machine's process count.
+fn bench_handler(c: &mut Criterion) {
+    let mut handler = ProcessHandler::new();
+    // Measure the refresh itself, not the wait for a meaningful CPU sample
+    handler.set_cpu_sample_interval(Duration::ZERO);
+    // The first refresh also builds per-process state that later ones only update
+    handler.refresh_processes();
+    c.bench_function("handler/refresh", |b| b.iter(|| handler.refresh()));
+    c.bench_function("handler/refresh_processes", |b| {
+        b.iter(|| handler.refresh_processes())
+    });
+}
+
+criterion_group!(benches, bench_filter_and_sort, bench_sort, bench_handler);
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+// src/automation.rs
+
+//! Pausing the actions alerts trigger, e.g. for a deployment whose restarts would
+//! otherwise set off `record --on-alert`.
+//!
+//! Automation is paused by `pause_automation` in the config, by `--pause-automation`,
+//! or from the GUI. The GUI leaves a marker file in the runtime directory, so CLI
+//! commands that are already running see the pause too. Paused actions still log
+//! what they would have done.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::quarantine;
+
+const MARKER_FILE: &str = "rust_task_manager-automation-paused";
+
+/// `rust_task_manager-automation-paused` in the runtime directory.
+pub fn marker_path() -> io::Result<PathBuf> {
+    Ok(quarantine::runtime_dir()?.join(MARKER_FILE))
+}
+
+/// Whether automation is paused by the config or a flag, or from the GUI.
+pub fn is_paused(config: &Config) -> bool {
+    config.pause_automation || is_paused_from_gui()
+}
+
+/// Whether the GUI left its pause marker.
+pub fn is_paused_from_gui() -> bool {
+    marker_path().is_ok_and(|path| path.exists())
+}
+
+/// Leaves or removes the GUI's pause marker.
+pub fn set_paused_from_gui(paused: bool) -> io::Result<()> {
+    let path = marker_path()?;
+    if paused {
+        fs::write(path, b"")
+    } else {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// src/cgroup.rs
+
+//! Resource limits a process is under from its cgroup (v2 only).
+//!
+//! `/proc/<pid>/cgroup` names the process's cgroup as a `0::<path>` line, relative
+//! to where the cgroup2 filesystem is mounted. A limit set on any ancestor applies
+//! too, so the whole path up to the root is checked. Systems with only cgroup v1,
+//! and cgroups without the memory or cpu controller enabled, report no limits.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Memory and CPU limits of a process's cgroup and how close it is to them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CgroupLimits {
+    /// The process's cgroup, e.g. `/system.slice/nginx.service`
+    pub path: String,
+    /// Memory in use, page cache included, by the cgroup that sets `memory_limit`,
+    /// or by the process's own cgroup when nothing limits it
+    pub memory_usage: Option<u64>,
+    /// `memory.max` of the cgroup (this one or an ancestor) with the least headroom
+    /// left; going over it gets a process in that cgroup OOM-killed
+    pub memory_limit: Option<u64>,
+    /// Tightest `cpu.max` along the path, in CPUs (quota divided by period)
+    pub cpu_limit: Option<f32>,
+}
+
+impl CgroupLimits {
+    /// Memory usage as a percentage of the limit, when there is one.
+    pub fn memory_percent(&self) -> Option<f32> {
+        let limit = self.memory_limit.filter(|&limit| limit > 0)?;
+        Some(self.memory_usage? as f32 / limit as f32 * 100.0)
+    }
+}
+
+/// Reads the limits of a process's cgroup; `None` without cgroup v2, or when the
+/// process has exited.
+pub fn read_limits(pid: i32) -> Option<CgroupLimits> {
+    let mount = cgroup2_mount()?;
+    let membership = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    let path = membership.lines().find_map(|line| line.strip_prefix("0::"))?.to_string();
+    let leaf = mount.join(path.trim_start_matches('/'));
+    if !leaf.is_dir() {
+        return None;
+    }
+
+    // The process's cgroup first, then each ancestor up to the mount
+    let chain: Vec<&Path> = leaf.ancestors().take_while(|dir| dir.starts_with(&mount)).collect();
+    let limited = chain
+        .iter()
+        .filter_map(|dir| {
+            let limit = read_value(dir, "memory.max")?;
+            let usage = read_value(dir, "memory.current")?;
+            Some((usage, limit))
+        })
+        .min_by_key(|(usage, limit)| limit.saturating_sub(*usage));
+    let (memory_usage, memory_limit) = match limited {
+        Some((usage, limit)) => (Some(usage), Some(limit)),
+        None => (read_value(&leaf, "memory.current"), None),
+    };
+    let cpu_limit = chain
+        .iter()
+        .filter_map(|dir| read_cpu_max(dir))
+        .min_by(|a, b| a.total_cmp(b));
+
+    Some(CgroupLimits { path, memory_usage, memory_limit, cpu_limit })
+}
+
+/// Where the cgroup2 filesystem is mounted: `/sys/fs/cgroup` on pure v2 systems,
+/// usually `/sys/fs/cgroup/unified` on hybrid ones.
+fn cgroup2_mount() -> Option<PathBuf> {
+    let mountinfo = fs::read_to_string("/proc/self/mountinfo").ok()?;
+    mountinfo.lines().find_map(|line| {
+        // The filesystem type follows the " - " separator; the mount point is field 5
+        let (mount, filesystem) = line.split_once(" - ")?;
+        if filesystem.split_whitespace().next()? != "cgroup2" {
+            return None;
+        }
+        mount.split_whitespace().nth(4).map(PathBuf::from)
+    })
+}
+
+/// A single number from a cgroup file; `None` for `max` (no limit) or a missing
+/// file, which means the controller isn't enabled there.
+fn read_value(dir: &Path, file: &str) -> Option<u64> {
+    fs::read_to_string(dir.join(file)).ok()?.trim().parse().ok()
+}
+
+/// `cpu.max` holds `<quota> <period>` in microseconds, or `max <period>`.
+fn read_cpu_max(dir: &Path) -> Option<f32> {
+    let contents = fs::read_to_string(dir.join("cpu.max")).ok()?;
+    let mut fields = contents.split_whitespace();
+    let quota: f32 = fields.next()?.parse().ok()?;
+    let period: f32 = fields.next()?.parse().ok()?;
+    (period > 0.0).then(|| quota / period)
+}
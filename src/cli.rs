@@ -1,33 +1,233 @@
 // src/cli.rs
 
-use clap::{Parser, Subcommand};
-use crate::process_handler::ProcessHandler;
-use nix::sys::signal::{self, Signal};
-use nix::unistd::Pid;
+use clap::builder::PossibleValuesParser;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use crate::automation;
+use crate::config::{Config, ConfigOverrides};
+use crate::data_structures::{
+    count_by_status, sort_processes, ProcessInfo, ProcessKey, ProcessList, SchedPolicy, SortField,
+    SortOrder,
+};
+use crate::error::ProcessError;
+use crate::export;
+use crate::filter::{FilterMode, FilterPreset, ProcessFilter, TermMatch};
+use crate::format::{
+    format_bytes, format_bytes_delta, format_cgroup_memory, format_cpu_list, format_sched_policy,
+    format_status_counts, set_byte_units,
+};
+use crate::ipc;
+use crate::logging;
+use crate::ports::find_port_owners;
+use crate::process_handler::{parse_signal, read_raw_stats, ProcessHandler, KILL_SIGNALS};
+use crate::quarantine::RESTORE_NICE;
+use crate::recorder::{RecordMode, Recorder, Sample};
+use crate::report::Report;
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use nix::sys::signal::Signal;
+use owo_colors::OwoColorize;
+use std::io::{IsTerminal, Write};
+use tracing::{info, info_span, warn};
+
+const DEFAULT_PER_PAGE: u64 = 50;
+const NET_TOP_COMMANDS: usize = 5; // Command names listed per namespace by net-top
+const HIGH_USAGE: f32 = 80.0; // CPU or MEM% marked red in the list
+const ELEVATED_USAGE: f32 = 50.0; // CPU or MEM% marked yellow in the list
+const WAIT_TIMED_OUT: i32 = 124; // Exit code of `wait` on timeout, as with timeout(1)
+const WAIT_POLL_MIN: Duration = Duration::from_millis(10);
+const WAIT_POLL_MAX: Duration = Duration::from_millis(500);
 
 #[derive(Parser)]
 #[command(name = "linux_task_manager")]
-#[command(about = "A CLI-based Linux Task Manager", long_about = None)]
+#[command(
+    about = "A Linux Task Manager; opens the GUI unless a command is given",
+    long_about = None
+)]
 pub struct Cli {
+    /// Without a command, the GUI opens
     #[command(subcommand)]
-    pub command: Commands,
+    pub command: Option<Commands>,
+
+    #[command(flatten)]
+    pub overrides: ConfigOverrides,
+
+    /// Enable debug logging (RUST_LOG takes precedence)
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// Print what destructive commands would do instead of doing it
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Run `monitor` or `record` in the background, detached from the terminal
+    #[arg(long, global = true)]
+    pub daemonize: bool,
+
+    /// Write the PID of `monitor` or `record` to this file, e.g. to stop it later
+    /// with `kill $(cat <file>)`
+    #[arg(long, global = true)]
+    pub pid_file: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns for reading in a terminal
+    Table,
+    /// `{"version": 2, "processes": [...]}`
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// One row per process after a header row
+    Csv,
+    /// `{"version": 2, "processes": [...]}`, as printed by `list --format json`
+    Json,
+}
+
+/// Which processes `list` and `export` include.
+#[derive(Args, Debug)]
+pub struct FilterArgs {
+    /// Filter by command name or PID; supports shell-style wildcards like '*worker*'
+    #[arg(short, long)]
+    filter: Option<String>,
+
+    /// Treat the filter as space-separated terms and show processes matching any
+    /// of them, e.g. -f 'chrome firefox' --any
+    #[arg(long)]
+    any: bool,
+
+    /// Treat the filter as space-separated terms and show processes matching all
+    /// of them
+    #[arg(long, conflicts_with = "any")]
+    all: bool,
+
+    /// Include processes on the config's ignore list
+    #[arg(long)]
+    show_all: bool,
+
+    /// Apply a saved filter preset's query, sort and usage ranges; --filter,
+    /// --sort-by and --order take precedence
+    #[arg(long)]
+    preset: Option<String>,
+}
+
+impl FilterArgs {
+    /// The preset named by --preset; exits listing the saved ones if there's no such
+    /// preset.
+    fn preset<'a>(&self, config: &'a Config) -> Option<&'a FilterPreset> {
+        let name = self.preset.as_ref()?;
+        match config.presets.get(name) {
+            Some(preset) => Some(preset),
+            None => {
+                let names: Vec<&str> = config.presets.keys().map(String::as_str).collect();
+                eprintln!("No preset named {} (saved presets: {})", name, names.join(", "));
+                std::process::exit(1);
+            }
+        }
+    }
+
+    /// Keeps the processes that match the filter (or else the preset's query) and the
+    /// preset's usage ranges, and that aren't ignored unless --show-all is given.
+    fn retain(
+        &self,
+        processes: &mut Vec<ProcessInfo>,
+        config: &Config,
+        preset: Option<&FilterPreset>,
+    ) {
+        let query = self
+            .filter
+            .as_deref()
+            .or(preset.map(|p| p.query.as_str()).filter(|query| !query.is_empty()));
+        if let Some(query) = query {
+            let terms = if self.any {
+                TermMatch::Any
+            } else if self.all {
+                TermMatch::All
+            } else {
+                TermMatch::Phrase
+            };
+            let filter = ProcessFilter::with_terms(query, FilterMode::Glob, terms);
+            processes.retain(|p| filter.matches(p));
+        }
+        if let Some(preset) = preset {
+            processes.retain(|p| preset.in_ranges(p));
+        }
+        if !self.show_all {
+            processes.retain(|p| !config.is_ignored(p.pid, &p.command));
+        }
+    }
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// List all running processes
     List {
-        /// Sort by field: pid, cpu, memory, command
-        #[arg(short, long, default_value = "pid")]
-        sort_by: String,
+        /// Field to sort by (default: from config)
+        #[arg(short, long, value_enum)]
+        sort_by: Option<SortField>,
 
-        /// Sort order: asc, desc
-        #[arg(short, long, default_value = "asc")]
-        order: String,
+        /// Sort order (default: from config)
+        #[arg(short, long, value_enum)]
+        order: Option<SortOrder>,
 
-        /// Filter by command name or PID
-        #[arg(short, long)]
-        filter: Option<String>,
+        #[command(flatten)]
+        filter: FilterArgs,
+
+        /// Page number to print, starting at 1
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        page: Option<u64>,
+
+        /// Number of processes per page (default: 50 when --page is given)
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        per_page: Option<u64>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+
+        /// Reuse the latest scan of a running GUI instance, scanning only if none is running
+        #[arg(long)]
+        attach: bool,
+
+        /// Only show these PIDs, e.g. --pids 12,34,56; exited ones are reported
+        #[arg(long, value_delimiter = ',')]
+        pids: Vec<i32>,
+
+        /// Measure CPU usage over --sample-ms instead of the shortest meaningful
+        /// interval, for steadier percentages
+        #[arg(long)]
+        accurate: bool,
+
+        /// Time --accurate measures CPU usage over, in milliseconds
+        #[arg(long, default_value_t = 500, requires = "accurate")]
+        sample_ms: u64,
+    },
+
+    /// Write the process list as CSV or JSON for scripts and dashboards
+    Export {
+        /// Output format
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+
+        /// File to write to (default: standard output)
+        #[arg(short = 'O', long)]
+        output: Option<PathBuf>,
+
+        /// Field to sort by (default: from config)
+        #[arg(short, long, value_enum)]
+        sort_by: Option<SortField>,
+
+        /// Sort order (default: from config)
+        #[arg(short, long, value_enum)]
+        order: Option<SortOrder>,
+
+        #[command(flatten)]
+        filter: FilterArgs,
     },
 
     /// Kill a process by PID
@@ -37,86 +237,1234 @@ pub enum Commands {
         pid: i32,
 
         /// Signal to send (default: SIGTERM)
-        #[arg(short, long, default_value = "SIGTERM")]
+        #[arg(
+            short,
+            long,
+            default_value = "SIGTERM",
+            value_parser = PossibleValuesParser::new(KILL_SIGNALS.map(Signal::as_str))
+        )]
         signal: String,
+
+        /// Show the process and ask before signaling it; the answer can also name a
+        /// different signal
+        #[arg(short, long)]
+        interactive: bool,
+
+        /// Answer yes to every prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Show disk usage of mounted filesystems
+    Disks,
+
+    /// Show traffic per network interface: bytes per second over the sample and
+    /// totals since each interface came up
+    Net {
+        /// How long to measure for, in milliseconds
+        #[arg(long, default_value_t = 1000)]
+        sample_ms: u64,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+
+    /// Show network traffic per network namespace (containers) and their processes.
+    /// The kernel doesn't count traffic per process, so processes sharing a namespace
+    /// are reported together; every ordinary process shares the host namespace.
+    NetTop {
+        /// How long to measure for, in milliseconds
+        #[arg(long, default_value_t = 1000)]
+        sample_ms: u64,
+
+        /// Number of namespaces to show
+        #[arg(short, long, default_value_t = 10)]
+        top: usize,
+    },
+
+    /// Find the process listening on a TCP port
+    Port {
+        /// Port number, e.g. 8080
+        port: u16,
+    },
+
+    /// Show the processes whose memory grew or shrank the most
+    Growth {
+        /// Number of samples to take, one per refresh interval
+        #[arg(
+            short,
+            long,
+            default_value_t = 10,
+            value_parser = clap::value_parser!(u64).range(2..)
+        )]
+        window: u64,
+
+        /// Number of processes to show
+        #[arg(short, long, default_value_t = 10)]
+        top: usize,
+    },
+
+    /// Write a snapshot of the whole system to a file
+    Report {
+        /// File to write the report to
+        #[arg(short = 'O', long)]
+        output: PathBuf,
+
+        /// Report format (table writes plain text)
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+
+    /// Compare two reports saved with `report --format json`
+    Diff {
+        /// The earlier report
+        before: PathBuf,
+
+        /// The later report
+        after: PathBuf,
+
+        /// Smallest CPU change, in percentage points, for a process to be listed
+        #[arg(long, default_value_t = 5.0)]
+        cpu_threshold: f32,
+
+        /// Smallest memory change, in MiB, for a process to be listed
+        #[arg(long, default_value_t = 50)]
+        memory_threshold_mb: u64,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+
+    /// Watch the system until stopped and append a timestamped line to a log file
+    /// whenever global CPU or memory usage crosses its limit (and when it recovers),
+    /// or a process's CPU usage jumps by more than the spike threshold in percentage
+    /// points between refreshes
+    Monitor {
+        /// File the alerts are appended to
+        #[arg(short, long)]
+        log: PathBuf,
+
+        /// Global CPU usage (%) to alert above (default: cpu_alert_threshold)
+        #[arg(long)]
+        cpu_max: Option<f32>,
+
+        /// Global memory usage (%) to alert above (default: memory_alert_threshold)
+        #[arg(long)]
+        mem_max: Option<f32>,
+    },
+
+    /// Append a sample of the system and every process to a file on each refresh,
+    /// one JSON object per line, until stopped
+    Record {
+        /// File the samples are appended to
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Only write samples around alerts: the ones before an alert fires (kept in
+        /// memory until then), while it lasts and after it clears
+        #[arg(long)]
+        on_alert: bool,
+
+        /// Samples to write from before an alert (default: record_pre_roll)
+        #[arg(long, requires = "on_alert")]
+        pre_roll: Option<usize>,
+
+        /// Samples to write after an alert clears (default: record_post_roll)
+        #[arg(long, requires = "on_alert")]
+        post_roll: Option<usize>,
+
+        /// Stop after this many refreshes instead of running until stopped
+        #[arg(long)]
+        samples: Option<usize>,
+    },
+
+    /// Wait until a process exits, e.g. to run something after it finishes. Exits
+    /// with 0 once it's gone, or 124 if the timeout runs out first.
+    Wait {
+        /// PID of the process to wait for
+        #[arg(short, long, required_unless_present = "name", conflicts_with = "name")]
+        pid: Option<i32>,
+
+        /// Wait for every process with this command name instead
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// Give up after this many seconds
+        #[arg(short, long)]
+        timeout: Option<u64>,
+    },
+
+    /// Show a process's scheduling policy, or change it
+    Sched {
+        /// PID of the process
+        #[arg(short, long)]
+        pid: i32,
+
+        /// Policy to switch to; fifo and rr are real-time and need root
+        #[arg(long)]
+        policy: Option<SchedPolicy>,
+
+        /// Real-time priority, 1-99 (default: 1 for fifo and rr, 0 otherwise)
+        #[arg(long, requires = "policy")]
+        priority: Option<i32>,
+    },
+
+    /// Show a process's OOM score adjustment, or change it
+    Oom {
+        /// PID of the process
+        #[arg(short, long)]
+        pid: i32,
+
+        /// New adjustment, from -1000 (never killed when out of memory) to 1000 (killed
+        /// first); lowering it needs root
+        #[arg(long, allow_negative_numbers = true,
+              value_parser = clap::value_parser!(i32).range(-1000..=1000))]
+        adj: Option<i32>,
+    },
+
+    /// Stop a process, set its nice value to 19 and pin it to one CPU, so it's frozen
+    /// but can be released with `unquarantine`; without --pid, list quarantined processes
+    Quarantine {
+        /// PID of the process
+        #[arg(short, long)]
+        pid: Option<i32>,
+    },
+
+    /// Release a quarantined process, restoring its CPU affinity and nice value
+    Unquarantine {
+        /// PID of the process
+        #[arg(short, long)]
+        pid: i32,
+    },
+
+    /// Print a shell completion script, e.g. `completions zsh > _linux_task_manager`
+    Completions {
+        /// Shell to generate the script for
+        shell: Shell,
+    },
+
+    /// Show details of a single process
+    Info {
+        /// PID of the process to inspect
+        #[arg(short, long)]
+        pid: i32,
+
+        /// Print every field of /proc/<pid>/stat and /proc/<pid>/status instead
+        #[arg(long)]
+        raw: bool,
     },
 }
 
-pub fn run_cli() {
-    let cli = Cli::parse();
+/// Refreshes every `refresh_interval_ms` and appends alerts to `log` until the process
+/// is stopped; only returns when the log can't be written.
+fn monitor(config: &Config, log: &Path, cpu_max: f32, mem_max: f32) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log)
+        .map_err(|e| format!("Failed to open {}: {}", log.display(), e))?;
+    let mut write_line = |message: String| {
+        let timestamp = humantime::format_rfc3339_seconds(std::time::SystemTime::now());
+        writeln!(file, "{} {}", timestamp, message)
+            .map_err(|e| format!("Failed to write {}: {}", log.display(), e))
+    };
 
-    match &cli.command {
-        Commands::List { sort_by, order, filter } => {
-            let mut handler = ProcessHandler::new();
-            let mut processes = handler.refresh_processes();
+    let interval = std::time::Duration::from_millis(config.refresh_interval_ms);
+    let mut handler = ProcessHandler::with_history_capacity(config.history_capacity);
+    let mut breached = [false; 2];
+    let mut previous_cpu: HashMap<ProcessKey, f32> = HashMap::new();
+    while !STOP_REQUESTED.load(Ordering::Relaxed) {
+        handler.refresh();
+        let processes = handler.refresh_processes();
 
-            if let Some(query) = filter {
-                let query = query.to_lowercase();
-                processes = processes.into_iter()
-                    .filter(|p|
-                        p.pid.to_string().contains(&query) ||
-                        p.command.to_lowercase().contains(&query)
-                    )
-                    .collect();
+        let usage = [
+            ("CPU", handler.get_cpu_usage_history().last().copied(), cpu_max),
+            ("Memory", handler.get_memory_usage_history().last().copied(), mem_max),
+        ];
+        for ((name, usage, limit), breached) in usage.into_iter().zip(&mut breached) {
+            let Some(usage) = usage else { continue };
+            // Only crossings are logged, so a long breach doesn't flood the file
+            if usage > limit && !*breached {
+                write_line(format!("{} usage {:.1}% exceeds {:.0}%", name, usage, limit))?;
+            } else if usage <= limit && *breached {
+                write_line(format!("{} usage back to {:.1}%", name, usage))?;
+            }
+            *breached = usage > limit;
+        }
+
+        for process in &processes {
+            let Some(&before) = previous_cpu.get(&process.key()) else { continue };
+            if process.cpu_usage - before > config.spike_threshold {
+                write_line(format!(
+                    "PID {} ({}) CPU spiked from {:.1}% to {:.1}%",
+                    process.pid,
+                    process.display_command(config.command_display),
+                    before,
+                    process.cpu_usage
+                ))?;
+            }
+        }
+        previous_cpu = processes.iter().map(|p| (p.key(), p.cpu_usage)).collect();
+
+        std::thread::sleep(interval);
+    }
+    Ok(())
+}
+
+/// Refreshes every `refresh_interval_ms` and hands each sample to the recorder,
+/// `samples` times or until the process is stopped.
+fn record(config: &Config, recorder: &mut Recorder, samples: Option<usize>) -> Result<(), String> {
+    let interval = std::time::Duration::from_millis(config.refresh_interval_ms);
+    let mut handler = ProcessHandler::with_history_capacity(config.history_capacity);
+    handler.warm_up_cpu_sampling();
+    let mut taken = 0;
+    while samples.is_none_or(|samples| taken < samples) && !STOP_REQUESTED.load(Ordering::Relaxed) {
+        std::thread::sleep(interval);
+        handler.refresh();
+        let processes = handler.refresh_processes();
+        // Checked on every refresh, so a pause from the GUI applies right away
+        recorder.set_paused(automation::is_paused(config));
+        let cpu_usage = handler.get_cpu_usage_history().last().copied().unwrap_or(0.0);
+        let memory_usage = handler.get_memory_usage_history().last().copied().unwrap_or(0.0);
+        let written = recorder.push(Sample::new(
+            humantime::format_rfc3339_seconds(std::time::SystemTime::now()).to_string(),
+            cpu_usage,
+            memory_usage,
+            config.alerts(Some(cpu_usage), Some(memory_usage)),
+            processes,
+        ))?;
+        if written > 0 {
+            info!(written, "Recorded samples");
+        }
+        taken += 1;
+    }
+    Ok(())
+}
+
+/// Set by SIGTERM or SIGINT while a PID file is held, so monitor and record finish
+/// their current refresh and return instead of dying with the file left behind.
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" fn request_stop(_signal: nix::libc::c_int) {
+    STOP_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+/// A PID file written by `detach`, removed again when dropped.
+struct PidFile(PathBuf);
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.0) {
+            warn!("Failed to remove {}: {}", self.0.display(), e);
+        }
+    }
+}
+
+/// Whether `pid` is another running instance of this program. A PID file left by a
+/// crash names a PID that may since have been reused, so a live process only counts
+/// when its command name matches ours.
+#[cfg(unix)]
+fn is_running_instance(pid: i32) -> bool {
+    use nix::errno::Errno;
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid;
+
+    let comm = |pid: &str| std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok();
+    // EPERM still means the process exists, just under another user
+    pid > 0
+        && pid != std::process::id() as i32
+        && !matches!(kill(Pid::from_raw(pid), None), Err(Errno::ESRCH))
+        && comm(&pid.to_string()).is_some_and(|name| Some(name) == comm("self"))
+}
+
+/// Forks into the background when `daemonize` is set, then writes the PID (of the
+/// background process, if any) to `pid_file`. Refuses to start when the PID file
+/// names another running instance; a stale file is overwritten. The working
+/// directory is kept, so relative paths still work; output goes to /dev/null once
+/// detached.
+///
+/// The returned guard removes the PID file when dropped. While it is held, SIGTERM
+/// and SIGINT set `STOP_REQUESTED` rather than killing the process.
+#[cfg(unix)]
+fn detach(daemonize: bool, pid_file: Option<&Path>) -> Result<Option<PidFile>, String> {
+    use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet};
+
+    if let Some(path) = pid_file {
+        let running = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| contents.trim().parse::<i32>().ok())
+            .filter(|&pid| is_running_instance(pid));
+        if let Some(pid) = running {
+            return Err(format!("Already running as PID {} (from {})", pid, path.display()));
+        }
+    }
+    if daemonize {
+        nix::unistd::daemon(true, false).map_err(|e| format!("Failed to daemonize: {}", e))?;
+    }
+    let Some(path) = pid_file else { return Ok(None) };
+    std::fs::write(path, format!("{}\n", std::process::id()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    let pid_file = PidFile(path.to_path_buf());
+
+    let action =
+        SigAction::new(SigHandler::Handler(request_stop), SaFlags::empty(), SigSet::empty());
+    for signal in [Signal::SIGTERM, Signal::SIGINT] {
+        // The handler only stores to an atomic, which is async-signal-safe
+        unsafe { sigaction(signal, &action) }
+            .map_err(|e| format!("Failed to handle {}: {}", signal, e))?;
+    }
+    Ok(Some(pid_file))
+}
+
+#[cfg(not(unix))]
+fn detach(_daemonize: bool, _pid_file: Option<&Path>) -> Result<Option<PidFile>, String> {
+    Err("--daemonize and --pid-file are only supported on Unix".into())
+}
+
+/// Polls until none of `pids` is alive; returns false if `timeout` ran out first.
+/// Checks often at first, so short-lived processes are noticed promptly, and back
+/// off to `WAIT_POLL_MAX` for long waits.
+fn wait_for_exit(handler: &ProcessHandler, pids: &[i32], timeout: Option<Duration>) -> bool {
+    let started = Instant::now();
+    let mut poll = WAIT_POLL_MIN;
+    loop {
+        if !pids.iter().any(|&pid| handler.is_alive(pid)) {
+            return true;
+        }
+        let mut sleep = poll;
+        if let Some(timeout) = timeout {
+            let left = timeout.saturating_sub(started.elapsed());
+            if left.is_zero() {
+                return false;
+            }
+            sleep = sleep.min(left);
+        }
+        std::thread::sleep(sleep);
+        poll = (poll * 2).min(WAIT_POLL_MAX);
+    }
+}
+
+/// A colored dot for CPU or memory usage above the marking thresholds, else a blank.
+fn severity_marker(usage: f32) -> String {
+    if usage > HIGH_USAGE {
+        "●".red().to_string()
+    } else if usage > ELEVATED_USAGE {
+        "●".yellow().to_string()
+    } else {
+        " ".to_string()
+    }
+}
+
+/// Shows a process and asks whether to signal it, like `kill -i`. Returns the
+/// signal to send, or `None` to leave the process alone. Answering with a signal
+/// name (`KILL`, `SIGHUP`) sends that signal instead of `default`.
+fn prompt_signal(process: &ProcessInfo, default: Signal) -> Option<Signal> {
+    println!(
+        "PID {} ({}) user {}, CPU {:.1}%, memory {}",
+        process.pid,
+        process.command,
+        process.user,
+        process.cpu_usage,
+        format_bytes(process.memory_usage)
+    );
+    if !process.cmdline.is_empty() {
+        println!("  {}", process.cmdline);
+    }
+
+    loop {
+        print!("Send {}? [y/N/signal] ", default.as_str());
+        let _ = std::io::stdout().flush();
+        let mut answer = String::new();
+        // End of input or a read error never counts as a yes
+        if std::io::stdin().read_line(&mut answer).unwrap_or(0) == 0 {
+            println!();
+            return None;
+        }
+        let answer = answer.trim();
+        match answer.to_lowercase().as_str() {
+            "y" | "yes" => return Some(default),
+            "" | "n" | "no" => return None,
+            _ => {}
+        }
+        let name = answer.to_uppercase();
+        let name = if name.starts_with("SIG") { name } else { format!("SIG{}", name) };
+        match name.parse::<Signal>() {
+            Ok(signal) => return Some(signal),
+            Err(_) => println!("Expected y, n or a signal name like KILL, got: {}", answer),
+        }
+    }
+}
+
+/// Writes `processes` to `out` as CSV or versioned JSON.
+fn export_processes(
+    out: &mut impl Write,
+    format: ExportFormat,
+    processes: &[ProcessInfo],
+) -> std::io::Result<()> {
+    match format {
+        ExportFormat::Csv => export::write_csv(out, processes),
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, &ProcessList::new(processes))?;
+            writeln!(out)?;
+            out.flush()
+        }
+    }
+}
+
+/// Runs `command`, one of `cli`'s subcommands.
+pub fn run_cli(cli: &Cli, command: &Commands) {
+    logging::init(cli.verbose);
+
+    let config = match Config::resolve(&cli.overrides) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    set_byte_units(config.units);
+
+    // Held until run_cli returns, so the PID file goes away on a normal exit
+    let _pid_file = if cli.daemonize || cli.pid_file.is_some() {
+        if !matches!(command, Commands::Monitor { .. } | Commands::Record { .. }) {
+            eprintln!("--daemonize and --pid-file only apply to monitor and record");
+            std::process::exit(1);
+        }
+        match detach(cli.daemonize, cli.pid_file.as_deref()) {
+            Ok(pid_file) => pid_file,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
             }
+        }
+    } else {
+        None
+    };
+
+    match command {
+        Commands::List {
+            sort_by,
+            order,
+            filter,
+            page,
+            per_page,
+            format,
+            attach,
+            pids,
+            accurate,
+            sample_ms,
+        } => {
+            let preset = filter.preset(&config);
+            let field = sort_by.or(preset.map(|p| p.sort)).unwrap_or(config.default_sort);
+            let order = order.or(preset.map(|p| p.order)).unwrap_or(config.default_order);
 
-            match sort_by.as_str() {
-                "pid" => {
-                    if order == "asc" {
-                        processes.sort_by_key(|p| p.pid);
+            let shared = if *attach { ipc::request_processes() } else { None };
+            let mut processes = match shared {
+                Some(processes) => {
+                    info!("Using the process list of the running instance");
+                    processes
+                }
+                None => {
+                    let mut handler = ProcessHandler::with_history_capacity(config.history_capacity);
+                    if *accurate {
+                        // CPU usage is measured between two samples, from this one on
+                        handler.warm_up_cpu_sampling();
+                        std::thread::sleep(std::time::Duration::from_millis(*sample_ms));
+                    }
+                    if pids.is_empty() {
+                        handler.refresh_processes()
                     } else {
-                        processes.sort_by_key(|p| std::cmp::Reverse(p.pid));
+                        handler
+                            .refresh_watched(pids)
+                            .into_iter()
+                            .filter_map(|(_, info)| info)
+                            .collect()
                     }
                 }
-                "cpu" => {
-                    if order == "asc" {
-                        processes.sort_by(|a, b| a.cpu_usage.partial_cmp(&b.cpu_usage).unwrap());
-                    } else {
-                        processes.sort_by(|a, b| b.cpu_usage.partial_cmp(&a.cpu_usage).unwrap());
+            };
+            if !pids.is_empty() {
+                processes.retain(|p| pids.contains(&p.pid));
+                for pid in pids.iter().filter(|&&pid| processes.iter().all(|p| p.pid != pid)) {
+                    eprintln!("PID {} exited", pid);
+                }
+            }
+
+            filter.retain(&mut processes, &config, preset);
+
+            sort_processes(&mut processes, field, order, config.command_display);
+            let status_summary = format_status_counts(&count_by_status(&processes));
+
+            // Only paginate when asked to, so the default output stays a full listing
+            let pagination = match (page, per_page) {
+                (None, None) => None,
+                (page, per_page) => Some((
+                    page.unwrap_or(1) as usize,
+                    per_page.unwrap_or(DEFAULT_PER_PAGE) as usize,
+                )),
+            };
+            let (page_processes, footer) = match pagination {
+                Some((page, per_page)) => {
+                    let total_pages = processes.len().div_ceil(per_page).max(1);
+                    let slice: Vec<_> = processes
+                        .into_iter()
+                        .skip((page - 1).saturating_mul(per_page))
+                        .take(per_page)
+                        .collect();
+                    (slice, Some(format!("page {}/{}", page, total_pages)))
+                }
+                None => (processes, None),
+            };
+
+            if *format == OutputFormat::Json {
+                match serde_json::to_string_pretty(&ProcessList::new(&page_processes)) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => {
+                        eprintln!("Failed to serialize processes: {}", e);
+                        std::process::exit(1);
                     }
                 }
-                "memory" => {
-                    if order == "asc" {
-                        processes.sort_by(|a, b| a.memory_usage.cmp(&b.memory_usage));
-                    } else {
-                        processes.sort_by(|a, b| b.memory_usage.cmp(&a.memory_usage));
+                return;
+            }
+
+            // Markers are for people; piped output keeps the plain columns scripts expect
+            let markers = std::io::stdout().is_terminal();
+            if markers {
+                print!("  ");
+            }
+            println!("{:<10} {:<15} {:<10} {:<8} {:<12} {:<6} {:<8} {:<5} {:<12} {:<12} Command", "PID", "User", "CPU%", "Avg%", "Memory", "MEM%", "Threads", "Nice", "Read", "Write");
+            for p in page_processes {
+                if markers {
+                    print!("{} ", severity_marker(p.cpu_usage.max(p.memory_percent)));
+                }
+                // Pad before coloring so the escape codes don't count toward the width
+                let mut nice = format!("{:<5}", p.nice);
+                if markers && p.nice < 0 {
+                    nice = nice.cyan().to_string();
+                }
+                println!(
+                    "{:<10} {:<15} {:<10.2} {:<8.2} {:<12} {:<6.1} {:<8} {} {:<12} {:<12} {}",
+                    p.pid,
+                    p.user,
+                    p.cpu_usage,
+                    p.observed_avg_cpu,
+                    format_bytes(p.memory_usage),
+                    p.memory_percent,
+                    p.threads,
+                    nice,
+                    format_bytes(p.disk_read),
+                    format_bytes(p.disk_write),
+                    p.display_command(config.command_display)
+                );
+            }
+            if let Some(footer) = footer {
+                println!("{}", footer);
+            }
+            if markers {
+                println!("{}", status_summary);
+            }
+        }
+
+        Commands::Kill { pid, signal, interactive, yes } => {
+            let mut sig = match parse_signal(signal) {
+                Ok(sig) => sig,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let mut handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            handler.set_kill_policy(config.kill_policy.clone());
+            if *interactive && !*yes {
+                let Some(process) = handler.process_info(*pid) else {
+                    eprintln!("No process with PID {}", pid);
+                    std::process::exit(1);
+                };
+                match prompt_signal(&process, sig) {
+                    Some(chosen) => sig = chosen,
+                    None => {
+                        println!("Skipped PID {}", pid);
+                        return;
                     }
                 }
-                "command" => {
-                    if order == "asc" {
-                        processes.sort_by(|a, b| a.command.cmp(&b.command));
-                    } else {
-                        processes.sort_by(|a, b| b.command.cmp(&a.command));
+            }
+
+            let _span = info_span!("kill", pid, signal = sig.as_str()).entered();
+            // Exits 1 on any refusal or failure, so scripts can tell nothing was sent
+            if cli.dry_run {
+                match handler.describe_signal(*pid, sig) {
+                    Ok(action) => println!("Would {}", action),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
                     }
                 }
-                _ => {
-                    eprintln!("Invalid sort field: {}", sort_by);
+                return;
+            }
+            match handler.kill_process(*pid, sig) {
+                Ok(_) => info!("Successfully sent {} to PID {}", sig, pid),
+                Err(e) => {
+                    eprintln!("{}", e);
                     std::process::exit(1);
                 }
             }
+        }
+
+        Commands::Export { format, output, sort_by, order, filter } => {
+            let preset = filter.preset(&config);
+            let mut handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            let mut processes = handler.refresh_processes();
+            filter.retain(&mut processes, &config, preset);
+            sort_processes(
+                &mut processes,
+                sort_by.or(preset.map(|p| p.sort)).unwrap_or(config.default_sort),
+                order.or(preset.map(|p| p.order)).unwrap_or(config.default_order),
+                config.command_display,
+            );
 
-            println!("{:<10} {:<15} {:<10} {:<10} {}", "PID", "User", "CPU%", "Memory", "Command");
-            for p in processes {
-                println!("{:<10} {:<15} {:<10.2} {:<10} {}", p.pid, p.user, p.cpu_usage, p.memory_usage, p.command);
+            let destination = output
+                .as_ref()
+                .map_or("standard output".to_string(), |path| path.display().to_string());
+            let written = match output {
+                Some(path) => std::fs::File::create(path).and_then(|file| {
+                    export_processes(&mut std::io::BufWriter::new(file), *format, &processes)
+                }),
+                None => export_processes(&mut std::io::stdout().lock(), *format, &processes),
+            };
+            if let Err(e) = written {
+                eprintln!("Failed to write {}: {}", destination, e);
+                std::process::exit(1);
+            }
+            if output.is_some() {
+                info!("Exported {} processes to {}", processes.len(), destination);
             }
         }
 
-        Commands::Kill { pid, signal } => {
-            let sig = match signal.as_str() {
-                "SIGTERM" => Signal::SIGTERM,
-                "SIGKILL" => Signal::SIGKILL,
-                "SIGHUP" => Signal::SIGHUP,
-                _ => {
-                    eprintln!("Unsupported signal: {}", signal);
+        Commands::Net { sample_ms, format } => {
+            let mut handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            handler.refresh_network();
+            std::thread::sleep(std::time::Duration::from_millis(*sample_ms));
+            handler.refresh_network();
+
+            let interfaces = handler.network_interfaces();
+            if *format == OutputFormat::Json {
+                match serde_json::to_string_pretty(interfaces) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => {
+                        eprintln!("Failed to serialize interfaces: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                return;
+            }
+            if interfaces.is_empty() {
+                eprintln!("No network interfaces could be read from /proc/net/dev");
+                std::process::exit(1);
+            }
+            println!(
+                "{:<16} {:<12} {:<12} {:<12} TX total",
+                "Interface", "RX/s", "TX/s", "RX total"
+            );
+            for interface in interfaces {
+                println!(
+                    "{:<16} {:<12} {:<12} {:<12} {}",
+                    interface.name,
+                    format_bytes(interface.rx_per_sec),
+                    format_bytes(interface.tx_per_sec),
+                    format_bytes(interface.rx_total),
+                    format_bytes(interface.tx_total)
+                );
+            }
+        }
+
+        Commands::NetTop { sample_ms, top } => {
+            let mut handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            handler.refresh_processes();
+            std::thread::sleep(std::time::Duration::from_millis(*sample_ms));
+            handler.refresh_processes();
+
+            let traffic = handler.network_traffic();
+            if traffic.is_empty() {
+                println!("No network namespaces could be read (try as root)");
+                return;
+            }
+            let commands: std::collections::HashMap<i32, &str> = handler
+                .last_scan()
+                .iter()
+                .map(|p| (p.pid, p.display_command(config.command_display)))
+                .collect();
+            let per_sec = |bytes: u64| bytes as f64 * 1000.0 / (*sample_ms).max(1) as f64;
+            println!("{:<24} {:<12} {:<12} Processes", "Namespace", "RX/s", "TX/s");
+            for t in traffic.iter().take(*top) {
+                let mut names: Vec<&str> =
+                    t.pids.iter().filter_map(|pid| commands.get(pid).copied()).collect();
+                names.sort_unstable();
+                names.dedup();
+                let more = names.len().saturating_sub(NET_TOP_COMMANDS);
+                names.truncate(NET_TOP_COMMANDS);
+                let names = if more > 0 {
+                    format!("{}, +{} more", names.join(", "), more)
+                } else {
+                    names.join(", ")
+                };
+                let label = if t.host {
+                    format!("{} (host)", t.namespace)
+                } else {
+                    t.namespace.clone()
+                };
+                println!(
+                    "{:<24} {:<12} {:<12} {} ({})",
+                    label,
+                    format_bytes(per_sec(t.rx_bytes) as u64),
+                    format_bytes(per_sec(t.tx_bytes) as u64),
+                    t.pids.len(),
+                    names
+                );
+            }
+        }
+
+        Commands::Port { port } => {
+            let owners = find_port_owners(*port);
+            if !owners.listening {
+                println!("Nothing is listening on TCP port {}", port);
+                std::process::exit(1);
+            }
+            let mut handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            for pid in &owners.pids {
+                let command = handler
+                    .process_info(*pid)
+                    .map(|process| process.command)
+                    .unwrap_or_else(|| "exited".into());
+                println!("TCP port {} is used by PID {} ({})", port, pid, command);
+            }
+            if owners.pids.is_empty() && owners.unreadable > 0 {
+                println!(
+                    "TCP port {} is listening, but its owner is one of {} processes whose open \
+                     files can't be read; try again as root",
+                    port, owners.unreadable
+                );
+            } else if owners.pids.is_empty() {
+                // e.g. a process in another PID namespace sharing our network namespace
+                println!("TCP port {} is listening, but no visible process owns it", port);
+            } else if owners.unreadable > 0 {
+                info!("Skipped {} processes whose open files can't be read", owners.unreadable);
+            }
+        }
+
+        Commands::Disks => {
+            let handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            let disks = handler.disk_usage();
+
+            println!("{:<20} {:<25} {:<10} {:<12} {:<12} Use%", "Filesystem", "Mounted on", "Type", "Size", "Available");
+            for d in disks {
+                println!(
+                    "{:<20} {:<25} {:<10} {:<12} {:<12} {:.1}%",
+                    d.name,
+                    d.mount_point,
+                    d.file_system,
+                    format_bytes(d.total_space),
+                    format_bytes(d.available_space),
+                    d.usage_percent
+                );
+            }
+        }
+
+        Commands::Growth { window, top } => {
+            let window = *window as usize;
+            // Keep every sample so the whole run is measured
+            let mut handler = ProcessHandler::with_history_capacity(window);
+            let interval = std::time::Duration::from_millis(config.refresh_interval_ms);
+            for sample in 0..window {
+                if sample > 0 {
+                    std::thread::sleep(interval);
+                }
+                handler.refresh_processes();
+            }
+
+            let commands: std::collections::HashMap<i32, String> = handler
+                .last_scan()
+                .iter()
+                .map(|p| (p.pid, p.display_command(config.command_display).to_string()))
+                .collect();
+            println!("{:<10} {:<14} Command", "PID", "Change");
+            for (pid, delta) in handler.memory_growth_leaderboard(window).into_iter().take(*top) {
+                let command = commands.get(&pid).map(String::as_str).unwrap_or("");
+                println!("{:<10} {:<14} {}", pid, format_bytes_delta(delta), command);
+            }
+        }
+
+        Commands::Report { output, format } => {
+            let report = Report::capture(&config);
+            let contents = match format {
+                OutputFormat::Table => report.to_text(),
+                OutputFormat::Json => match serde_json::to_string_pretty(&report) {
+                    Ok(json) => json,
+                    Err(e) => {
+                        eprintln!("Failed to serialize report: {}", e);
+                        std::process::exit(1);
+                    }
+                },
+            };
+            if let Err(e) = std::fs::write(output, contents) {
+                eprintln!("Failed to write {}: {}", output.display(), e);
+                std::process::exit(1);
+            }
+            info!("Wrote report to {}", output.display());
+        }
+
+        Commands::Diff { before, after, cpu_threshold, memory_threshold_mb, format } => {
+            let load = |path: &PathBuf| {
+                Report::load(path).unwrap_or_else(|e| {
+                    eprintln!("{}", e);
                     std::process::exit(1);
+                })
+            };
+            let memory_threshold = memory_threshold_mb * 1024 * 1024;
+            let diff = load(before).diff(&load(after), *cpu_threshold, memory_threshold);
+            match format {
+                OutputFormat::Table => print!("{}", diff.to_text()),
+                OutputFormat::Json => match serde_json::to_string_pretty(&diff) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => {
+                        eprintln!("Failed to serialize diff: {}", e);
+                        std::process::exit(1);
+                    }
+                },
+            }
+        }
+
+        Commands::Monitor { log, cpu_max, mem_max } => {
+            let cpu_max = cpu_max.unwrap_or(config.cpu_alert_threshold);
+            let mem_max = mem_max.unwrap_or(config.memory_alert_threshold);
+            println!("Monitoring; alerts are appended to {} (Ctrl+C to stop)", log.display());
+            if let Err(e) = monitor(&config, log, cpu_max, mem_max) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Record { output, on_alert, pre_roll, post_roll, samples } => {
+            let mode = if *on_alert {
+                RecordMode::OnAlert {
+                    pre_roll: pre_roll.unwrap_or(config.record_pre_roll),
+                    post_roll: post_roll.unwrap_or(config.record_post_roll),
                 }
+            } else {
+                RecordMode::Continuous
             };
+            let result = Recorder::create(output, mode)
+                .and_then(|mut recorder| record(&config, &mut recorder, *samples));
+            if let Err(e) = result {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
 
-            let result = signal::kill(Pid::from_raw(*pid), sig);
-            match result {
-                Ok(_) => println!("Successfully sent {} to PID {}", signal, pid),
-                Err(e) => eprintln!("Failed to send signal: {}", e),
+        Commands::Wait { pid, name, timeout } => {
+            let mut handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            let pids: Vec<i32> = match (pid, name) {
+                (Some(pid), _) => vec![*pid],
+                (None, Some(name)) => handler
+                    .refresh_processes()
+                    .into_iter()
+                    .filter(|p| p.command == *name)
+                    .map(|p| p.pid)
+                    .collect(),
+                (None, None) => unreachable!("clap requires --pid or --name"),
+            };
+            // Already gone counts as exited, so a script can't hang on a fast process
+            if pids.is_empty() {
+                info!("No process named {} is running", name.as_deref().unwrap_or_default());
+            }
+            if !wait_for_exit(&handler, &pids, timeout.map(Duration::from_secs)) {
+                let running: Vec<String> = pids
+                    .iter()
+                    .filter(|&&pid| handler.is_alive(pid))
+                    .map(i32::to_string)
+                    .collect();
+                eprintln!("Timed out waiting for PID {}", running.join(", "));
+                std::process::exit(WAIT_TIMED_OUT);
+            }
+        }
+
+        Commands::Sched { pid, policy: None, .. } => {
+            let handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            match handler.get_sched_policy(*pid) {
+                Ok((policy, priority)) => println!("{}", format_sched_policy(policy, priority)),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Commands::Sched { pid, policy: Some(policy), priority } => {
+            let priority = priority.unwrap_or(if policy.is_realtime() { 1 } else { 0 });
+            let handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            if cli.dry_run {
+                println!("Would set PID {} to {}", pid, format_sched_policy(*policy, priority));
+                return;
+            }
+            match handler.set_sched_policy(*pid, *policy, priority) {
+                Ok(()) => info!("Set PID {} to {}", pid, format_sched_policy(*policy, priority)),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Commands::Oom { pid, adj: None } => {
+            let handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            match handler.get_oom_score_adj(*pid) {
+                Ok(adj) => println!("{}", adj),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Commands::Oom { pid, adj: Some(adj) } => {
+            let handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            if cli.dry_run {
+                println!("Would set the OOM score adjustment of PID {} to {}", pid, adj);
+                return;
+            }
+            match handler.set_oom_score_adj(*pid, *adj) {
+                Ok(()) => info!("Set the OOM score adjustment of PID {} to {}", pid, adj),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Commands::Quarantine { pid: None } => {
+            let handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            match handler.quarantined() {
+                Ok(records) if records.is_empty() => println!("No processes are quarantined"),
+                Ok(records) => {
+                    println!("{:<8} {:<20} {:<6} Original CPUs", "PID", "Command", "Nice");
+                    for record in records {
+                        println!(
+                            "{:<8} {:<20} {:<6} {}",
+                            record.pid,
+                            record.command,
+                            record.nice,
+                            format_cpu_list(&record.cpus)
+                        );
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Commands::Quarantine { pid: Some(pid) } => {
+            let mut handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            handler.set_kill_policy(config.kill_policy.clone());
+            if cli.dry_run {
+                // The same policy check as the SIGSTOP quarantine starts with
+                match handler.describe_signal(*pid, Signal::SIGSTOP) {
+                    Ok(action) => {
+                        println!("Would {}, set its nice to 19 and pin it to one CPU", action)
+                    }
+                    Err(e) => warn!("{}", e),
+                }
+                return;
+            }
+            match handler.quarantine(*pid) {
+                Ok(record) => info!(
+                    "Quarantined PID {} ({}); release it with `unquarantine --pid {}`",
+                    pid, record.command, pid
+                ),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Commands::Unquarantine { pid } => {
+            let mut handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            handler.set_kill_policy(config.kill_policy.clone());
+            if cli.dry_run {
+                println!("Would release PID {} from quarantine", pid);
+                return;
+            }
+            match handler.unquarantine(*pid) {
+                Ok(record) => info!(
+                    "Released PID {} ({}): nice {}, CPUs {}",
+                    pid,
+                    record.command,
+                    record.nice,
+                    format_cpu_list(&record.cpus)
+                ),
+                Err(ProcessError::PermissionDenied { action: RESTORE_NICE, .. }) => {
+                    warn!(
+                        "Resumed PID {}, but its nice value stays at 19 (lowering it needs root)",
+                        pid
+                    );
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Commands::Completions { shell } => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_string();
+            clap_complete::generate(*shell, &mut command, name, &mut std::io::stdout());
+        }
+
+        Commands::Info { pid, raw: true } => {
+            let stats = match read_raw_stats(*pid) {
+                Ok(stats) => stats,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+            println!("/proc/{}/stat:", pid);
+            for (name, value) in &stats.stat {
+                println!("  {:<22} {}", name, value);
+            }
+            println!();
+            println!("/proc/{}/status:", pid);
+            for (name, value) in &stats.status {
+                println!("  {:<22} {}", name, value);
+            }
+        }
+
+        Commands::Info { pid, raw: false } => {
+            let mut handler = ProcessHandler::with_history_capacity(config.history_capacity);
+            let Some(process) = handler.process_info(*pid) else {
+                eprintln!("No process with PID {}", pid);
+                std::process::exit(1);
+            };
+
+            println!("{:<10} {}", "PID:", process.pid);
+            if let Some(ppid) = process.ppid {
+                println!("{:<10} {}", "Parent:", ppid);
+            }
+            println!("{:<10} {}", "User:", process.user);
+            let details = handler.get_process_details(process.pid).ok();
+            match details.as_ref().and_then(|details| details.started_at) {
+                Some(started_at) => println!(
+                    "{:<10} {} ({} ago)",
+                    "Started:",
+                    humantime::format_rfc3339_seconds(started_at),
+                    humantime::format_duration(Duration::from_secs(process.run_time_secs))
+                ),
+                None => println!("{:<10} {}s ago", "Started:", process.run_time_secs),
+            }
+            println!("{:<10} {}", "Threads:", process.threads);
+            println!("{:<10} {:.2}%", "CPU:", process.cpu_usage);
+            println!("{:<10} {}", "Memory:", format_bytes(process.memory_usage));
+            println!("{:<10} {}", "Peak:", format_bytes(process.peak_memory));
+            println!("{:<10} {}", "Swap:", format_bytes(process.swap_used));
+            println!(
+                "{:<10} {:.1}% over {}s",
+                "Life CPU:", process.efficiency * 100.0, process.run_time_secs
+            );
+            println!("{:<10} {}", "Command:", process.command);
+            if process.exe_deleted {
+                println!("{:<10} {} (deleted while running)", "Exe:", process.exe);
+            } else {
+                println!("{:<10} {}", "Exe:", process.exe);
+            }
+            if !process.cmdline.is_empty() {
+                println!("{:<10} {}", "Cmdline:", process.cmdline);
+            }
+            if let Some(details) = &details {
+                match &details.cwd {
+                    Ok(cwd) => println!("{:<10} {}", "Cwd:", cwd),
+                    Err(e) => println!("{:<10} unavailable ({})", "Cwd:", e),
+                }
+                match &details.open_fds {
+                    Ok(count) => println!("{:<10} {}", "Open fds:", count),
+                    Err(e) => println!("{:<10} unavailable ({})", "Open fds:", e),
+                }
+            }
+            match handler.listening_ports(process.pid) {
+                Ok(ports) if ports.is_empty() => println!("{:<10} none listening", "Ports:"),
+                Ok(ports) => {
+                    let ports: Vec<String> = ports.iter().map(u16::to_string).collect();
+                    println!("{:<10} {}", "Ports:", ports.join(", "));
+                }
+                Err(e) => println!("{:<10} unavailable ({})", "Ports:", e),
+            }
+            match handler.get_sched_policy(process.pid) {
+                Ok((policy, priority)) => {
+                    println!("{:<10} {}", "Sched:", format_sched_policy(policy, priority))
+                }
+                Err(e) => println!("{:<10} unavailable ({})", "Sched:", e),
+            }
+            match handler.get_oom_score_adj(process.pid) {
+                Ok(adj) => println!("{:<10} {}", "OOM adj:", adj),
+                Err(e) => println!("{:<10} unavailable ({})", "OOM adj:", e),
+            }
+            match handler.cgroup_limits(process.pid) {
+                Some(limits) => {
+                    println!("{:<10} {}", "Cgroup:", limits.path);
+                    println!("{:<10} {}", "  Memory:", format_cgroup_memory(&limits));
+                    if let Some(cpus) = limits.cpu_limit {
+                        println!("{:<10} limited to {:.2} cores", "  CPU:", cpus);
+                    }
+                }
+                None => println!("{:<10} unavailable (needs cgroup v2)", "Cgroup:"),
+            }
+
+            if process.namespaces.is_empty() {
+                println!("Namespaces: unavailable (permission denied or process exited)");
+                return;
+            }
+
+            let host = handler.host_namespaces();
+            let mut kinds: Vec<_> = process.namespaces.keys().collect();
+            kinds.sort();
+            let isolated: Vec<&str> = kinds
+                .iter()
+                .filter(|kind| host.get(kind.as_str()) != process.namespaces.get(kind.as_str()))
+                .map(|kind| kind.as_str())
+                .collect();
+
+            if isolated.is_empty() {
+                println!("Namespaces: host");
+            } else {
+                println!("Namespaces: isolated ({})", isolated.join(", "));
+            }
+            for kind in kinds {
+                let marker = if isolated.contains(&kind.as_str()) { "" } else { " (host)" };
+                println!("  {:<10} {}{}", kind, process.namespaces[kind], marker);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn pid_file_of_another_program_is_stale() {
+        let mut sleep = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let other = sleep.id() as i32;
+        assert!(!is_running_instance(other));
+        sleep.kill().unwrap();
+        sleep.wait().unwrap();
+        // Nor once it has exited
+        assert!(!is_running_instance(other));
+        assert!(!is_running_instance(std::process::id() as i32));
+        assert!(!is_running_instance(0));
+    }
+}
@@ -0,0 +1,830 @@
+// src/config.rs
+
+use clap::{Args, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::data_structures::{CommandDisplay, SortField, SortOrder};
+use crate::filter::FilterPreset;
+use crate::format::ByteUnits;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeChoice {
+    Light,
+    Dark,
+}
+
+/// Spacing and font size of the GUI process list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RowDensity {
+    Comfortable,
+    Compact,
+}
+
+impl RowDensity {
+    /// Padding inside each row, and spacing between rows
+    pub fn row_padding(&self) -> u16 {
+        match self {
+            RowDensity::Comfortable => 5,
+            RowDensity::Compact => 1,
+        }
+    }
+
+    pub fn text_size(&self) -> f32 {
+        match self {
+            RowDensity::Comfortable => 16.0,
+            RowDensity::Compact => 13.0,
+        }
+    }
+}
+
+/// Restricts which processes may be signalled, e.g. on shared or kiosk machines.
+///
+/// Each entry is a command name (`firefox`), a PID (`1234`) or an inclusive PID
+/// range (`1-1000`). Empty lists impose no restriction. A name matches the command
+/// name or the basename of the executable or of argv[0], since the kernel cuts command
+/// names to 15 bytes (`chromium-browse`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KillPolicy {
+    /// When non-empty, only matching processes may be killed
+    pub allow: Vec<String>,
+    /// Matching processes may never be killed; takes precedence over `allow`
+    pub deny: Vec<String>,
+}
+
+impl KillPolicy {
+    /// Whether the policy lets `pid` be signalled; `names` are the ones it goes by,
+    /// as `ProcessInfo::names` gives them.
+    pub fn permits<S: AsRef<str>>(&self, pid: i32, names: &[S]) -> bool {
+        let matches = |entry: &String| policy_entry_matches(entry, pid, names);
+        !self.deny.iter().any(matches) && (self.allow.is_empty() || self.allow.iter().any(matches))
+    }
+}
+
+fn policy_entry_matches<S: AsRef<str>>(entry: &str, pid: i32, names: &[S]) -> bool {
+    let entry = entry.trim();
+    if let Ok(entry_pid) = entry.parse::<i32>() {
+        return entry_pid == pid;
+    }
+    // Names like `gnome-shell` contain '-' too, so only numeric bounds make a range
+    if let Some((start, end)) = entry.split_once('-') {
+        if let (Ok(start), Ok(end)) = (start.trim().parse::<i32>(), end.trim().parse::<i32>()) {
+            return (start..=end).contains(&pid);
+        }
+    }
+    names.iter().any(|name| name.as_ref() == entry)
+}
+
+/// Writes the keys that differ between `saved` and `updated` into `document`, going
+/// into tables such as `presets` so that unchanged entries keep their text.
+fn merge_changes(
+    document: &mut toml_edit::Table,
+    saved: &toml::Table,
+    updated: &toml::Table,
+) -> Result<(), String> {
+    for (key, value) in updated {
+        let old = saved.get(key);
+        if old == Some(value) {
+            continue;
+        }
+        if let (Some(toml::Value::Table(old)), toml::Value::Table(new)) = (old, value) {
+            if let Some(table) = document.get_mut(key).and_then(|item| item.as_table_mut()) {
+                merge_changes(table, old, new)?;
+                continue;
+            }
+        }
+        let mut item = to_item(key, value)?;
+        match document.get_mut(key) {
+            Some(old) => {
+                // Keeps a comment after the old value on the same line
+                if let (Some(old), Some(new)) = (old.as_value(), item.as_value_mut()) {
+                    *new.decor_mut() = old.decor().clone();
+                }
+                *old = item;
+            }
+            None => {
+                document.insert(key, item);
+            }
+        }
+    }
+    for key in saved.keys().filter(|key| !updated.contains_key(*key)) {
+        document.remove(key);
+    }
+    Ok(())
+}
+
+fn to_table(config: &Config) -> Result<toml::Table, String> {
+    toml::Table::try_from(config).map_err(|e| format!("Failed to serialize config: {}", e))
+}
+
+/// A single config key as written on its own, so tables get `[section]` headers
+/// instead of being inlined.
+fn to_item(key: &str, value: &toml::Value) -> Result<toml_edit::Item, String> {
+    let mut table = toml::Table::new();
+    table.insert(key.to_string(), value.clone());
+    let text = toml::to_string(&table)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let mut document: toml_edit::DocumentMut =
+        text.parse().map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let mut item = document
+        .remove(key)
+        .ok_or_else(|| format!("Failed to serialize config key {}", key))?;
+    if let Some(table) = item.as_table_mut() {
+        space_out(table);
+    }
+    Ok(item)
+}
+
+/// Puts a blank line before each `[section]` header in `table`.
+fn space_out(table: &mut toml_edit::Table) {
+    if !table.is_implicit() {
+        table.decor_mut().set_prefix("\n");
+    }
+    for (_, item) in table.iter_mut() {
+        if let Some(table) = item.as_table_mut() {
+            space_out(table);
+        }
+    }
+}
+
+/// Settings shared by the GUI and the CLI.
+///
+/// Values are resolved in three layers: built-in defaults, then the TOML
+/// config file, then any flags given on the command line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// How often process data is refreshed, in milliseconds
+    pub refresh_interval_ms: u64,
+    /// Seconds without input, or with the window unfocused, before the GUI switches to
+    /// `idle_refresh_interval_ms`; 0 never slows down
+    pub idle_timeout_secs: u64,
+    /// Refresh interval while idle, in milliseconds
+    pub idle_refresh_interval_ms: u64,
+    /// CPU usage of the GUI itself, in percent of one core, above which it refreshes half
+    /// as often and draws charts with half the points; 0 never does
+    pub self_cpu_limit: f32,
+    /// How often global CPU usage is sampled between refreshes, in milliseconds, so the
+    /// CPU chart shows spikes shorter than a refresh; 0 turns it off
+    pub cpu_fine_sample_ms: u64,
+    /// Percentage change between two samples that is drawn as a spike
+    pub spike_threshold: f32,
+    /// Global CPU usage (%) above which an alert is shown
+    pub cpu_alert_threshold: f32,
+    /// Global memory usage (%) above which an alert is shown
+    pub memory_alert_threshold: f32,
+    /// CPU temperature (°C) above which the reading is shown in red
+    pub temperature_alert_threshold: f32,
+    pub default_sort: SortField,
+    pub default_order: SortOrder,
+    /// Sort column and order the GUI opens with, saved when its window closes;
+    /// `default_sort` and `default_order` until then
+    pub gui_sort: Option<SortField>,
+    pub gui_order: Option<SortOrder>,
+    /// Columns the GUI sorts biggest first when switching to them; the others start
+    /// ascending
+    pub descending_fields: Vec<SortField>,
+    pub theme: ThemeChoice,
+    /// How long after a kill the GUI checks whether the process is gone, in milliseconds
+    pub kill_check_delay_ms: u64,
+    /// Show each process's CPU usage averaged over its last `cpu_average_window` refreshes
+    pub smooth_cpu: bool,
+    pub cpu_average_window: usize,
+    /// Number of samples kept for the CPU and memory charts
+    pub history_capacity: usize,
+    /// Most points a chart draws; longer histories are grouped into buckets
+    pub chart_render_budget: usize,
+    /// Samples written from before an alert by `record --on-alert`
+    pub record_pre_roll: usize,
+    /// Samples written after an alert clears by `record --on-alert`
+    pub record_post_roll: usize,
+    /// Alerts don't trigger actions such as `record --on-alert`, which only log what
+    /// they would have done, e.g. during a deployment
+    pub pause_automation: bool,
+    /// Age in seconds at which a process's PID has faded from the "just started"
+    /// highlight to the normal text color; 0 turns the highlight off
+    pub age_gradient_secs: u64,
+    /// Show the command as the executable name or its full path
+    pub command_display: CommandDisplay,
+    pub row_density: RowDensity,
+    /// Byte counts in KiB/MiB/GiB (binary) or KB/MB/GB (decimal)
+    pub units: ByteUnits,
+    /// Bold colors, thicker chart lines, larger text and bordered rows in the GUI
+    pub high_contrast: bool,
+    /// Processes left out of the GUI list and `list` unless asked for: command names,
+    /// PIDs or PID ranges, like the kill policy
+    pub ignore: Vec<String>,
+    pub kill_policy: KillPolicy,
+    /// Named filter presets, saved from the GUI or written by hand
+    pub presets: BTreeMap<String, FilterPreset>,
+    /// File these settings were read from, and where GUI changes are saved
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            refresh_interval_ms: 1000,
+            idle_timeout_secs: 120,
+            idle_refresh_interval_ms: 10_000,
+            self_cpu_limit: 15.0,
+            cpu_fine_sample_ms: 0,
+            spike_threshold: 20.0,
+            cpu_alert_threshold: 90.0,
+            memory_alert_threshold: 90.0,
+            temperature_alert_threshold: 85.0,
+            default_sort: SortField::PID,
+            default_order: SortOrder::Ascending,
+            gui_sort: None,
+            gui_order: None,
+            descending_fields: vec![
+                SortField::CPU,
+                SortField::ObservedCpu,
+                SortField::Memory,
+                SortField::MemoryPercent,
+                SortField::PeakMemory,
+                SortField::IO,
+                SortField::DiskRead,
+                SortField::DiskWrite,
+                SortField::Threads,
+            ],
+            theme: ThemeChoice::Light,
+            kill_check_delay_ms: 1000,
+            smooth_cpu: false,
+            cpu_average_window: 5,
+            history_capacity: 100,
+            chart_render_budget: 200,
+            record_pre_roll: 30,
+            record_post_roll: 30,
+            pause_automation: false,
+            age_gradient_secs: 300,
+            command_display: CommandDisplay::Name,
+            row_density: RowDensity::Comfortable,
+            units: ByteUnits::Binary,
+            high_contrast: false,
+            ignore: Vec::new(),
+            kill_policy: KillPolicy::default(),
+            presets: BTreeMap::new(),
+            path: None,
+        }
+    }
+}
+
+/// The settings that can be changed from the GUI.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GuiSettings {
+    pub sort: SortField,
+    pub order: SortOrder,
+    pub command_display: CommandDisplay,
+    pub row_density: RowDensity,
+    pub high_contrast: bool,
+    pub smooth_cpu: bool,
+}
+
+/// Command-line flags that take precedence over the config file.
+#[derive(Args, Debug, Default)]
+pub struct ConfigOverrides {
+    /// Path to the config file (default: ~/.config/rust_task_manager/config.toml)
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Refresh interval in milliseconds
+    #[arg(long, global = true)]
+    pub refresh_interval: Option<u64>,
+
+    /// Sample global CPU this often between refreshes, in milliseconds; 0 turns it off
+    #[arg(long, global = true)]
+    pub cpu_fine_sample: Option<u64>,
+
+    /// Percentage change between samples highlighted as a spike
+    #[arg(long, global = true)]
+    pub spike_threshold: Option<f32>,
+
+    /// Global CPU usage (%) that triggers an alert
+    #[arg(long, global = true)]
+    pub cpu_alert: Option<f32>,
+
+    /// Global memory usage (%) that triggers an alert
+    #[arg(long, global = true)]
+    pub memory_alert: Option<f32>,
+
+    /// CPU temperature (°C) highlighted as too hot
+    #[arg(long, global = true)]
+    pub temperature_alert: Option<f32>,
+
+    /// GUI theme
+    #[arg(long, global = true)]
+    pub theme: Option<ThemeChoice>,
+
+    /// Refreshes averaged when smoothing per-process CPU usage
+    #[arg(long, global = true)]
+    pub cpu_average_window: Option<usize>,
+
+    /// Number of samples kept for the usage charts
+    #[arg(long, global = true)]
+    pub history_capacity: Option<usize>,
+
+    /// Show commands as executable name or full path
+    #[arg(long, global = true)]
+    pub command_display: Option<CommandDisplay>,
+
+    /// Spacing of the GUI process list
+    #[arg(long, global = true)]
+    pub row_density: Option<RowDensity>,
+
+    /// Show sizes in binary (KiB, MiB) or decimal (KB, MB) units
+    #[arg(long, global = true)]
+    pub units: Option<ByteUnits>,
+
+    /// Use the high-contrast GUI colors and sizes
+    #[arg(long, global = true)]
+    pub high_contrast: bool,
+
+    /// Don't let alerts trigger actions such as `record --on-alert`; they only log
+    /// what they would have done
+    #[arg(long, global = true)]
+    pub pause_automation: bool,
+}
+
+impl Config {
+    /// Default location of the config file, `~/.config/rust_task_manager/config.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+        Some(base.join("rust_task_manager").join("config.toml"))
+    }
+
+    /// Loads the config file at `path`, or at the default location when `None`.
+    ///
+    /// A missing default file is not an error and yields the built-in defaults,
+    /// but an explicitly requested file must exist.
+    pub fn load(path: Option<&Path>) -> Result<Config, String> {
+        match path {
+            Some(path) => Self::read(path, false),
+            None => match Self::default_path() {
+                Some(path) => Self::read(&path, true),
+                None => Ok(Config::default()),
+            },
+        }
+    }
+
+    fn read(path: &Path, missing_ok: bool) -> Result<Config, String> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && missing_ok => {
+                return Ok(Config { path: Some(path.to_path_buf()), ..Config::default() });
+            }
+            Err(e) => {
+                return Err(format!("Failed to read config file {}: {}", path.display(), e));
+            }
+        };
+
+        let mut config = Self::parse(&contents, path)?;
+        config.path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    fn parse(contents: &str, path: &Path) -> Result<Config, String> {
+        let config: Config = toml::from_str(contents)
+            .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))?;
+        config
+            .validate()
+            .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))?;
+        Ok(config)
+    }
+
+    /// The GUI settings the window opens with.
+    pub fn gui_settings(&self) -> GuiSettings {
+        GuiSettings {
+            sort: self.gui_sort.unwrap_or(self.default_sort),
+            order: self.gui_order.unwrap_or(self.default_order),
+            command_display: self.command_display,
+            row_density: self.row_density,
+            high_contrast: self.high_contrast,
+            smooth_cpu: self.smooth_cpu,
+        }
+    }
+
+    /// Saves the GUI settings that differ between `opened_with` and `current` to the
+    /// file they were loaded from.
+    ///
+    /// Settings the user didn't change in the GUI aren't written, so a one-off flag such
+    /// as `--high-contrast` doesn't become permanent. The sort goes to `gui_sort` and
+    /// `gui_order`, leaving the default of `list` and `export` alone.
+    pub fn save_gui_settings(
+        &self,
+        opened_with: &GuiSettings,
+        current: &GuiSettings,
+    ) -> Result<(), String> {
+        if opened_with == current {
+            return Ok(());
+        }
+        self.write_back(|saved| {
+            if (opened_with.sort, opened_with.order) != (current.sort, current.order) {
+                saved.gui_sort = Some(current.sort);
+                saved.gui_order = Some(current.order);
+            }
+            if opened_with.command_display != current.command_display {
+                saved.command_display = current.command_display;
+            }
+            if opened_with.row_density != current.row_density {
+                saved.row_density = current.row_density;
+            }
+            if opened_with.high_contrast != current.high_contrast {
+                saved.high_contrast = current.high_contrast;
+            }
+            if opened_with.smooth_cpu != current.smooth_cpu {
+                saved.smooth_cpu = current.smooth_cpu;
+            }
+        })
+    }
+
+    /// Saves the filter presets to the file they were loaded from, leaving every
+    /// other key as it is in the file.
+    pub fn save_presets(&self) -> Result<(), String> {
+        self.write_back(|saved| saved.presets = self.presets.clone())
+    }
+
+    /// Saves the ignore list to the file it was loaded from, leaving every other key
+    /// as it is in the file.
+    pub fn save_ignore_list(&self) -> Result<(), String> {
+        self.write_back(|saved| saved.ignore = self.ignore.clone())
+    }
+
+    /// Whether a process is on the ignore list.
+    pub fn is_ignored(&self, pid: i32, command: &str) -> bool {
+        self.ignore.iter().any(|entry| policy_entry_matches(entry, pid, &[command]))
+    }
+
+    /// Re-reads the config file, applies `update` and writes back the keys it changed.
+    ///
+    /// Only those keys are rewritten; comments, layout and every other key stay as
+    /// they are in the file.
+    fn write_back(&self, update: impl FnOnce(&mut Config)) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Err("No config file location (HOME is not set)".into());
+        };
+
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(format!("Failed to read config file {}: {}", path.display(), e));
+            }
+        };
+        let saved = Self::parse(&contents, path)?;
+        let mut updated = saved.clone();
+        update(&mut updated);
+
+        let mut document: toml_edit::DocumentMut = contents
+            .parse()
+            .map_err(|e| format!("Invalid config file {}: {}", path.display(), e))?;
+        merge_changes(&mut document, &to_table(&saved)?, &to_table(&updated)?)?;
+
+        let contents = document.to_string();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        fs::write(path, contents)
+            .map_err(|e| format!("Failed to write config file {}: {}", path.display(), e))
+    }
+
+    /// Loads the config file and applies the command-line overrides on top.
+    pub fn resolve(overrides: &ConfigOverrides) -> Result<Config, String> {
+        let mut config = Self::load(overrides.config.as_deref())?;
+        config.apply_overrides(overrides);
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn apply_overrides(&mut self, overrides: &ConfigOverrides) {
+        if let Some(interval) = overrides.refresh_interval {
+            self.refresh_interval_ms = interval;
+        }
+        if let Some(interval) = overrides.cpu_fine_sample {
+            self.cpu_fine_sample_ms = interval;
+        }
+        if let Some(threshold) = overrides.spike_threshold {
+            self.spike_threshold = threshold;
+        }
+        if let Some(threshold) = overrides.cpu_alert {
+            self.cpu_alert_threshold = threshold;
+        }
+        if let Some(threshold) = overrides.memory_alert {
+            self.memory_alert_threshold = threshold;
+        }
+        if let Some(threshold) = overrides.temperature_alert {
+            self.temperature_alert_threshold = threshold;
+        }
+        if let Some(theme) = overrides.theme {
+            self.theme = theme;
+        }
+        if let Some(window) = overrides.cpu_average_window {
+            self.cpu_average_window = window;
+        }
+        if let Some(capacity) = overrides.history_capacity {
+            self.history_capacity = capacity;
+        }
+        if let Some(mode) = overrides.command_display {
+            self.command_display = mode;
+        }
+        if let Some(density) = overrides.row_density {
+            self.row_density = density;
+        }
+        if let Some(units) = overrides.units {
+            self.units = units;
+        }
+        if overrides.high_contrast {
+            self.high_contrast = true;
+        }
+        if overrides.pause_automation {
+            self.pause_automation = true;
+        }
+    }
+
+    /// Order a column is sorted in when switching to it.
+    pub fn initial_order(&self, field: SortField) -> SortOrder {
+        if self.descending_fields.contains(&field) {
+            SortOrder::Descending
+        } else {
+            SortOrder::Ascending
+        }
+    }
+
+    /// Human-readable alerts for global CPU and memory usage above their thresholds.
+    pub fn alerts(&self, cpu_usage: Option<f32>, memory_usage: Option<f32>) -> Vec<String> {
+        let mut alerts = Vec::new();
+        if let Some(cpu) = cpu_usage.filter(|&cpu| cpu > self.cpu_alert_threshold) {
+            alerts.push(format!(
+                "CPU usage {:.1}% exceeds {:.0}%",
+                cpu, self.cpu_alert_threshold
+            ));
+        }
+        if let Some(memory) = memory_usage.filter(|&memory| memory > self.memory_alert_threshold) {
+            alerts.push(format!(
+                "Memory usage {:.1}% exceeds {:.0}%",
+                memory, self.memory_alert_threshold
+            ));
+        }
+        alerts
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.refresh_interval_ms == 0 {
+            return Err("refresh_interval_ms must be greater than 0".into());
+        }
+        if self.idle_refresh_interval_ms == 0 {
+            return Err("idle_refresh_interval_ms must be greater than 0".into());
+        }
+        // The kernel counts CPU time in 10 ms ticks, so shorter samples are mostly noise
+        if (1..50).contains(&self.cpu_fine_sample_ms) {
+            return Err("cpu_fine_sample_ms must be 0 (off) or at least 50".into());
+        }
+        if self.self_cpu_limit < 0.0 {
+            return Err("self_cpu_limit must be 0 (off) or positive".into());
+        }
+        if self.cpu_average_window == 0 {
+            return Err("cpu_average_window must be greater than 0".into());
+        }
+        if self.history_capacity < 2 {
+            return Err("history_capacity must be at least 2".into());
+        }
+        if self.chart_render_budget < 2 {
+            return Err("chart_render_budget must be at least 2".into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structures::ProcessInfo;
+
+    /// A config file named after the test, holding `contents`.
+    fn config_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("ltm-config-{}-{}.toml", name, std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn policy(allow: &[&str], deny: &[&str]) -> KillPolicy {
+        KillPolicy {
+            allow: allow.iter().map(|entry| entry.to_string()).collect(),
+            deny: deny.iter().map(|entry| entry.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn flags_override_the_file_which_overrides_the_defaults() {
+        let path = config_file(
+            "layers",
+            "refresh_interval_ms = 2000\ncpu_alert_threshold = 80.0\ntheme = \"dark\"\n",
+        );
+        let overrides = ConfigOverrides {
+            config: Some(path.clone()),
+            refresh_interval: Some(500),
+            ..Default::default()
+        };
+        let config = Config::resolve(&overrides).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.refresh_interval_ms, 500);
+        assert_eq!(config.cpu_alert_threshold, 80.0);
+        assert_eq!(config.theme, ThemeChoice::Dark);
+        assert_eq!(config.memory_alert_threshold, 90.0);
+        assert_eq!(config.path, Some(path));
+    }
+
+    #[test]
+    fn only_a_missing_default_file_is_fine() {
+        let path = std::env::temp_dir().join("ltm-config-does-not-exist.toml");
+        assert!(Config::load(Some(&path)).unwrap_err().starts_with("Failed to read"));
+
+        let config = Config::read(&path, true).unwrap();
+        assert_eq!(config.refresh_interval_ms, Config::default().refresh_interval_ms);
+        assert_eq!(config.path, Some(path));
+    }
+
+    #[test]
+    fn invalid_files_are_rejected() {
+        for (name, contents, error) in [
+            ("unknown", "refresh_interval = 5\n", "unknown field"),
+            ("type", "refresh_interval_ms = \"fast\"\n", "invalid type"),
+            ("zero", "refresh_interval_ms = 0\n", "must be greater than 0"),
+        ] {
+            let path = config_file(name, contents);
+            let result = Config::load(Some(&path));
+            fs::remove_file(&path).unwrap();
+            let e = result.unwrap_err();
+            assert!(e.starts_with("Invalid config file") && e.contains(error), "{}", e);
+        }
+    }
+
+    #[test]
+    fn validate_checks_each_limit() {
+        assert!(Config::default().validate().is_ok());
+        let defaults = Config::default;
+        for (key, config) in [
+            ("refresh_interval_ms", Config { refresh_interval_ms: 0, ..defaults() }),
+            ("idle_refresh_interval_ms", Config { idle_refresh_interval_ms: 0, ..defaults() }),
+            ("cpu_fine_sample_ms", Config { cpu_fine_sample_ms: 49, ..defaults() }),
+            ("self_cpu_limit", Config { self_cpu_limit: -1.0, ..defaults() }),
+            ("cpu_average_window", Config { cpu_average_window: 0, ..defaults() }),
+            ("history_capacity", Config { history_capacity: 1, ..defaults() }),
+            ("chart_render_budget", Config { chart_render_budget: 1, ..defaults() }),
+        ] {
+            assert!(config.validate().unwrap_err().starts_with(key));
+        }
+
+        let config = Config { cpu_fine_sample_ms: 50, self_cpu_limit: 0.0, ..Config::default() };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn resolve_rejects_invalid_flags() {
+        let path = config_file("bad-flag", "");
+        let overrides = ConfigOverrides {
+            config: Some(path.clone()),
+            history_capacity: Some(1),
+            ..Default::default()
+        };
+        let result = Config::resolve(&overrides);
+        fs::remove_file(&path).unwrap();
+        assert!(result.unwrap_err().starts_with("history_capacity"));
+    }
+
+    #[test]
+    fn apply_overrides_changes_only_the_given_flags() {
+        let mut config = Config { high_contrast: true, ..Config::default() };
+        config.apply_overrides(&ConfigOverrides {
+            cpu_alert: Some(70.0),
+            theme: Some(ThemeChoice::Dark),
+            row_density: Some(RowDensity::Compact),
+            units: Some(ByteUnits::Decimal),
+            pause_automation: true,
+            ..Default::default()
+        });
+
+        assert_eq!(config.cpu_alert_threshold, 70.0);
+        assert_eq!(config.theme, ThemeChoice::Dark);
+        assert_eq!(config.row_density, RowDensity::Compact);
+        assert_eq!(config.units, ByteUnits::Decimal);
+        assert!(config.pause_automation);
+        // A flag that wasn't given doesn't turn a setting off
+        assert!(config.high_contrast);
+        let defaults = Config::default();
+        assert_eq!(config.refresh_interval_ms, defaults.refresh_interval_ms);
+        assert_eq!(config.memory_alert_threshold, defaults.memory_alert_threshold);
+        assert_eq!(config.command_display, defaults.command_display);
+    }
+
+    #[test]
+    fn empty_policy_permits_everything() {
+        assert!(KillPolicy::default().permits(1234, &["anything"]));
+    }
+
+    #[test]
+    fn policy_matches_pids_and_ranges() {
+        let policy = policy(&[], &["1", "100-200", " 300 - 310 "]);
+        assert!(!policy.permits(1, &["init"]));
+        assert!(!policy.permits(100, &["a"]));
+        assert!(!policy.permits(200, &["a"]));
+        assert!(policy.permits(201, &["a"]));
+        assert!(!policy.permits(305, &["a"]));
+        assert!(policy.permits(11, &["a"]));
+    }
+
+    #[test]
+    fn policy_matches_names_and_deny_wins() {
+        let policy = policy(&["firefox", "gnome-shell"], &["firefox"]);
+        assert!(policy.permits(50, &["gnome-shell"]));
+        assert!(!policy.permits(50, &["firefox"]));
+        // Not on the allow list
+        assert!(!policy.permits(50, &["bash"]));
+        // A name containing '-' isn't read as a range
+        assert!(!policy.permits(50, &["gnome"]));
+    }
+
+    #[test]
+    fn policy_matches_long_names_by_executable() {
+        // The kernel cuts the command name to 15 bytes
+        let names = ["chromium-browse", "chromium-browser"];
+        assert!(!policy(&[], &["chromium-browser"]).permits(50, &names));
+        assert!(policy(&["chromium-browser"], &[]).permits(50, &names));
+
+        let session = ProcessInfo {
+            command: "gnome-session-b".to_string(),
+            exe: "/usr/libexec/gnome-session-binary".to_string(),
+            cmdline: "/usr/libexec/gnome-session-binary --session=ubuntu".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(session.names(), ["gnome-session-b", "gnome-session-binary"]);
+        assert!(!policy(&[], &["gnome-session-binary"]).permits(50, &session.names()));
+    }
+
+    #[test]
+    fn saving_keeps_comments_and_other_keys() {
+        let original = "# My settings\n\
+            refresh_interval_ms = 2000 # slow machine\n\
+            # Never kill these\n\
+            ignore = [\"init\"]  # just init\n\
+            \n\
+            [presets.busy]\n\
+            query = \"node\"\n\
+            sort = \"cpu\"\n\
+            order = \"desc\"\n";
+        let path = config_file("comments", original);
+
+        let mut config = Config::load(Some(&path)).unwrap();
+        config.ignore.push("sshd".to_string());
+        config.save_ignore_list().unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            written,
+            original.replace("[\"init\"]", "[\"init\", \"sshd\"]"),
+            "only the ignore list changes"
+        );
+        assert_eq!(Config::parse(&written, &path).unwrap().ignore, ["init", "sshd"]);
+    }
+
+    #[test]
+    fn gui_saves_only_what_was_changed_in_it() {
+        let path = config_file("gui", "default_sort = \"memory\"\nsmooth_cpu = true\n");
+
+        let mut config = Config::load(Some(&path)).unwrap();
+        config.apply_overrides(&ConfigOverrides { high_contrast: true, ..Default::default() });
+        let opened_with = config.gui_settings();
+        assert_eq!(opened_with.sort, SortField::Memory);
+
+        // Nothing changed: the file isn't touched
+        config.save_gui_settings(&opened_with, &opened_with).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().starts_with("default_sort"));
+
+        let current = GuiSettings {
+            sort: SortField::CPU,
+            order: SortOrder::Descending,
+            row_density: RowDensity::Compact,
+            ..opened_with
+        };
+        config.save_gui_settings(&opened_with, &current).unwrap();
+        let saved = Config::load(Some(&path)).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(saved.gui_settings(), GuiSettings { high_contrast: false, ..current });
+        assert_eq!(saved.default_sort, SortField::Memory);
+        assert_eq!(saved.default_order, SortOrder::Ascending);
+    }
+}
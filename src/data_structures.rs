@@ -1,10 +1,582 @@
 // src/data_structures.rs
 
-#[derive(Debug, Clone)] // Added Debug here
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::SystemTime;
+
+/// Version of the machine-readable process list format.
+///
+/// Bump this whenever the serialized form of `ProcessInfo` changes. Fields added
+/// since version 1 are `#[serde(default)]`, so older documents still parse.
+pub const PROCESS_LIST_VERSION: u32 = 2;
+
+/// Top-level envelope for every JSON process list the tool emits, so consumers
+/// can check `version` before parsing `processes`.
+#[derive(Debug, Serialize)]
+pub struct ProcessList<'a> {
+    pub version: u32,
+    pub processes: &'a [ProcessInfo],
+}
+
+impl<'a> ProcessList<'a> {
+    pub fn new(processes: &'a [ProcessInfo]) -> Self {
+        Self { version: PROCESS_LIST_VERSION, processes }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)] // Added Debug here
 pub struct ProcessInfo {
     pub pid: i32,
+    /// Parent PID; `None` for PID 1 and kernel threads without a parent
+    #[serde(default)]
+    pub ppid: Option<i32>,
+    /// Original parent PID when the process has since been reparented (to init or a
+    /// subreaper) because that parent exited. Only known for parents seen this session.
+    #[serde(default)]
+    pub orphaned_from: Option<i32>,
     pub user: String,
+    /// Real user ID of the owner; `None` when it can't be read
+    pub uid: Option<u32>,
     pub cpu_usage: f32,
+    /// Mean CPU usage over every refresh since this PID was first seen. Equal to
+    /// `cpu_usage` on the first one.
+    #[serde(default)]
+    pub observed_avg_cpu: f32,
+    /// Number of CPUs the process may run on, from its affinity mask; 0 when it
+    /// can't be read
+    #[serde(default)]
+    pub allowed_cpus: usize,
+    /// Resident memory in bytes, as sysinfo reports it; formatted only for display,
+    /// so sorting and filtering use the exact value
     pub memory_usage: u64,
+    /// Resident memory as a percentage of total system memory (`top`'s MEM%)
+    #[serde(default)]
+    pub memory_percent: f32,
+    /// Highest resident memory since the process started, in bytes (`VmHWM`), so one
+    /// that ballooned and shrank back still shows it. Kept by the kernel per process,
+    /// so a new process that reuses a PID starts over. Never below `memory_usage`.
+    #[serde(default)]
+    pub peak_memory: u64,
+    /// Virtual memory size in bytes
+    pub virtual_memory: u64,
+    /// Bytes of this process's memory that are swapped out (`VmSwap`); 0 for kernel
+    /// threads and when it can't be read
+    #[serde(default)]
+    pub swap_used: u64,
+    /// Scheduler state, e.g. "Runnable", "Sleeping", "Zombie"
+    pub status: String,
+    /// Process name as reported by the OS (usually the executable's basename)
     pub command: String,
+    /// Full path of the executable; empty when it can't be read
+    pub exe: String,
+    /// The executable was deleted or replaced (e.g. by an upgrade) while the process
+    /// kept running. Always false when the executable link can't be read.
+    #[serde(default)]
+    pub exe_deleted: bool,
+    /// Command line with arguments, space-separated; empty for kernel threads
+    #[serde(default)]
+    pub cmdline: String,
+    pub threads: u32,
+    /// Scheduling niceness from -20 (highest priority) to 19; 0 is the default
+    #[serde(default)]
+    pub nice: i32,
+    /// Seconds since the process started
+    #[serde(default)]
+    pub run_time_secs: u64,
+    /// When the process started, in clock ticks after boot (`starttime` in
+    /// `/proc/<pid>/stat`). Tells a process apart from an earlier one with the same PID.
+    #[serde(default)]
+    pub start_time: u64,
+    /// CPU time (user + system) over wall-clock age; 1.0 means one core busy for the
+    /// process's whole life. 0 for processes that started less than a second ago.
+    #[serde(default)]
+    pub efficiency: f32,
+    /// Rough power draw in watts: the CPU package power split by share of CPU usage.
+    /// `None` when RAPL powercap counters aren't available.
+    #[serde(default)]
+    pub power_watts: Option<f32>,
+    /// Bytes read from disk since the previous refresh (since start on the first one)
+    pub disk_read: u64,
+    /// Bytes written to disk since the previous refresh (since start on the first one)
+    pub disk_write: u64,
+    /// Bytes received by the process's network namespace since the previous refresh.
+    /// Per-process traffic isn't exposed by the kernel, so every process in a namespace
+    /// shares its total; `None` in the host namespace, whose total says nothing about
+    /// any single process, and when the namespace can't be read.
+    #[serde(default)]
+    pub net_rx: Option<u64>,
+    /// Bytes sent by the process's network namespace since the previous refresh; see `net_rx`
+    #[serde(default)]
+    pub net_tx: Option<u64>,
+    /// Namespace kind (`pid`, `net`, `mnt`, ...) to its `/proc/<pid>/ns` link target,
+    /// e.g. `"net" -> "net:[4026531840]"`. Empty when the links can't be read.
+    pub namespaces: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskInfo {
+    pub name: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub total_space: u64,
+    pub available_space: u64,
+    pub usage_percent: f32,
+}
+
+/// Traffic of one network interface, from `/proc/net/dev`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterfaceTraffic {
+    pub name: String,
+    /// Bytes received since the interface came up
+    pub rx_total: u64,
+    /// Bytes sent since the interface came up
+    pub tx_total: u64,
+    /// Bytes received per second between the last two samples
+    pub rx_per_sec: u64,
+    /// Bytes sent per second between the last two samples
+    pub tx_per_sec: u64,
+}
+
+/// Identifies one process across refreshes. PIDs are reused after a process exits,
+/// so per-process state is keyed on the PID together with the start time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProcessKey {
+    pub pid: i32,
+    pub start_time: u64,
+}
+
+impl ProcessInfo {
+    pub fn key(&self) -> ProcessKey {
+        ProcessKey { pid: self.pid, start_time: self.start_time }
+    }
+
+    /// CPU usage relative to the CPUs the process may run on, so a process pinned to
+    /// two cores that keeps both busy reads 100%. `None` when the affinity is unknown.
+    pub fn cpu_usage_of_allowed(&self) -> Option<f32> {
+        (self.allowed_cpus > 0).then(|| self.cpu_usage / self.allowed_cpus as f32)
+    }
+
+    /// Names the process goes by, for matching it by name: the command name, which the
+    /// kernel cuts to 15 bytes, then the basenames of the executable and of argv[0]
+    /// when they are known.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = vec![self.command.as_str()];
+        let exe = self.exe.strip_suffix(" (deleted)").unwrap_or(&self.exe);
+        let argv0 = self.cmdline.split(' ').next().unwrap_or_default();
+        for path in [exe, argv0] {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            if !name.is_empty() && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// The command as it should be shown for the given display mode.
+    pub fn display_command(&self, mode: CommandDisplay) -> &str {
+        match mode {
+            // Some platforms report a full path as the name; keep only the last component.
+            // Kernel threads like `kworker/0:1` also contain '/', so only strip real paths.
+            CommandDisplay::Name if self.command.starts_with('/') => {
+                self.command.rsplit('/').next().unwrap_or(&self.command)
+            }
+            CommandDisplay::Name => &self.command,
+            CommandDisplay::Path if !self.exe.is_empty() => &self.exe,
+            CommandDisplay::Path => &self.command,
+        }
+    }
+}
+
+/// How the command column is shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandDisplay {
+    /// Executable basename
+    Name,
+    /// Full executable path
+    Path,
+}
+
+/// Traffic of one network namespace between the last two process refreshes, from
+/// `/proc/<pid>/net/dev` of one of its processes. Loopback traffic is left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamespaceTraffic {
+    /// Link target of `/proc/<pid>/ns/net`, e.g. `net:[4026531840]`
+    pub namespace: String,
+    pub host: bool,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub pids: Vec<i32>,
+}
+
+/// What the details panel and `info` show beyond `ProcessInfo`, read on demand since
+/// it's too costly to gather for every process on every refresh. A field that can't
+/// be read, usually because the process belongs to another user, holds the reason.
+#[derive(Debug, Clone)]
+pub struct ProcessDetails {
+    /// `None` when the clock tick rate is unknown
+    pub started_at: Option<SystemTime>,
+    pub cwd: Result<String, String>,
+    /// As set when the process started, in their original order; later changes the
+    /// process makes to its own environment aren't visible
+    pub environment: Result<Vec<(String, String)>, String>,
+    pub open_fds: Result<usize, String>,
+}
+
+/// Recent samples for a single process, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessHistory {
+    pub cpu_usage: VecDeque<f32>,
+    /// Resident memory in bytes
+    pub memory_usage: VecDeque<u64>,
+}
+
+/// Linux scheduling policy of a process. The nice value only matters under the
+/// normal policies; the real-time ones run ahead of every normal process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+#[value(rename_all = "lowercase")]
+pub enum SchedPolicy {
+    /// The default time-sharing policy (SCHED_OTHER)
+    Other,
+    /// Real-time, first in first out (SCHED_FIFO)
+    Fifo,
+    /// Real-time, round robin (SCHED_RR)
+    #[serde(rename = "rr")]
+    #[value(name = "rr")]
+    RoundRobin,
+    /// CPU-bound background work, preempted less often (SCHED_BATCH)
+    Batch,
+    /// Only runs when nothing else wants the CPU (SCHED_IDLE)
+    Idle,
+    /// Real-time with a runtime budget per period; set with chrt, not here
+    #[value(skip)]
+    Deadline,
+}
+
+impl SchedPolicy {
+    /// Short label, e.g. "FIFO", as shown by `info` and the details panel.
+    pub fn label(&self) -> &'static str {
+        match self {
+            SchedPolicy::Other => "OTHER",
+            SchedPolicy::Fifo => "FIFO",
+            SchedPolicy::RoundRobin => "RR",
+            SchedPolicy::Batch => "BATCH",
+            SchedPolicy::Idle => "IDLE",
+            SchedPolicy::Deadline => "DEADLINE",
+        }
+    }
+
+    /// Real-time policies take a priority from 1 to 99 and need privileges to set.
+    pub fn is_realtime(&self) -> bool {
+        matches!(self, SchedPolicy::Fifo | SchedPolicy::RoundRobin | SchedPolicy::Deadline)
+    }
+}
+
+/// Long-run CPU utilization, accumulated by the kernel since boot.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CpuSinceBoot {
+    pub usage_percent: f32,
+    pub idle_percent: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum SortOrder {
+    #[serde(rename = "asc")]
+    #[value(name = "asc")]
+    Ascending,
+    #[serde(rename = "desc")]
+    #[value(name = "desc")]
+    Descending,
+}
+
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Ascending => "asc",
+            SortOrder::Descending => "desc",
+        }
+    }
+}
+
+// Serde and clap names match `as_str`, so config files and flags use the same spelling
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+#[value(rename_all = "lowercase")]
+pub enum SortField {
+    PID,
+    CPU,
+    Memory,
+    #[serde(rename = "mem_percent")]
+    #[value(name = "mem_percent")]
+    MemoryPercent,
+    Command,
+    /// Disk reads plus writes
+    #[serde(alias = "disk")]
+    #[value(alias = "disk")]
+    IO,
+    #[serde(rename = "read")]
+    #[value(name = "read")]
+    DiskRead,
+    #[serde(rename = "write")]
+    #[value(name = "write")]
+    DiskWrite,
+    Threads,
+    /// Nice value
+    Priority,
+    /// Mean CPU usage since the process was first seen
+    #[serde(rename = "avg_cpu")]
+    #[value(name = "avg_cpu")]
+    ObservedCpu,
+    /// Peak resident memory
+    #[serde(rename = "peak")]
+    #[value(name = "peak")]
+    PeakMemory,
+}
+
+impl SortField {
+    pub const ALL: [SortField; 12] = [
+        SortField::PID,
+        SortField::CPU,
+        SortField::Memory,
+        SortField::MemoryPercent,
+        SortField::Command,
+        SortField::IO,
+        SortField::DiskRead,
+        SortField::DiskWrite,
+        SortField::Threads,
+        SortField::Priority,
+        SortField::ObservedCpu,
+        SortField::PeakMemory,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortField::PID => "pid",
+            SortField::CPU => "cpu",
+            SortField::Memory => "memory",
+            SortField::MemoryPercent => "mem_percent",
+            SortField::Command => "command",
+            SortField::IO => "io",
+            SortField::DiskRead => "read",
+            SortField::DiskWrite => "write",
+            SortField::Threads => "threads",
+            SortField::Priority => "priority",
+            SortField::ObservedCpu => "avg_cpu",
+            SortField::PeakMemory => "peak",
+        }
+    }
+
+    /// Compares two processes by this field in ascending order.
+    pub fn compare(&self, a: &ProcessInfo, b: &ProcessInfo, mode: CommandDisplay) -> Ordering {
+        match self {
+            SortField::PID => a.pid.cmp(&b.pid),
+            SortField::CPU => a.cpu_usage.total_cmp(&b.cpu_usage),
+            SortField::Memory => a.memory_usage.cmp(&b.memory_usage),
+            SortField::MemoryPercent => a.memory_percent.total_cmp(&b.memory_percent),
+            SortField::Command => a.display_command(mode).cmp(b.display_command(mode)),
+            SortField::IO => (a.disk_read + a.disk_write).cmp(&(b.disk_read + b.disk_write)),
+            SortField::DiskRead => a.disk_read.cmp(&b.disk_read),
+            SortField::DiskWrite => a.disk_write.cmp(&b.disk_write),
+            SortField::Threads => a.threads.cmp(&b.threads),
+            SortField::Priority => a.nice.cmp(&b.nice),
+            SortField::ObservedCpu => a.observed_avg_cpu.total_cmp(&b.observed_avg_cpu),
+            SortField::PeakMemory => a.peak_memory.cmp(&b.peak_memory),
+        }
+    }
+}
+
+/// Number of processes in each state (`Sleeping`, `Runnable`, `Zombie`, ...).
+pub fn count_by_status(processes: &[ProcessInfo]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for process in processes {
+        *counts.entry(process.status.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// State counts ordered most common first, ties by name, so breakdowns read the same
+/// way on every refresh.
+pub fn most_common_first(counts: &HashMap<String, usize>) -> Vec<(&str, usize)> {
+    let mut counts: Vec<(&str, usize)> =
+        counts.iter().map(|(status, count)| (status.as_str(), *count)).collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    counts
+}
+
+/// Sorts processes in place; shared by the CLI and the GUI so both order rows the same way.
+pub fn sort_processes(
+    processes: &mut [ProcessInfo],
+    field: SortField,
+    order: SortOrder,
+    mode: CommandDisplay,
+) {
+    processes.sort_by(|a, b| {
+        let ordering = field.compare(a, b, mode);
+        match order {
+            SortOrder::Ascending => ordering,
+            SortOrder::Descending => ordering.reverse(),
+        }
+    });
+}
+
+/// Orders processes depth-first as a tree: each root is followed by its descendants.
+/// Siblings are sorted by `field` within their parent, so the nesting is kept while
+/// e.g. the busiest child still comes first. A process whose parent isn't in the list
+/// (because it was filtered out, or has no parent) is a root.
+///
+/// Returns the depth of each process in the new order, 0 for roots.
+pub fn sort_process_tree(
+    processes: &mut Vec<ProcessInfo>,
+    field: SortField,
+    order: SortOrder,
+    mode: CommandDisplay,
+) -> Vec<usize> {
+    let present: HashSet<i32> = processes.iter().map(|p| p.pid).collect();
+    let mut children: HashMap<Option<i32>, Vec<ProcessInfo>> = HashMap::new();
+    for process in processes.drain(..) {
+        let parent = process.ppid.filter(|ppid| *ppid != process.pid && present.contains(ppid));
+        children.entry(parent).or_default().push(process);
+    }
+    for siblings in children.values_mut() {
+        sort_processes(siblings, field, order, mode);
+    }
+
+    let mut depths = Vec::with_capacity(present.len());
+    // Siblings are pushed in reverse so the first of them is popped first
+    let mut stack: Vec<(ProcessInfo, usize)> = children
+        .remove(&None)
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .map(|process| (process, 0))
+        .collect();
+    while let Some((process, depth)) = stack.pop() {
+        if let Some(siblings) = children.remove(&Some(process.pid)) {
+            stack.extend(siblings.into_iter().rev().map(|child| (child, depth + 1)));
+        }
+        processes.push(process);
+        depths.push(depth);
+    }
+    // Parent links that form a loop can't be reached from any root; list them flat
+    for process in children.into_values().flatten() {
+        processes.push(process);
+        depths.push(0);
+    }
+    depths
+}
+
+/// Hides the descendants of the `collapsed` PIDs from a list ordered by
+/// `sort_process_tree`, dropping their depths too.
+///
+/// Returns how many descendants each remaining process has, hidden or not.
+pub fn collapse_process_tree(
+    processes: &mut Vec<ProcessInfo>,
+    depths: &mut Vec<usize>,
+    collapsed: &HashSet<i32>,
+) -> Vec<usize> {
+    let all_depths = std::mem::take(depths);
+    let mut descendants = Vec::with_capacity(all_depths.len());
+    // Depth of the collapsed process whose subtree is being skipped
+    let mut hiding_below = None;
+    let mut index = 0;
+    processes.retain(|process| {
+        let depth = all_depths[index];
+        index += 1;
+        if hiding_below.is_some_and(|collapsed_depth| depth > collapsed_depth) {
+            return false;
+        }
+        let below = all_depths[index..].iter().take_while(|&&d| d > depth).count();
+        hiding_below = (below > 0 && collapsed.contains(&process.pid)).then_some(depth);
+        depths.push(depth);
+        descendants.push(below);
+        true
+    });
+    descendants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: i32, ppid: Option<i32>, cpu: f32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid,
+            cpu_usage: cpu,
+            observed_avg_cpu: cpu,
+            command: format!("proc{pid}"),
+            threads: 1,
+            ..Default::default()
+        }
+    }
+
+    fn sorted_tree(order: SortOrder) -> Vec<(i32, usize)> {
+        let mut processes = vec![
+            process(10, Some(1), 1.0),
+            process(20, Some(11), 2.0),
+            process(1, None, 5.0),
+            process(21, Some(11), 9.0),
+            // Its parent 99 was filtered out of the list
+            process(30, Some(99), 20.0),
+            process(11, Some(1), 30.0),
+            process(2, Some(0), 50.0),
+        ];
+        let depths = sort_process_tree(&mut processes, SortField::CPU, order, CommandDisplay::Name);
+        processes.iter().map(|p| p.pid).zip(depths).collect()
+    }
+
+    fn with_io(pid: i32, disk_read: u64, disk_write: u64, threads: u32) -> ProcessInfo {
+        ProcessInfo { disk_read, disk_write, threads, ..process(pid, None, 0.0) }
+    }
+
+    fn sorted_pids(field: SortField, order: SortOrder) -> Vec<i32> {
+        // Totals: 1 -> 500, 2 -> 900, 3 -> 100
+        let mut processes =
+            vec![with_io(1, 100, 400, 8), with_io(2, 800, 100, 2), with_io(3, 50, 50, 16)];
+        sort_processes(&mut processes, field, order, CommandDisplay::Name);
+        processes.iter().map(|p| p.pid).collect()
+    }
+
+    #[test]
+    fn compare_io_fields_and_threads() {
+        use SortOrder::{Ascending, Descending};
+        assert_eq!(sorted_pids(SortField::IO, Ascending), [3, 1, 2]);
+        assert_eq!(sorted_pids(SortField::IO, Descending), [2, 1, 3]);
+        assert_eq!(sorted_pids(SortField::DiskRead, Ascending), [3, 1, 2]);
+        assert_eq!(sorted_pids(SortField::DiskRead, Descending), [2, 1, 3]);
+        assert_eq!(sorted_pids(SortField::DiskWrite, Ascending), [3, 2, 1]);
+        assert_eq!(sorted_pids(SortField::DiskWrite, Descending), [1, 2, 3]);
+        assert_eq!(sorted_pids(SortField::Threads, Ascending), [2, 1, 3]);
+        assert_eq!(sorted_pids(SortField::Threads, Descending), [3, 1, 2]);
+    }
+
+    #[test]
+    fn version_1_process_still_parses() {
+        let json = r#"{
+            "pid": 7, "user": "root", "uid": 0, "cpu_usage": 1.5, "memory_usage": 4096,
+            "virtual_memory": 8192, "status": "Sleeping", "command": "sshd",
+            "exe": "/usr/sbin/sshd", "threads": 1, "disk_read": 0, "disk_write": 0,
+            "namespaces": {}
+        }"#;
+        let process: ProcessInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(process.pid, 7);
+        assert_eq!(process.command, "sshd");
+        assert_eq!(process.ppid, None);
+        assert_eq!(process.cmdline, "");
+        assert_eq!(process.power_watts, None);
+    }
+
+    #[test]
+    fn tree_sorts_siblings_within_their_parent() {
+        assert_eq!(
+            sorted_tree(SortOrder::Descending),
+            [(2, 0), (30, 0), (1, 0), (11, 1), (21, 2), (20, 2), (10, 1)]
+        );
+        assert_eq!(
+            sorted_tree(SortOrder::Ascending),
+            [(1, 0), (10, 1), (11, 1), (20, 2), (21, 2), (30, 0), (2, 0)]
+        );
+    }
 }
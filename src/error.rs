@@ -0,0 +1,79 @@
+// src/error.rs
+
+//! Errors from operations on a single process, so callers can tell "no such
+//! process" from "permission denied" without parsing messages.
+
+use nix::errno::Errno;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum ProcessError {
+    /// No process has this PID, or it exited before the operation
+    NotFound(i32),
+    /// The kernel refused, usually because the process belongs to another user or
+    /// the change needs privileges; `action` is what was attempted, e.g. "signal"
+    PermissionDenied { pid: i32, action: &'static str },
+    /// The kill policy in the config forbids touching this process
+    PolicyDenied { pid: i32, command: String },
+    /// A signal name that isn't supported, e.g. "SIGFOO"
+    InvalidSignal(String),
+    /// An argument the operation can't take, e.g. a real-time priority above 99
+    InvalidArgument(String),
+    /// Any other failure reported by the system
+    Io(io::Error),
+}
+
+impl ProcessError {
+    /// Classifies a failed system call on `pid`.
+    pub fn from_errno(pid: i32, action: &'static str, errno: Errno) -> Self {
+        match errno {
+            Errno::ESRCH => ProcessError::NotFound(pid),
+            Errno::EPERM | Errno::EACCES => ProcessError::PermissionDenied { pid, action },
+            _ => ProcessError::Io(errno.into()),
+        }
+    }
+
+    /// Classifies a failed read of one of `pid`'s files under `/proc`.
+    pub fn from_io(pid: i32, action: &'static str, error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => ProcessError::NotFound(pid),
+            io::ErrorKind::PermissionDenied => ProcessError::PermissionDenied { pid, action },
+            _ => ProcessError::Io(error),
+        }
+    }
+}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessError::NotFound(pid) => {
+                write!(f, "No process with PID {} (it may have exited)", pid)
+            }
+            ProcessError::PermissionDenied { pid, action } => {
+                write!(f, "Permission denied to {} PID {} (try as root)", action, pid)
+            }
+            ProcessError::PolicyDenied { pid, command } => {
+                write!(f, "Killing PID {} ({}) is not allowed by the kill policy", pid, command)
+            }
+            ProcessError::InvalidSignal(name) => write!(f, "Unsupported signal: {}", name),
+            ProcessError::InvalidArgument(message) => f.write_str(message),
+            ProcessError::Io(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ProcessError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProcessError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ProcessError {
+    fn from(error: io::Error) -> Self {
+        ProcessError::Io(error)
+    }
+}
@@ -0,0 +1,112 @@
+// src/export.rs
+
+//! CSV output of the process list for the `export` command, for spreadsheets and
+//! scripts. The JSON export is the same `ProcessList` that `list --format json` prints.
+
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+use crate::data_structures::ProcessInfo;
+
+/// Header row of the CSV export. Sizes are in bytes and CPU in percent of one core,
+/// as in `ProcessInfo`; namespaces are left out since they don't fit in a column.
+pub const CSV_COLUMNS: [&str; 20] = [
+    "pid",
+    "ppid",
+    "user",
+    "uid",
+    "cpu_usage",
+    "observed_avg_cpu",
+    "memory_usage",
+    "memory_percent",
+    "peak_memory",
+    "virtual_memory",
+    "swap_used",
+    "status",
+    "threads",
+    "nice",
+    "run_time_secs",
+    "disk_read",
+    "disk_write",
+    "command",
+    "exe",
+    "cmdline",
+];
+
+/// Writes a header row and one row per process, following RFC 4180.
+pub fn write_csv(out: &mut impl Write, processes: &[ProcessInfo]) -> io::Result<()> {
+    writeln!(out, "{}", CSV_COLUMNS.join(","))?;
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    for p in processes {
+        let fields = [
+            p.pid.to_string(),
+            optional(p.ppid.map(|ppid| ppid.to_string())),
+            p.user.clone(),
+            optional(p.uid.map(|uid| uid.to_string())),
+            p.cpu_usage.to_string(),
+            p.observed_avg_cpu.to_string(),
+            p.memory_usage.to_string(),
+            p.memory_percent.to_string(),
+            p.peak_memory.to_string(),
+            p.virtual_memory.to_string(),
+            p.swap_used.to_string(),
+            p.status.clone(),
+            p.threads.to_string(),
+            p.nice.to_string(),
+            p.run_time_secs.to_string(),
+            p.disk_read.to_string(),
+            p.disk_write.to_string(),
+            p.command.clone(),
+            p.exe.clone(),
+            p.cmdline.clone(),
+        ];
+        let row: Vec<Cow<str>> = fields.iter().map(|field| csv_field(field)).collect();
+        writeln!(out, "{}", row.join(","))?;
+    }
+    out.flush()
+}
+
+/// Quotes a field containing a comma, quote or line break, doubling its quotes.
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("cr\rlf"), "\"cr\rlf\"");
+    }
+
+    #[test]
+    fn command_lines_survive_as_one_field() {
+        let process = ProcessInfo {
+            pid: 42,
+            ppid: Some(1),
+            command: "sh".to_string(),
+            exe: "/bin/sh".to_string(),
+            cmdline: "sh -c \"echo a,b\"\necho done".to_string(),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        write_csv(&mut out, &[process]).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+
+        let (header, row) = csv.split_once('\n').unwrap();
+        assert_eq!(header, CSV_COLUMNS.join(","));
+        assert!(row.starts_with("42,1,"), "{}", row);
+        // The last field keeps its comma and line break, with its quotes doubled
+        assert!(row.ends_with(",sh,/bin/sh,\"sh -c \"\"echo a,b\"\"\necho done\"\n"), "{}", row);
+    }
+}
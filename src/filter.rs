@@ -0,0 +1,358 @@
+// src/filter.rs
+
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use crate::data_structures::{ProcessInfo, SortField, SortOrder};
+
+/// How a search query is interpreted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterMode {
+    /// The query is matched literally as a substring
+    Substring,
+    /// Shell-style wildcards (`*`, `?`, `[...]`); a query without any of them
+    /// still matches as a substring
+    Glob,
+    /// The query's characters appear in order, not necessarily together, so `chrm`
+    /// matches `chrome`; matches are ranked by how closely they fit
+    Fuzzy,
+}
+
+/// How the space-separated words of a search query are combined.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TermMatch {
+    /// The whole query is a single term, spaces included
+    #[default]
+    Phrase,
+    /// A process matches if any term does
+    Any,
+    /// A process matches only if every term does
+    All,
+}
+
+/// A compiled search query, matched case-insensitively against the PID and command.
+#[derive(Debug, Clone)]
+pub enum ProcessFilter {
+    Substring(String),
+    Glob(Pattern),
+    Fuzzy(String),
+    Any(Vec<ProcessFilter>),
+    All(Vec<ProcessFilter>),
+}
+
+const GLOB_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: false,
+    require_literal_leading_dot: false,
+};
+
+impl ProcessFilter {
+    pub fn new(query: &str, mode: FilterMode) -> Self {
+        if mode == FilterMode::Fuzzy {
+            return ProcessFilter::Fuzzy(query.to_string());
+        }
+        let has_wildcards = query.contains(['*', '?', '[']);
+        if mode == FilterMode::Glob && has_wildcards {
+            // An unbalanced `[` isn't a valid pattern; fall back to a literal match
+            if let Ok(pattern) = Pattern::new(query) {
+                return ProcessFilter::Glob(pattern);
+            }
+        }
+        ProcessFilter::Substring(query.to_lowercase())
+    }
+
+    /// Like `new`, but with `Any` or `All` each whitespace-separated word of the
+    /// query is compiled as its own term, e.g. `chrome firefox` with `Any` matches
+    /// both browsers.
+    pub fn with_terms(query: &str, mode: FilterMode, terms: TermMatch) -> Self {
+        let words: Vec<ProcessFilter> =
+            query.split_whitespace().map(|word| Self::new(word, mode)).collect();
+        match terms {
+            // A blank query keeps matching the way it does as a phrase
+            _ if words.is_empty() => Self::new(query, mode),
+            TermMatch::Phrase => Self::new(query, mode),
+            TermMatch::Any => ProcessFilter::Any(words),
+            TermMatch::All => ProcessFilter::All(words),
+        }
+    }
+
+    pub fn matches(&self, process: &ProcessInfo) -> bool {
+        match self {
+            ProcessFilter::Substring(query) => {
+                process.pid.to_string().contains(query)
+                    || process.command.to_lowercase().contains(query)
+            }
+            ProcessFilter::Glob(pattern) => {
+                pattern.matches_with(&process.command, GLOB_OPTIONS)
+                    || pattern.matches(&process.pid.to_string())
+            }
+            ProcessFilter::Fuzzy(_) => self.score(process).is_some(),
+            ProcessFilter::Any(terms) => terms.iter().any(|term| term.matches(process)),
+            ProcessFilter::All(terms) => terms.iter().all(|term| term.matches(process)),
+        }
+    }
+
+    /// How well a process matches, higher is better; `None` if it doesn't. Only fuzzy
+    /// terms tell matches apart, other matches all score 0. With several terms, `Any`
+    /// takes the best term's score and `All` adds them up.
+    pub fn score(&self, process: &ProcessInfo) -> Option<i64> {
+        match self {
+            ProcessFilter::Fuzzy(query) => {
+                fuzzy_matcher().fuzzy_match(&process.command, query).or_else(|| {
+                    // A PID is only ever typed exactly, so it's not matched fuzzily
+                    process.pid.to_string().contains(query.as_str()).then_some(0)
+                })
+            }
+            ProcessFilter::Any(terms) => terms.iter().filter_map(|term| term.score(process)).max(),
+            ProcessFilter::All(terms) => terms.iter().map(|term| term.score(process)).sum(),
+            _ => self.matches(process).then_some(0),
+        }
+    }
+
+    /// Byte ranges of `text` the query matched, sorted and not overlapping, for
+    /// highlighting why a row matched. Fuzzy terms report the characters the matcher
+    /// picked; glob patterns the literal parts between their wildcards. Empty when
+    /// only the PID matched.
+    pub fn match_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        let mut ranges = match self {
+            ProcessFilter::Substring(query) => find_ignore_case(text, query, 0),
+            ProcessFilter::Glob(pattern) => glob_literal_ranges(text, pattern.as_str()),
+            ProcessFilter::Fuzzy(query) => {
+                let Some((_, indices)) = fuzzy_matcher().fuzzy_indices(text, query) else {
+                    return Vec::new();
+                };
+                // The matcher reports character indices
+                text.char_indices()
+                    .enumerate()
+                    .filter(|(index, _)| indices.contains(index))
+                    .map(|(_, (start, c))| start..start + c.len_utf8())
+                    .collect()
+            }
+            ProcessFilter::Any(terms) | ProcessFilter::All(terms) => {
+                terms.iter().flat_map(|term| term.match_ranges(text)).collect()
+            }
+        };
+        ranges.sort_by_key(|range| range.start);
+        let mut merged: Vec<Range<usize>> = Vec::new();
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+}
+
+fn fuzzy_matcher() -> &'static SkimMatcherV2 {
+    static MATCHER: OnceLock<SkimMatcherV2> = OnceLock::new();
+    MATCHER.get_or_init(|| SkimMatcherV2::default().ignore_case())
+}
+
+/// Every occurrence of `query` in `text` from byte `from` on, ignoring case; `query`
+/// must already be lowercase. Compared a character at a time, since lowercasing can
+/// change a string's length in bytes.
+fn find_ignore_case(text: &str, query: &str, from: usize) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut ranges = Vec::new();
+    let mut next = from;
+    for (start, _) in text.char_indices().filter(|&(start, _)| start >= from) {
+        if start < next {
+            continue;
+        }
+        let mut end = start;
+        let mut candidate = text[start..].chars();
+        let matched = query.chars().all(|expected| match candidate.next() {
+            Some(c) if c.to_lowercase().eq(expected.to_lowercase()) => {
+                end += c.len_utf8();
+                true
+            }
+            _ => false,
+        });
+        if matched {
+            ranges.push(start..end);
+            next = end;
+        }
+    }
+    ranges
+}
+
+/// Where the literal runs of a glob pattern, e.g. `chr` and `me` in `chr*me`, fall in
+/// `text`, each found after the one before it.
+fn glob_literal_ranges(text: &str, pattern: &str) -> Vec<Range<usize>> {
+    let mut literals = Vec::new();
+    let mut literal = String::new();
+    let mut in_class = false;
+    for c in pattern.chars() {
+        match c {
+            // A character class stands for one unknown character, like `?`
+            '[' if !in_class => in_class = true,
+            ']' if in_class => in_class = false,
+            _ if in_class => {}
+            '*' | '?' => {}
+            _ => {
+                literal.extend(c.to_lowercase());
+                continue;
+            }
+        }
+        literals.push(std::mem::take(&mut literal));
+    }
+    literals.push(literal);
+
+    let mut ranges = Vec::new();
+    let mut from = 0;
+    for literal in literals.iter().filter(|literal| !literal.is_empty()) {
+        let Some(found) = find_ignore_case(text, literal, from).into_iter().next() else {
+            break;
+        };
+        from = found.end;
+        ranges.push(found);
+    }
+    ranges
+}
+
+/// A saved investigation: search query, sort and usage ranges, stored by name under
+/// `[presets.<name>]` in the config file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterPreset {
+    /// Matched like the search box, wildcards included; empty matches everything
+    pub query: String,
+    pub sort: SortField,
+    pub order: SortOrder,
+    /// CPU usage range in percent; no maximum means unbounded
+    pub cpu_min: f32,
+    pub cpu_max: Option<f32>,
+    /// Resident memory range in MB; no maximum means unbounded
+    pub memory_min_mb: f32,
+    pub memory_max_mb: Option<f32>,
+}
+
+impl Default for FilterPreset {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            sort: SortField::PID,
+            order: SortOrder::Ascending,
+            cpu_min: 0.0,
+            cpu_max: None,
+            memory_min_mb: 0.0,
+            memory_max_mb: None,
+        }
+    }
+}
+
+impl FilterPreset {
+    /// Whether a process falls inside the preset's CPU and memory ranges.
+    pub fn in_ranges(&self, process: &ProcessInfo) -> bool {
+        let memory_mb = process.memory_usage as f32 / (1024.0 * 1024.0);
+        let within = |value: f32, min: f32, max: Option<f32>| {
+            value >= min && max.is_none_or(|max| value <= max)
+        };
+        within(process.cpu_usage, self.cpu_min, self.cpu_max)
+            && within(memory_mb, self.memory_min_mb, self.memory_max_mb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: i32, command: &str) -> ProcessInfo {
+        ProcessInfo { pid, command: command.to_string(), ..Default::default() }
+    }
+
+    /// Commands of the sample processes the filter matches.
+    fn matching(filter: &ProcessFilter) -> Vec<&'static str> {
+        ["chrome", "firefox", "bash", "zsh", "Chromium", "gnome-shell"]
+            .into_iter()
+            .enumerate()
+            .filter(|&(i, command)| filter.matches(&process(1000 + i as i32, command)))
+            .map(|(_, command)| command)
+            .collect()
+    }
+
+    #[test]
+    fn glob_wildcards() {
+        let glob = |query| matching(&ProcessFilter::new(query, FilterMode::Glob));
+        assert_eq!(glob("chr*"), ["chrome", "Chromium"]);
+        assert_eq!(glob("?sh"), ["zsh"]);
+        assert_eq!(glob("[bz]*sh"), ["bash", "zsh"]);
+        // The whole name has to match once there are wildcards
+        assert!(glob("fire*x?").is_empty());
+        // Without wildcards it's a substring search
+        assert_eq!(glob("sh"), ["bash", "zsh", "gnome-shell"]);
+        // An unbalanced bracket is matched literally
+        let unbalanced = ProcessFilter::new("[ab", FilterMode::Glob);
+        assert!(matches!(unbalanced, ProcessFilter::Substring(_)));
+    }
+
+    #[test]
+    fn matching_ignores_case() {
+        let both = ["chrome", "Chromium"];
+        assert_eq!(matching(&ProcessFilter::new("CHROM", FilterMode::Substring)), both);
+        assert_eq!(matching(&ProcessFilter::new("CHROM*", FilterMode::Glob)), both);
+        assert_eq!(matching(&ProcessFilter::new("FRFX", FilterMode::Fuzzy)), ["firefox"]);
+    }
+
+    #[test]
+    fn pids_match_too() {
+        assert_eq!(matching(&ProcessFilter::new("1003", FilterMode::Substring)), ["zsh"]);
+        let glob = ProcessFilter::new("100[01]", FilterMode::Glob);
+        assert_eq!(matching(&glob), ["chrome", "firefox"]);
+    }
+
+    #[test]
+    fn term_modes() {
+        let terms =
+            |query, terms| matching(&ProcessFilter::with_terms(query, FilterMode::Glob, terms));
+        assert_eq!(terms("fire bash", TermMatch::Any), ["firefox", "bash"]);
+        assert_eq!(terms("gnome *sh*", TermMatch::All), ["gnome-shell"]);
+        assert!(terms("fire bash", TermMatch::All).is_empty());
+        // As a phrase the space is part of the query
+        assert!(terms("fire bash", TermMatch::Phrase).is_empty());
+    }
+
+    #[test]
+    fn match_ranges_are_byte_offsets() {
+        let text = "naïve-Ünïcode";
+        let ranges = ProcessFilter::new("ün", FilterMode::Substring).match_ranges(text);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(&text[ranges[0].clone()], "Ün");
+        assert_eq!(ranges[0], 7..10);
+
+        let ranges = ProcessFilter::new("ïv", FilterMode::Fuzzy).match_ranges(text);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(&text[ranges[0].clone()], "ïv");
+
+        // Every range starts and ends on a character boundary
+        let ranges = ProcessFilter::new("n*ï?O*E", FilterMode::Glob).match_ranges(text);
+        assert_eq!(ranges, [0..1, 2..4, 13..14, 15..16]);
+        let on_boundaries = |r: &Range<usize>| {
+            text.is_char_boundary(r.start) && text.is_char_boundary(r.end)
+        };
+        assert!(ranges.iter().all(on_boundaries));
+    }
+
+    #[test]
+    fn glob_ranges_follow_the_literal_runs() {
+        let glob = ProcessFilter::new("chr*me", FilterMode::Glob);
+        assert_eq!(glob.match_ranges("chrome"), [0..3, 4..6]);
+        // Only the PID matched
+        assert!(ProcessFilter::new("12", FilterMode::Substring).match_ranges("chrome").is_empty());
+    }
+
+    #[test]
+    fn overlapping_term_ranges_are_merged() {
+        let filter = ProcessFilter::with_terms("chr hro", FilterMode::Substring, TermMatch::Any);
+        let ranges = filter.match_ranges("chrome");
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0], 0..4);
+    }
+}
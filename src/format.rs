@@ -0,0 +1,179 @@
+// src/format.rs
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::cgroup::CgroupLimits;
+use crate::data_structures::{most_common_first, SchedPolicy};
+
+const BINARY_UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+const DECIMAL_UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+/// Set once at startup from the config; binary until then.
+static DECIMAL: AtomicBool = AtomicBool::new(false);
+
+/// Units byte counts are shown in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ByteUnits {
+    /// Powers of 1024: KiB, MiB, GiB (IEC), as the kernel counts memory
+    Binary,
+    /// Powers of 1000: KB, MB, GB (SI), as disks are usually sold
+    Decimal,
+}
+
+impl ByteUnits {
+    /// Bytes in the first unit above bytes and its label: 1024 and `KiB`, or 1000
+    /// and `KB`. For scales that can't switch units as the values grow, e.g. charts.
+    pub fn kilo(self) -> (u64, &'static str) {
+        match self {
+            ByteUnits::Binary => (1024, BINARY_UNITS[1]),
+            ByteUnits::Decimal => (1000, DECIMAL_UNITS[1]),
+        }
+    }
+}
+
+/// Sets the units `format_bytes` uses from now on.
+pub fn set_byte_units(units: ByteUnits) {
+    DECIMAL.store(units == ByteUnits::Decimal, Ordering::Relaxed);
+}
+
+/// The units `format_bytes` currently uses.
+pub fn byte_units() -> ByteUnits {
+    match DECIMAL.load(Ordering::Relaxed) {
+        true => ByteUnits::Decimal,
+        false => ByteUnits::Binary,
+    }
+}
+
+/// Formats a byte count in the configured units, using the largest unit that keeps
+/// the value above 1.
+pub fn format_bytes(bytes: u64) -> String {
+    format_bytes_in(bytes, byte_units())
+}
+
+/// Formats a byte count in the given units, e.g. `1.50 MiB` or `1.57 MB`.
+pub fn format_bytes_in(bytes: u64, units: ByteUnits) -> String {
+    let (base, labels) = match units {
+        ByteUnits::Binary => (1024.0, BINARY_UNITS),
+        ByteUnits::Decimal => (1000.0, DECIMAL_UNITS),
+    };
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= base && unit < labels.len() - 1 {
+        value /= base;
+        unit += 1;
+    }
+    // Just below the next unit, e.g. 1023.999 KiB, would print as "1024.00 KiB"
+    if unit > 0 && unit < labels.len() - 1 && (value * 100.0).round() / 100.0 >= base {
+        value /= base;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, labels[0])
+    } else {
+        format!("{:.2} {}", value, labels[unit])
+    }
+}
+
+/// Formats a change in size with an explicit sign, e.g. `+1.50 MiB` or `-512 B`.
+pub fn format_bytes_delta(delta: i64) -> String {
+    let sign = if delta < 0 { "-" } else { "+" };
+    format!("{}{}", sign, format_bytes(delta.unsigned_abs()))
+}
+
+/// Memory use against a cgroup's limit, e.g. `using 1.20 GiB of 2.00 GiB limit (60%)`.
+pub fn format_cgroup_memory(limits: &CgroupLimits) -> String {
+    match (limits.memory_usage, limits.memory_limit, limits.memory_percent()) {
+        (Some(usage), Some(limit), Some(percent)) => format!(
+            "using {} of {} limit ({:.0}%)",
+            format_bytes(usage),
+            format_bytes(limit),
+            percent
+        ),
+        (Some(usage), _, _) => format!("using {}, no memory limit", format_bytes(usage)),
+        // The root cgroup doesn't account its own memory
+        (None, _, _) => "no memory limit".to_string(),
+    }
+}
+
+/// A scheduling policy with its priority when it has one, e.g. `FIFO 50` or `OTHER`.
+pub fn format_sched_policy(policy: SchedPolicy, priority: i32) -> String {
+    if policy.is_realtime() {
+        format!("{} {}", policy.label(), priority)
+    } else {
+        policy.label().to_string()
+    }
+}
+
+/// CPU numbers in the kernel's list format, e.g. `0-3,6`.
+pub fn format_cpu_list(cpus: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == cpu => *end = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    let ranges: Vec<String> = ranges
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            }
+        })
+        .collect();
+    ranges.join(",")
+}
+
+/// Summarizes process states, e.g. `312 processes: 300 sleeping, 10 idle, 2 runnable`.
+pub fn format_status_counts(counts: &HashMap<String, usize>) -> String {
+    let total: usize = counts.values().sum();
+    let states: Vec<String> = most_common_first(counts)
+        .into_iter()
+        .map(|(status, count)| format!("{} {}", count, status.to_lowercase()))
+        .collect();
+    format!("{} processes: {}", total, states.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kilo_matches_the_formatted_unit() {
+        for units in [ByteUnits::Binary, ByteUnits::Decimal] {
+            let (bytes, label) = units.kilo();
+            assert_eq!(format_bytes_in(bytes, units), format!("1.00 {}", label));
+        }
+    }
+
+    #[test]
+    fn binary_boundaries() {
+        let format = |bytes| format_bytes_in(bytes, ByteUnits::Binary);
+        assert_eq!(format(0), "0 B");
+        assert_eq!(format(1023), "1023 B");
+        assert_eq!(format(1024), "1.00 KiB");
+        assert_eq!(format(1024 * 1024 - 1), "1.00 MiB");
+        assert_eq!(format(1024 * 1024), "1.00 MiB");
+        assert_eq!(format(1 << 40), "1.00 TiB");
+        assert_eq!(format(u64::MAX), "16777216.00 TiB");
+    }
+
+    #[test]
+    fn decimal_boundaries() {
+        let format = |bytes| format_bytes_in(bytes, ByteUnits::Decimal);
+        assert_eq!(format(0), "0 B");
+        assert_eq!(format(999), "999 B");
+        assert_eq!(format(1000), "1.00 KB");
+        assert_eq!(format(999_999), "1.00 MB");
+        assert_eq!(format(1_000_000), "1.00 MB");
+        assert_eq!(format(1_000_000_000_000), "1.00 TB");
+        assert_eq!(format(u64::MAX), "18446744.07 TB");
+    }
+}
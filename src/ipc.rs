@@ -0,0 +1,211 @@
+// src/ipc.rs
+
+//! Lets CLI commands reuse the scan of a running GUI instance.
+//!
+//! The GUI listens on a Unix socket. A client writes one request line (`list`)
+//! and reads back a single JSON document, then the connection is closed. Both ends
+//! check that the other runs as the same user before exchanging anything.
+
+use nix::sys::socket::{getsockopt, sockopt::PeerCredentials};
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::data_structures::{ProcessInfo, ProcessList, PROCESS_LIST_VERSION};
+use crate::process_handler::ProcessHandler;
+
+/// How long a client waits for the GUI before falling back to its own scan.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Attempts at binding while another instance still answers on the socket, e.g. one
+/// that is shutting down as this one starts; the wait doubles after each.
+const BIND_ATTEMPTS: u32 = 4;
+const BIND_BACKOFF: Duration = Duration::from_millis(50);
+
+/// `$XDG_RUNTIME_DIR/rust_task_manager.sock`, or a per-user path in `/tmp`.
+pub fn socket_path() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("rust_task_manager.sock"),
+        _ => PathBuf::from(format!(
+            "/tmp/rust_task_manager-{}.sock",
+            nix::unistd::getuid().as_raw()
+        )),
+    }
+}
+
+/// Starts answering requests on the socket in a background thread.
+///
+/// Requests are served from the handler's most recent scan, so they never trigger a
+/// scan of their own. Fails if another instance keeps listening; the caller carries on
+/// without the socket.
+pub fn spawn_server(handler: Arc<Mutex<ProcessHandler>>) -> Result<(), String> {
+    let path = socket_path();
+    let listener = bind(&path)?;
+    info!("Listening for CLI requests on {}", path.display());
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if let Err(e) = handle_client(stream, &handler) {
+                        debug!("IPC request failed: {}", e);
+                    }
+                }
+                Err(e) => warn!("IPC connection failed: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Binds the socket, replacing a stale one left by an instance that didn't shut down
+/// cleanly, and retrying with backoff while a live instance holds it.
+fn bind(path: &Path) -> Result<UnixListener, String> {
+    let mut delay = BIND_BACKOFF;
+    for attempt in 1..=BIND_ATTEMPTS {
+        match UnixListener::bind(path) {
+            Ok(listener) => return Ok(listener),
+            Err(e) if e.kind() == ErrorKind::AddrInUse => {}
+            Err(e) => return Err(format!("Failed to bind {}: {}", path.display(), e)),
+        }
+        match UnixStream::connect(path) {
+            // Only a refused connection shows that nothing is listening any more
+            Err(e) if e.kind() == ErrorKind::ConnectionRefused => {
+                remove_stale_socket(path)?;
+                continue;
+            }
+            Err(e) => return Err(format!("{} is in use: {}", path.display(), e)),
+            Ok(_) if attempt < BIND_ATTEMPTS => {
+                debug!(
+                    "Another instance is listening on {}; retrying in {:?}",
+                    path.display(),
+                    delay
+                );
+                thread::sleep(delay);
+                delay *= 2;
+            }
+            Ok(_) => {}
+        }
+    }
+    Err(format!("Another instance is listening on {}", path.display()))
+}
+
+/// Deletes a socket file nothing listens on. Anything else at the path is left alone,
+/// since it isn't ours to delete.
+fn remove_stale_socket(path: &Path) -> Result<(), String> {
+    let metadata = std::fs::symlink_metadata(path)
+        .map_err(|e| format!("Failed to inspect {}: {}", path.display(), e))?;
+    if !metadata.file_type().is_socket() {
+        return Err(format!("{} exists and isn't a socket", path.display()));
+    }
+    debug!("Removing stale socket {}", path.display());
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => {
+            Err(format!("Failed to remove stale socket {}: {}", path.display(), e))
+        }
+        // Already gone if another starting instance got there first
+        _ => Ok(()),
+    }
+}
+
+/// Removes the socket file; called when the GUI exits.
+pub fn remove_socket() {
+    let _ = std::fs::remove_file(socket_path());
+}
+
+/// User the process at the other end of `stream` runs as, from `SO_PEERCRED`.
+fn peer_uid(stream: &UnixStream) -> std::io::Result<u32> {
+    let credentials = getsockopt(stream.as_raw_fd(), PeerCredentials)?;
+    Ok(credentials.uid())
+}
+
+/// Fails unless the other end of `stream` runs as our own user. Without this, any
+/// user could read the process list, or bind the shared `/tmp` path first and hand
+/// clients a made-up one.
+fn check_peer(stream: &UnixStream) -> std::io::Result<()> {
+    let uid = peer_uid(stream)?;
+    if uid != nix::unistd::getuid().as_raw() {
+        return Err(std::io::Error::new(
+            ErrorKind::PermissionDenied,
+            format!("peer runs as uid {}", uid),
+        ));
+    }
+    Ok(())
+}
+
+fn handle_client(stream: UnixStream, handler: &Mutex<ProcessHandler>) -> std::io::Result<()> {
+    if let Err(e) = check_peer(&stream) {
+        warn!("Rejected IPC request: {}", e);
+        return Ok(());
+    }
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut request = String::new();
+    BufReader::new(&stream).read_line(&mut request)?;
+
+    let response = match request.trim() {
+        "list" => {
+            let handler = handler.lock().unwrap();
+            serde_json::to_string(&ProcessList::new(handler.last_scan()))?
+        }
+        other => serde_json::json!({ "error": format!("unknown request: {}", other) }).to_string(),
+    };
+    (&stream).write_all(response.as_bytes())
+}
+
+#[derive(Deserialize)]
+struct ListResponse {
+    version: u32,
+    processes: Vec<ProcessInfo>,
+}
+
+/// Asks a running GUI instance for its process list.
+///
+/// Returns `None` when no instance is running or it answers with an unsupported
+/// format version, in which case the caller should scan by itself.
+pub fn request_processes() -> Option<Vec<ProcessInfo>> {
+    let mut stream = UnixStream::connect(socket_path()).ok()?;
+    if let Err(e) = check_peer(&stream) {
+        warn!("Not using the instance on {}: {}", socket_path().display(), e);
+        return None;
+    }
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    stream.write_all(b"list\n").ok()?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok()?;
+    let list: ListResponse = match serde_json::from_str(&response) {
+        Ok(list) => list,
+        Err(e) => {
+            warn!("Invalid response from the running instance: {}", e);
+            return None;
+        }
+    };
+    if list.version != PROCESS_LIST_VERSION {
+        warn!(
+            "Running instance uses process list version {} (expected {})",
+            list.version, PROCESS_LIST_VERSION
+        );
+        return None;
+    }
+    Some(list.processes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peer_of_our_own_socket_is_accepted() {
+        let (client, server) = UnixStream::pair().unwrap();
+        assert_eq!(peer_uid(&client).unwrap(), nix::unistd::getuid().as_raw());
+        assert!(check_peer(&client).is_ok());
+        assert!(check_peer(&server).is_ok());
+    }
+}
@@ -1,6 +1,19 @@
 // src/lib.rs
 
+pub mod automation;
+pub mod cgroup;
+pub mod config;
 pub mod data_structures;
+pub mod error;
+pub mod export;
+pub mod filter;
+pub mod format;
+pub mod ipc;
+pub mod logging;
+pub mod ports;
 pub mod process_handler;
+pub mod quarantine;
+pub mod recorder;
+pub mod report;
 pub mod cli;
 pub mod ui;
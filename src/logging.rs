@@ -0,0 +1,23 @@
+// src/logging.rs
+
+use std::io::IsTerminal;
+use tracing_subscriber::EnvFilter;
+
+/// Installs the global `tracing` subscriber, writing to stderr.
+///
+/// `RUST_LOG` takes precedence when set; otherwise this crate logs at `info`
+/// (or `debug` with `verbose`) and dependencies only at `warn`.
+pub fn init(verbose: bool) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        let level = if verbose { "debug" } else { "info" };
+        EnvFilter::new(format!("warn,linux_task_manager={}", level))
+    });
+
+    // Ignore the error if a subscriber was already installed
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_target(false)
+        .with_ansi(std::io::stderr().is_terminal())
+        .with_writer(std::io::stderr)
+        .try_init();
+}
@@ -1,12 +1,42 @@
 // src/main.rs
 
-mod data_structures;
-mod process_handler;
-mod ui;
-use iced::Application;
+use clap::Parser;
+use iced::{Application, Settings};
 
-use ui::TaskManager;
+use linux_task_manager::cli::{run_cli, Cli};
+use linux_task_manager::config::Config;
+use linux_task_manager::format;
+use linux_task_manager::logging;
+use linux_task_manager::ui::TaskManager;
 
 fn main() {
-    TaskManager::run(iced::Settings::default()).unwrap();
+    let cli = Cli::parse();
+    // A command runs headless, e.g. over SSH, without ever opening a window
+    if let Some(command) = &cli.command {
+        run_cli(&cli, command);
+        return;
+    }
+    // The GUI would ignore these, so say so rather than open a window anyway
+    if cli.daemonize || cli.pid_file.is_some() || cli.dry_run {
+        eprintln!("--daemonize, --pid-file and --dry-run need a command, e.g. monitor");
+        std::process::exit(1);
+    }
+
+    logging::init(cli.verbose);
+
+    let config = match Config::resolve(&cli.overrides) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    format::set_byte_units(config.units);
+
+    TaskManager::run(Settings {
+        // The app saves its settings before closing the window itself
+        exit_on_close_request: false,
+        ..Settings::with_flags(config)
+    })
+    .unwrap();
 }
@@ -0,0 +1,109 @@
+// src/ports.rs
+
+//! Maps listening TCP ports to the processes that own them.
+//!
+//! `/proc/net/tcp` and `/proc/net/tcp6` list every socket with its inode, and each
+//! `/proc/<pid>/fd/*` link to a socket reads `socket:[<inode>]`. Only sockets in our
+//! own network namespace are visible, and other users' file descriptors can only be
+//! read as root.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use crate::error::ProcessError;
+
+/// Socket state code for LISTEN in `/proc/net/tcp`.
+const TCP_LISTEN: &str = "0A";
+
+/// Inode of every listening TCP socket (IPv4 and IPv6), mapped to its local port.
+pub fn listening_sockets() -> HashMap<u64, u16> {
+    let mut sockets = HashMap::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        // tcp6 is missing when IPv6 is disabled
+        let Ok(contents) = fs::read_to_string(table) else { continue };
+        for line in contents.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // sl, local_address, rem_address, st, ..., inode is the tenth field
+            if fields.len() < 10 || fields[3] != TCP_LISTEN {
+                continue;
+            }
+            let port = fields[1]
+                .rsplit_once(':')
+                .and_then(|(_, port)| u16::from_str_radix(port, 16).ok());
+            if let (Some(port), Ok(inode)) = (port, fields[9].parse::<u64>()) {
+                sockets.insert(inode, port);
+            }
+        }
+    }
+    sockets
+}
+
+/// Inodes of the sockets a process has open.
+///
+/// Fails when `/proc/<pid>/fd` can't be read, usually because the process belongs
+/// to another user, or because it has exited.
+pub fn socket_inodes(pid: i32) -> Result<HashSet<u64>, ProcessError> {
+    read_socket_inodes(pid).map_err(|e| ProcessError::from_io(pid, "read the open files of", e))
+}
+
+fn read_socket_inodes(pid: i32) -> std::io::Result<HashSet<u64>> {
+    Ok(fs::read_dir(format!("/proc/{}/fd", pid))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| fs::read_link(entry.path()).ok())
+        .filter_map(|target| {
+            let target = target.to_string_lossy();
+            target.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+        })
+        .collect())
+}
+
+/// Listening ports owned by a process, lowest first, without duplicates (a server
+/// listening on both IPv4 and IPv6 shows its port once).
+pub fn listening_ports(pid: i32) -> Result<Vec<u16>, ProcessError> {
+    let inodes = socket_inodes(pid)?;
+    let listening = listening_sockets();
+    let mut ports: Vec<u16> =
+        inodes.iter().filter_map(|inode| listening.get(inode)).copied().collect();
+    ports.sort_unstable();
+    ports.dedup();
+    Ok(ports)
+}
+
+/// Who is listening on a port, as found by `find_port_owners`.
+pub struct PortOwners {
+    /// PIDs with a listening socket on the port
+    pub pids: Vec<i32>,
+    /// Whether the port has a listening socket at all
+    pub listening: bool,
+    /// Processes whose open files couldn't be read, so an owner may be missing
+    pub unreadable: usize,
+}
+
+/// Finds the processes listening on a TCP port by checking every process's open
+/// sockets.
+pub fn find_port_owners(port: u16) -> PortOwners {
+    let inodes: HashSet<u64> = listening_sockets()
+        .into_iter()
+        .filter(|&(_, socket_port)| socket_port == port)
+        .map(|(inode, _)| inode)
+        .collect();
+    let mut owners = PortOwners { pids: Vec::new(), listening: !inodes.is_empty(), unreadable: 0 };
+    if inodes.is_empty() {
+        return owners;
+    }
+
+    let pids = fs::read_dir("/proc")
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<i32>().ok());
+    for pid in pids {
+        match read_socket_inodes(pid) {
+            Ok(open) if !open.is_disjoint(&inodes) => owners.pids.push(pid),
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => owners.unreadable += 1,
+            // Also covers processes that exited since /proc was listed
+            _ => {}
+        }
+    }
+    owners.pids.sort_unstable();
+    owners
+}
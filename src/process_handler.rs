@@ -1,43 +1,340 @@
 // src/process_handler.rs
 
-use sysinfo::{CpuExt, PidExt, ProcessExt, System, SystemExt};
-use crate::data_structures::ProcessInfo;
+use sysinfo::{
+    ComponentExt, CpuExt, DiskExt, Pid, PidExt, Process, ProcessExt, System, SystemExt,
+    UserExt,
+};
+use crate::cgroup::{self, CgroupLimits};
+use crate::config::KillPolicy;
+use crate::error::ProcessError;
+use crate::ports;
+use crate::quarantine::{self, QuarantineRecord, RESTORE_NICE};
+use crate::data_structures::{
+    count_by_status, CpuSinceBoot, DiskInfo, InterfaceTraffic, NamespaceTraffic, ProcessDetails,
+    ProcessHistory, ProcessInfo, ProcessKey, SchedPolicy,
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+use nix::unistd::{sysconf, SysconfVar};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tracing::warn;
+
+/// How soon after a process exits a new one with the same command counts as a restart.
+const RESTART_WINDOW: Duration = Duration::from_secs(10);
+
+/// Fine CPU samples kept when nothing calls `refresh` to collect them.
+const FINE_SAMPLES_MAX: usize = 10_000;
 
 pub struct ProcessHandler {
     system: System,
     cpu_usage_history: Vec<f32>,
     memory_usage_history: Vec<f32>,
+    history_capacity: usize,
+    /// Global CPU usage taken by the fine sampler since the last refresh
+    fine_cpu_samples: Option<Arc<Mutex<Vec<f32>>>>,
+    /// Lowest and highest CPU usage the fine sampler saw between each pair of
+    /// refreshes, lined up with the end of `cpu_usage_history`
+    cpu_range_history: Vec<(f32, f32)>,
+    /// Refreshes since the handler was created, so chart events keep their place as
+    /// old samples drop out of the histories
+    samples_taken: u64,
+    /// Labelled moments on the charts, by the number of the sample they belong to
+    chart_events: Vec<(u64, String)>,
+    host_namespaces: HashMap<String, String>,
+    process_histories: HashMap<ProcessKey, ProcessHistory>,
+    last_scan: Vec<ProcessInfo>,
+    /// Number of processes in the previous full scan, to notice incomplete ones
+    previous_scan_len: usize,
+    /// Previous RAPL energy counter per CPU package, to turn energy into power
+    last_package_energy: Option<(Vec<RaplCounter>, Instant)>,
+    /// CPU-seconds used per command name since the handler was created
+    session_cpu: HashMap<String, f64>,
+    last_cpu_sample: Option<Instant>,
+    /// Shortest time CPU usage is measured over; see `set_cpu_sample_interval`
+    cpu_sample_interval: Duration,
+    /// When sysinfo last sampled global CPU usage, and every process's
+    last_system_refresh: Instant,
+    last_process_refresh: Instant,
+    kill_policy: KillPolicy,
+    /// Where quarantines are recorded; `quarantine::state_path()` unless overridden
+    quarantine_file: Option<PathBuf>,
+    /// When set, reported CPU usage is averaged over this many refreshes
+    cpu_average_window: Option<usize>,
+    /// Parent of each process when it was first seen, to detect reparenting
+    original_parents: HashMap<ProcessKey, i32>,
+    /// Sum of CPU usage samples and their count per PID since it was first seen
+    observed_cpu: HashMap<ProcessKey, (f64, u32)>,
+    /// Command of every process in the previous full scan, to notice exits and new ones
+    known_commands: HashMap<ProcessKey, String>,
+    /// Commands that exited within the last `RESTART_WINDOW` and weren't replaced yet
+    recent_exits: Vec<(String, Instant)>,
+    restart_counts: HashMap<String, u32>,
+    /// Received and sent byte counters per network namespace at the previous refresh
+    net_counters: HashMap<String, (u64, u64)>,
+    network_traffic: Vec<NamespaceTraffic>,
+    /// Byte counters of each interface at the previous read, for the next rates
+    interface_counters: HashMap<String, (u64, u64)>,
+    interfaces_read_at: Option<Instant>,
+    interfaces: Vec<InterfaceTraffic>,
+    /// Received and sent bytes per second on all interfaces but loopback, one entry
+    /// per `refresh`, lined up with `cpu_usage_history`
+    network_history: Vec<(u64, u64)>,
+    /// Bytes read from and written to physical disks so far, and when they were read
+    disk_io_counters: Option<(Instant, (u64, u64))>,
+    /// Bytes read and written per second on physical disks, lined up with
+    /// `cpu_usage_history`
+    disk_io_history: Vec<(u64, u64)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RaplCounter {
+    energy_uj: u64,
+    max_energy_range_uj: u64,
+}
+
+impl Default for ProcessHandler {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ProcessHandler {
     pub fn new() -> Self {
+        Self::with_history_capacity(100)
+    }
+
+    pub fn with_history_capacity(history_capacity: usize) -> Self {
         let mut system = System::new_all();
         system.refresh_all();
         Self {
             system,
             cpu_usage_history: Vec::new(),
             memory_usage_history: Vec::new(),
+            history_capacity,
+            fine_cpu_samples: None,
+            cpu_range_history: Vec::new(),
+            samples_taken: 0,
+            chart_events: Vec::new(),
+            // PID 1's namespaces are the host's; fall back to our own when it isn't readable
+            host_namespaces: match read_namespaces(1) {
+                namespaces if !namespaces.is_empty() => namespaces,
+                _ => read_namespaces(std::process::id() as i32),
+            },
+            process_histories: HashMap::new(),
+            last_scan: Vec::new(),
+            previous_scan_len: 0,
+            last_package_energy: None,
+            session_cpu: HashMap::new(),
+            last_cpu_sample: None,
+            cpu_sample_interval: System::MINIMUM_CPU_UPDATE_INTERVAL,
+            // `refresh_all` above took the first samples
+            last_system_refresh: Instant::now(),
+            last_process_refresh: Instant::now(),
+            kill_policy: KillPolicy::default(),
+            quarantine_file: None,
+            cpu_average_window: None,
+            original_parents: HashMap::new(),
+            observed_cpu: HashMap::new(),
+            known_commands: HashMap::new(),
+            recent_exits: Vec::new(),
+            restart_counts: HashMap::new(),
+            net_counters: HashMap::new(),
+            network_traffic: Vec::new(),
+            interface_counters: HashMap::new(),
+            interfaces_read_at: None,
+            interfaces: Vec::new(),
+            network_history: Vec::new(),
+            disk_io_counters: None,
+            disk_io_history: Vec::new(),
         }
     }
 
     pub fn refresh(&mut self) {
-        self.system.refresh_all();
+        // Disks change slowly, so they are left to `refresh_disks`; processes are
+        // refreshed by `refresh_processes` or `refresh_watched`
+        wait_out(self.last_system_refresh, self.cpu_sample_interval);
+        self.system.refresh_system();
+        self.last_system_refresh = Instant::now();
 
         // Update CPU usage history
         let cpu_usage = self.system.global_cpu_info().cpu_usage();
         self.cpu_usage_history.push(cpu_usage);
-        if self.cpu_usage_history.len() > 100 {
+        if self.cpu_usage_history.len() > self.history_capacity {
             self.cpu_usage_history.remove(0);
         }
+        if let Some(samples) = &self.fine_cpu_samples {
+            let samples = std::mem::take(&mut *samples.lock().unwrap());
+            let range = samples
+                .iter()
+                .fold((cpu_usage, cpu_usage), |(min, max), &sample| {
+                    (min.min(sample), max.max(sample))
+                });
+            self.cpu_range_history.push(range);
+            if self.cpu_range_history.len() > self.history_capacity {
+                self.cpu_range_history.remove(0);
+            }
+        }
 
         // Update memory usage history
         let total_memory = self.system.total_memory() as f32;
         let used_memory = self.system.used_memory() as f32;
         let memory_usage_percent = (used_memory / total_memory) * 100.0;
         self.memory_usage_history.push(memory_usage_percent);
-        if self.memory_usage_history.len() > 100 {
+        if self.memory_usage_history.len() > self.history_capacity {
             self.memory_usage_history.remove(0);
         }
+
+        self.refresh_network();
+        let network = self
+            .interfaces
+            .iter()
+            .filter(|interface| interface.name != "lo")
+            .fold((0, 0), |(rx, tx), interface| {
+                (rx + interface.rx_per_sec, tx + interface.tx_per_sec)
+            });
+        self.network_history.push(network);
+        if self.network_history.len() > self.history_capacity {
+            self.network_history.remove(0);
+        }
+
+        // 0 on the first refresh, with nothing to compare against yet
+        let now = Instant::now();
+        let disk_io = read_disk_io();
+        let rates = match (self.disk_io_counters, disk_io) {
+            (Some((then, (read, written))), Some((read_now, written_now))) => {
+                let secs = now.duration_since(then).as_secs_f64().max(0.001);
+                let rate = |now: u64, then: u64| (now.saturating_sub(then) as f64 / secs) as u64;
+                (rate(read_now, read), rate(written_now, written))
+            }
+            _ => (0, 0),
+        };
+        self.disk_io_counters = disk_io.map(|counters| (now, counters));
+        self.disk_io_history.push(rates);
+        if self.disk_io_history.len() > self.history_capacity {
+            self.disk_io_history.remove(0);
+        }
+
+        self.samples_taken += 1;
+        let first_sample = self.first_sample();
+        self.chart_events.retain(|(sample, _)| *sample >= first_sample);
+    }
+
+    /// Marks the latest sample on the charts, e.g. with "kill 1234", to line actions
+    /// up with their effect. The mark scrolls off with the sample.
+    pub fn mark_event(&mut self, label: impl Into<String>) {
+        let sample = self.samples_taken.saturating_sub(1);
+        self.chart_events.push((sample, label.into()));
+    }
+
+    /// Chart events as indices into `get_cpu_usage_history` and
+    /// `get_memory_usage_history`, oldest first.
+    pub fn chart_events(&self) -> Vec<(usize, String)> {
+        let first_sample = self.first_sample();
+        self.chart_events
+            .iter()
+            .filter(|(sample, _)| *sample >= first_sample)
+            .map(|(sample, label)| ((sample - first_sample) as usize, label.clone()))
+            .collect()
+    }
+
+    /// Number of the oldest sample still in the CPU history.
+    fn first_sample(&self) -> u64 {
+        self.samples_taken - self.cpu_usage_history.len() as u64
+    }
+
+    /// Forgets every accumulated sample, for a fresh baseline: the CPU and memory
+    /// charts, per-process histories and averages, session CPU time, restart counts
+    /// and network counters. Parents and commands of running processes are kept, so
+    /// orphan and restart detection keep working.
+    pub fn clear_history(&mut self) {
+        self.cpu_usage_history.clear();
+        self.cpu_range_history.clear();
+        self.chart_events.clear();
+        self.memory_usage_history.clear();
+        self.process_histories.clear();
+        self.observed_cpu.clear();
+        self.session_cpu.clear();
+        self.recent_exits.clear();
+        self.restart_counts.clear();
+        self.net_counters.clear();
+        self.network_traffic.clear();
+        self.network_history.clear();
+        self.disk_io_history.clear();
+    }
+
+    /// Samples global CPU usage every `interval` on a background thread, so spikes
+    /// shorter than a refresh show up in `cpu_range_history`. The thread stops once
+    /// the handler is dropped.
+    pub fn start_fine_sampling(&mut self, interval: Duration) {
+        if self.fine_cpu_samples.is_some() {
+            return;
+        }
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let weak = Arc::downgrade(&samples);
+        let spawned = std::thread::Builder::new().name("cpu-sampler".into()).spawn(move || {
+            let mut previous = read_cpu_times();
+            loop {
+                std::thread::sleep(interval);
+                let Some(samples) = weak.upgrade() else {
+                    break;
+                };
+                let current = read_cpu_times();
+                if let (Some((total, idle)), Some((previous_total, previous_idle))) =
+                    (current, previous)
+                {
+                    let elapsed = total.saturating_sub(previous_total);
+                    let idle = idle.saturating_sub(previous_idle).min(elapsed);
+                    let mut samples = samples.lock().unwrap();
+                    if elapsed > 0 && samples.len() < FINE_SAMPLES_MAX {
+                        samples.push((elapsed - idle) as f32 / elapsed as f32 * 100.0);
+                    }
+                }
+                previous = current;
+            }
+        });
+        match spawned {
+            Ok(_) => self.fine_cpu_samples = Some(samples),
+            Err(e) => warn!("Failed to start the CPU sampler: {}", e),
+        }
+    }
+
+    /// Lowest and highest global CPU usage between each pair of refreshes, lined up
+    /// with the end of `get_cpu_usage_history`; empty unless `start_fine_sampling` ran.
+    pub fn cpu_range_history(&self) -> &[(f32, f32)] {
+        &self.cpu_range_history
+    }
+
+    /// Takes a process CPU sample without recording it in any history.
+    ///
+    /// CPU usage is the difference between two samples, so processes that appeared
+    /// since the last refresh read 0% until they have been sampled once before.
+    pub fn warm_up_cpu_sampling(&mut self) {
+        self.system.refresh_processes();
+        self.last_process_refresh = Instant::now();
+        self.last_cpu_sample = Some(self.last_process_refresh);
+    }
+
+    /// Shortest time CPU usage is measured over; see `set_cpu_sample_interval`.
+    pub fn cpu_sample_interval(&self) -> Duration {
+        self.cpu_sample_interval
+    }
+
+    /// Sets the shortest time CPU usage is measured over, by default the shortest delay
+    /// between two samples that still gives a meaningful CPU usage. `refresh` and
+    /// `refresh_processes` block until that long has passed since their previous
+    /// sample, and `refresh_processes` samples twice when new processes appeared, so no
+    /// process reads 0% just because it wasn't sampled before. Zero turns this off, e.g.
+    /// for benchmarks.
+    pub fn set_cpu_sample_interval(&mut self, interval: Duration) {
+        self.cpu_sample_interval = interval;
+    }
+
+    /// 1, 5 and 15 minute load averages.
+    pub fn load_average(&self) -> [f64; 3] {
+        let load = self.system.load_average();
+        [load.one, load.five, load.fifteen]
     }
 
     pub fn get_cpu_usage_history(&self) -> &[f32] {
@@ -48,31 +345,1489 @@ impl ProcessHandler {
         &self.memory_usage_history
     }
 
+    /// Installed memory in bytes, as of the last refresh.
+    pub fn total_memory(&self) -> u64 {
+        self.system.total_memory()
+    }
+
+    /// p50, p95 and p99 of global CPU usage over the recorded history; zeros when
+    /// nothing has been recorded yet.
+    pub fn cpu_percentiles(&self) -> (f32, f32, f32) {
+        percentiles(&self.cpu_usage_history)
+    }
+
+    /// p50, p95 and p99 of global memory usage over the recorded history.
+    pub fn memory_percentiles(&self) -> (f32, f32, f32) {
+        percentiles(&self.memory_usage_history)
+    }
+
+    /// Hottest CPU sensor reading in °C, or `None` on machines without CPU sensors.
+    ///
+    /// Sensors are updated together with CPU and memory in `refresh`.
+    pub fn cpu_temperature(&self) -> Option<f32> {
+        self.system
+            .components()
+            .iter()
+            .filter(|component| is_cpu_sensor(component.label()))
+            .map(|component| component.temperature())
+            // Unreadable sensors report NaN or 0 rather than being left out
+            .filter(|temperature| temperature.is_finite() && *temperature > 0.0)
+            .max_by(|a, b| a.total_cmp(b))
+    }
+
+    /// Average CPU utilization since boot, read from the aggregate line of `/proc/stat`.
+    pub fn cpu_since_boot(&self) -> Option<CpuSinceBoot> {
+        let (total, idle) = read_cpu_times()?;
+        if total == 0 {
+            return None;
+        }
+        let idle_percent = idle as f32 / total as f32 * 100.0;
+        Some(CpuSinceBoot {
+            usage_percent: 100.0 - idle_percent,
+            idle_percent,
+        })
+    }
+
+    /// Reads the interface counters in `/proc/net/dev` and works out each interface's
+    /// rates since the previous read. Rates are 0 on the first read and for an
+    /// interface that just appeared. `refresh` calls this too.
+    pub fn refresh_network(&mut self) {
+        let Ok(contents) = fs::read_to_string("/proc/net/dev") else {
+            warn!("Could not read /proc/net/dev");
+            self.interfaces.clear();
+            return;
+        };
+        let now = Instant::now();
+        let counters: HashMap<String, (u64, u64)> = parse_net_dev(&contents).into_iter().collect();
+        let secs = self
+            .interfaces_read_at
+            .map_or(0.0, |then| now.duration_since(then).as_secs_f64().max(0.001));
+        // Counters restart when an interface is recreated
+        let rate = |now: u64, then: u64| (now.saturating_sub(then) as f64 / secs) as u64;
+        let mut interfaces: Vec<InterfaceTraffic> = counters
+            .iter()
+            .map(|(name, &(rx_total, tx_total))| {
+                let (rx_per_sec, tx_per_sec) = match self.interface_counters.get(name) {
+                    Some(&(rx, tx)) => (rate(rx_total, rx), rate(tx_total, tx)),
+                    None => (0, 0),
+                };
+                InterfaceTraffic { name: name.clone(), rx_total, tx_total, rx_per_sec, tx_per_sec }
+            })
+            .collect();
+        interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+        self.interfaces = interfaces;
+        self.interface_counters = counters;
+        self.interfaces_read_at = Some(now);
+    }
+
+    /// Every network interface as of the last `refresh_network`, by name.
+    pub fn network_interfaces(&self) -> &[InterfaceTraffic] {
+        &self.interfaces
+    }
+
+    /// Received and sent bytes per second on all interfaces but loopback, oldest
+    /// first, lined up with `get_cpu_usage_history`.
+    pub fn network_history(&self) -> &[(u64, u64)] {
+        &self.network_history
+    }
+
+    /// Bytes read and written per second on all physical disks, oldest first, lined
+    /// up with `get_cpu_usage_history`. Per-process figures are in `ProcessInfo`.
+    pub fn disk_io_history(&self) -> &[(u64, u64)] {
+        &self.disk_io_history
+    }
+
+    pub fn refresh_disks(&mut self) {
+        // Re-read the mount list so newly mounted filesystems show up
+        self.system.refresh_disks_list();
+        self.system.refresh_disks();
+    }
+
+    pub fn disk_usage(&self) -> Vec<DiskInfo> {
+        self.system
+            .disks()
+            .iter()
+            .map(|disk| {
+                let total_space = disk.total_space();
+                let available_space = disk.available_space();
+                let usage_percent = if total_space > 0 {
+                    (total_space - available_space) as f32 / total_space as f32 * 100.0
+                } else {
+                    0.0
+                };
+                DiskInfo {
+                    name: disk.name().to_string_lossy().into_owned(),
+                    mount_point: disk.mount_point().display().to_string(),
+                    file_system: String::from_utf8_lossy(disk.file_system()).into_owned(),
+                    total_space,
+                    available_space,
+                    usage_percent,
+                }
+            })
+            .collect()
+    }
+
     pub fn refresh_processes(&mut self) -> Vec<ProcessInfo> {
+        let mut processes = self.scan_processes();
+        // There is always at least this process, so an empty scan is a failed read
+        if processes.is_empty() {
+            warn!("Process scan came back empty, retrying once");
+            processes = self.scan_processes();
+        }
+        if processes.len() < self.previous_scan_len / 2 {
+            warn!(
+                previous = self.previous_scan_len,
+                current = processes.len(),
+                "Process count dropped sharply; the scan may be incomplete"
+            );
+        }
+        self.previous_scan_len = processes.len();
+
+        if let Some(package_watts) = self.package_power() {
+            let total_cpu: f32 = processes.iter().map(|p| p.cpu_usage).sum();
+            for process in &mut processes {
+                let share = if total_cpu > 0.0 { process.cpu_usage / total_cpu } else { 0.0 };
+                process.power_watts = Some(package_watts * share);
+            }
+        }
+        self.track_scan(processes)
+    }
+
+    /// Updates the per-process state kept across refreshes (histories, orphans,
+    /// averages, restarts, network shares) from a fresh scan and fills in the fields
+    /// that depend on it.
+    fn track_scan(&mut self, mut processes: Vec<ProcessInfo>) -> Vec<ProcessInfo> {
+        self.record_process_histories(&processes);
+        self.detect_orphans(&mut processes);
+        self.accumulate_session_cpu(&processes);
+        self.track_observed_cpu(&mut processes);
+        self.track_restarts(&processes);
+        self.attribute_network(&mut processes);
+        // Histories and the session totals above keep the raw values
+        if let Some(window) = self.cpu_average_window {
+            for process in &mut processes {
+                if let Some(history) = self.process_histories.get(&process.key()) {
+                    let samples = history.cpu_usage.len().min(window);
+                    let total: f32 = history.cpu_usage.iter().rev().take(samples).sum();
+                    process.cpu_usage = total / samples as f32;
+                }
+            }
+        }
+        self.last_scan = processes.clone();
+        processes
+    }
+
+    fn scan_processes(&mut self) -> Vec<ProcessInfo> {
+        let known: HashSet<Pid> = self.system.processes().keys().copied().collect();
+        wait_out(self.last_process_refresh, self.cpu_sample_interval);
         self.system.refresh_processes();
+        self.last_process_refresh = Instant::now();
+        if !self.cpu_sample_interval.is_zero()
+            && self.system.processes().keys().any(|pid| !known.contains(pid))
+        {
+            std::thread::sleep(self.cpu_sample_interval);
+            self.system.refresh_processes();
+            self.last_process_refresh = Instant::now();
+        }
         self.system
             .processes()
             .iter()
-            .map(|(pid, process)| ProcessInfo {
-                pid: pid.as_u32() as i32,
-                user: process
-                    .user_id()
-                    .map(|uid| uid.to_string())
-                    .unwrap_or_else(|| "Unknown".into()),
-                cpu_usage: process.cpu_usage(),
-                memory_usage: process.memory(),
-                command: process.name().to_string(),
-            })
+            .filter_map(|(pid, process)| build_process_info(*pid, process, &self.system))
             .collect()
     }
 
-    pub fn kill_process(&self, pid: i32) -> Result<(), String> {
-        use nix::sys::signal::{kill, Signal};
+    /// Average CPU package power in watts since the previous call, from the RAPL
+    /// powercap counters. `None` without RAPL (or permission to read it) and on the
+    /// first call, which only takes the starting reading.
+    fn package_power(&mut self) -> Option<f32> {
+        let counters = read_rapl_counters()?;
+        let now = Instant::now();
+        let previous = self.last_package_energy.replace((counters.clone(), now));
+        let (previous, then) = previous.filter(|(previous, _)| previous.len() == counters.len())?;
+
+        let elapsed = now.duration_since(then).as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+        let energy_uj: u64 = previous
+            .iter()
+            .zip(&counters)
+            .map(|(before, after)| {
+                if after.energy_uj >= before.energy_uj {
+                    after.energy_uj - before.energy_uj
+                } else {
+                    // The counter wrapped around
+                    before.max_energy_range_uj - before.energy_uj + after.energy_uj
+                }
+            })
+            .sum();
+        Some((energy_uj as f64 / 1_000_000.0 / elapsed) as f32)
+    }
+
+    /// Total CPU-seconds per command name over the lifetime of this handler,
+    /// busiest first. Processes sharing a name (e.g. browser tabs) are summed.
+    pub fn session_cpu_by_command(&self) -> Vec<(String, f64)> {
+        let mut totals: Vec<(String, f64)> = self
+            .session_cpu
+            .iter()
+            .map(|(command, seconds)| (command.clone(), *seconds))
+            .collect();
+        totals.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        totals
+    }
+
+    /// Flags processes whose parent changed since they were first seen. A process only
+    /// gets a new parent when the old one exits, so these have lost their parent.
+    fn detect_orphans(&mut self, processes: &mut [ProcessInfo]) {
+        let alive: HashSet<ProcessKey> = processes.iter().map(ProcessInfo::key).collect();
+        self.original_parents.retain(|key, _| alive.contains(key));
+
+        for process in processes {
+            let Some(ppid) = process.ppid else { continue };
+            let original = *self.original_parents.entry(process.key()).or_insert(ppid);
+            if original != ppid {
+                process.orphaned_from = Some(original);
+            }
+        }
+    }
+
+    fn track_observed_cpu(&mut self, processes: &mut [ProcessInfo]) {
+        let alive: HashSet<ProcessKey> = processes.iter().map(ProcessInfo::key).collect();
+        self.observed_cpu.retain(|key, _| alive.contains(key));
+
+        for process in processes {
+            let (sum, count) = self.observed_cpu.entry(process.key()).or_default();
+            *sum += process.cpu_usage as f64;
+            *count += 1;
+            process.observed_avg_cpu = (*sum / *count as f64) as f32;
+        }
+    }
+
+    /// Counts a restart whenever a new PID appears with the command of a process
+    /// that exited less than `RESTART_WINDOW` ago, as supervisors respawn crashed
+    /// services under a new PID.
+    fn track_restarts(&mut self, processes: &[ProcessInfo]) {
+        let now = Instant::now();
+        self.recent_exits.retain(|(_, exited)| now.duration_since(*exited) <= RESTART_WINDOW);
+
+        // On the first scan every process is new; none of them restarted
+        if !self.known_commands.is_empty() {
+            let alive: HashSet<ProcessKey> = processes.iter().map(ProcessInfo::key).collect();
+            for (key, command) in &self.known_commands {
+                if !alive.contains(key) {
+                    self.recent_exits.push((command.clone(), now));
+                }
+            }
+            let new = processes.iter().filter(|p| !self.known_commands.contains_key(&p.key()));
+            for process in new {
+                let exit = self.recent_exits.iter().position(|(command, _)| {
+                    *command == process.command
+                });
+                if let Some(index) = exit {
+                    self.recent_exits.remove(index);
+                    *self.restart_counts.entry(process.command.clone()).or_default() += 1;
+                }
+            }
+        }
+
+        self.known_commands = processes.iter().map(|p| (p.key(), p.command.clone())).collect();
+    }
+
+    /// Measures each network namespace's traffic since the previous refresh and hands
+    /// it to the processes in non-host namespaces (typically containers).
+    fn attribute_network(&mut self, processes: &mut [ProcessInfo]) {
+        let mut members: HashMap<&str, Vec<i32>> = HashMap::new();
+        for process in processes.iter() {
+            if let Some(namespace) = process.namespaces.get("net") {
+                members.entry(namespace).or_default().push(process.pid);
+            }
+        }
+
+        let host = self.host_namespaces.get("net");
+        let mut counters = HashMap::new();
+        let mut traffic = Vec::new();
+        for (namespace, pids) in members {
+            // Every member sees the same counters; try the next one if a process exited
+            let Some(current) = pids.iter().find_map(|&pid| read_net_dev(pid)) else {
+                continue;
+            };
+            if let Some(&(rx, tx)) = self.net_counters.get(namespace) {
+                traffic.push(NamespaceTraffic {
+                    namespace: namespace.to_string(),
+                    host: host.is_some_and(|host| host == namespace),
+                    // Counters restart when an interface is recreated
+                    rx_bytes: current.0.saturating_sub(rx),
+                    tx_bytes: current.1.saturating_sub(tx),
+                    pids,
+                });
+            }
+            counters.insert(namespace.to_string(), current);
+        }
+        self.net_counters = counters;
+
+        let shared: HashMap<&str, (u64, u64)> = traffic
+            .iter()
+            .filter(|t| !t.host)
+            .map(|t| (t.namespace.as_str(), (t.rx_bytes, t.tx_bytes)))
+            .collect();
+        for process in processes {
+            let totals = process.namespaces.get("net").and_then(|ns| shared.get(ns.as_str()));
+            process.net_rx = totals.map(|(rx, _)| *rx);
+            process.net_tx = totals.map(|(_, tx)| *tx);
+        }
+        traffic.sort_by_key(|t| std::cmp::Reverse(t.rx_bytes + t.tx_bytes));
+        self.network_traffic = traffic;
+    }
+
+    /// Traffic per network namespace between the last two `refresh_processes` calls,
+    /// busiest first. Empty until the second call.
+    pub fn network_traffic(&self) -> &[NamespaceTraffic] {
+        &self.network_traffic
+    }
+
+    /// How many times each command has been restarted this session, by name.
+    pub fn restart_counts(&self) -> HashMap<String, u32> {
+        self.restart_counts.clone()
+    }
+
+    fn accumulate_session_cpu(&mut self, processes: &[ProcessInfo]) {
+        let now = Instant::now();
+        // CPU usage is averaged over the time since the previous sample, so usage
+        // multiplied by that time is the CPU time used in between
+        let Some(previous) = self.last_cpu_sample.replace(now) else {
+            return;
+        };
+        let elapsed = now.duration_since(previous).as_secs_f64();
+        for process in processes.iter().filter(|p| p.cpu_usage > 0.0) {
+            *self.session_cpu.entry(process.command.clone()).or_default() +=
+                process.cpu_usage as f64 / 100.0 * elapsed;
+        }
+    }
+
+    /// Refreshes only the given PIDs, which is much cheaper than a full scan.
+    ///
+    /// Each PID is paired with its info, or `None` if it has exited. Histories are
+    /// kept for the watched processes only.
+    pub fn refresh_watched(&mut self, pids: &[i32]) -> Vec<(i32, Option<ProcessInfo>)> {
+        wait_out(self.last_process_refresh, self.cpu_sample_interval);
+        let watched: Vec<(i32, Option<ProcessInfo>)> = pids
+            .iter()
+            .map(|&pid| {
+                let sys_pid = Pid::from_u32(pid as u32);
+                let info = self
+                    .system
+                    .refresh_process(sys_pid)
+                    .then(|| self.system.process(sys_pid))
+                    .flatten()
+                    .and_then(|process| build_process_info(sys_pid, process, &self.system));
+                (pid, info)
+            })
+            .collect();
+
+        let found: Vec<ProcessInfo> = watched.iter().filter_map(|(_, info)| info.clone()).collect();
+        self.record_process_histories(&found);
+        self.last_scan = found;
+        watched
+    }
+
+    /// Processes found by the most recent `refresh_processes`.
+    pub fn last_scan(&self) -> &[ProcessInfo] {
+        &self.last_scan
+    }
+
+    /// Children of each process in the most recent scan by parent PID, in PID order.
+    pub fn process_tree(&self) -> HashMap<i32, Vec<i32>> {
+        let mut tree: HashMap<i32, Vec<i32>> = HashMap::new();
+        for process in &self.last_scan {
+            if let Some(ppid) = process.ppid.filter(|&ppid| ppid != process.pid) {
+                tree.entry(ppid).or_default().push(process.pid);
+            }
+        }
+        for children in tree.values_mut() {
+            children.sort_unstable();
+        }
+        tree
+    }
+
+    /// Every process below `pid` in the most recent scan, parents before children.
+    pub fn descendants(&self, pid: i32) -> Vec<i32> {
+        let tree = self.process_tree();
+        let mut found = Vec::new();
+        let mut seen = HashSet::from([pid]);
+        let mut parents = VecDeque::from([pid]);
+        while let Some(parent) = parents.pop_front() {
+            for &child in tree.get(&parent).into_iter().flatten() {
+                if seen.insert(child) {
+                    found.push(child);
+                    parents.push_back(child);
+                }
+            }
+        }
+        found
+    }
+
+    /// Number of processes in each state as of the most recent refresh.
+    pub fn status_counts(&self) -> HashMap<String, usize> {
+        count_by_status(&self.last_scan)
+    }
+
+    /// History of the live process with this PID. Exited processes are forgotten on
+    /// each refresh, so at most one entry matches.
+    pub fn process_history(&self, pid: i32) -> Option<&ProcessHistory> {
+        self.process_histories
+            .iter()
+            .find(|(key, _)| key.pid == pid)
+            .map(|(_, history)| history)
+    }
+
+    /// This process's own CPU usage averaged over its last `window` samples and its
+    /// latest resident memory, to show what monitoring costs; `None` before the
+    /// first full scan.
+    pub fn own_usage(&self, window: usize) -> Option<(f32, u64)> {
+        let history = self.process_history(std::process::id() as i32)?;
+        let recent: Vec<f32> =
+            history.cpu_usage.iter().rev().take(window.max(1)).copied().collect();
+        let memory = *history.memory_usage.back()?;
+        Some((recent.iter().sum::<f32>() / recent.len().max(1) as f32, memory))
+    }
+
+    /// PIDs ranked by how much their resident memory changed over the last `window`
+    /// samples, largest absolute change first.
+    ///
+    /// The delta is signed, so processes that freed memory rank by the amount released.
+    /// Processes with fewer samples are measured over the samples they have;
+    /// those with only one sample are left out.
+    pub fn memory_growth_leaderboard(&self, window: usize) -> Vec<(i32, i64)> {
+        let mut leaderboard: Vec<(i32, i64)> = self
+            .process_histories
+            .iter()
+            .filter_map(|(key, history)| {
+                let samples = &history.memory_usage;
+                if samples.len() < 2 {
+                    return None;
+                }
+                let first = samples[samples.len().saturating_sub(window.max(2))];
+                let last = *samples.back()?;
+                Some((key.pid, last as i64 - first as i64))
+            })
+            .collect();
+        leaderboard.sort_by_key(|&(pid, delta)| (std::cmp::Reverse(delta.unsigned_abs()), pid));
+        leaderboard
+    }
+
+    /// The process with the highest CPU usage averaged over the last `window`
+    /// samples, with that average. Processes younger than the window count as idle
+    /// before they started, so a short burst doesn't outrank steady load. `None`
+    /// before the first refresh.
+    pub fn top_cpu_consumer(&self, window: usize) -> Option<(i32, f32)> {
+        // Early on every history is shorter than the window; average over what exists
+        let samples = self
+            .process_histories
+            .values()
+            .map(|history| history.cpu_usage.len())
+            .max()?
+            .min(window)
+            .max(1);
+        self.process_histories
+            .iter()
+            .map(|(key, history)| {
+                let total: f32 = history.cpu_usage.iter().rev().take(samples).sum();
+                (key.pid, total / samples as f32)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+    }
+
+    fn record_process_histories(&mut self, processes: &[ProcessInfo]) {
+        // Forget processes that have exited so the map doesn't grow without bound; a new
+        // process that reused one of their PIDs starts with an empty history
+        let alive: HashSet<ProcessKey> = processes.iter().map(ProcessInfo::key).collect();
+        self.process_histories.retain(|key, _| alive.contains(key));
+
+        for process in processes {
+            let history = self.process_histories.entry(process.key()).or_default();
+            history.cpu_usage.push_back(process.cpu_usage);
+            history.memory_usage.push_back(process.memory_usage);
+            if history.cpu_usage.len() > self.history_capacity {
+                history.cpu_usage.pop_front();
+                history.memory_usage.pop_front();
+            }
+        }
+    }
+
+    /// Refreshes and returns a single process, or `None` if it doesn't exist.
+    pub fn process_info(&mut self, pid: i32) -> Option<ProcessInfo> {
+        let pid = Pid::from_u32(pid as u32);
+        if !self.system.refresh_process(pid) {
+            return None;
+        }
+        self.system
+            .process(pid)
+            .and_then(|process| build_process_info(pid, process, &self.system))
+    }
+
+    /// TCP ports the process is listening on. Fails when its open files can't be
+    /// read, which for other users' processes requires root.
+    pub fn listening_ports(&self, pid: i32) -> Result<Vec<u16>, ProcessError> {
+        ports::listening_ports(pid)
+    }
+
+    /// Memory and CPU limits from the process's cgroup, with its memory usage, so
+    /// you can tell how close a container is to being OOM-killed. `None` on systems
+    /// without cgroup v2 or when the process has exited.
+    pub fn cgroup_limits(&self, pid: i32) -> Option<CgroupLimits> {
+        cgroup::read_limits(pid)
+    }
+
+    /// Namespaces of the host, used to tell containerized processes apart.
+    pub fn host_namespaces(&self) -> &HashMap<String, String> {
+        &self.host_namespaces
+    }
+
+    /// Whether a process still exists. Zombies count as gone: they have exited and
+    /// only wait for their parent to collect the exit status.
+    pub fn is_alive(&self, pid: i32) -> bool {
+        use nix::sys::signal::kill;
         use nix::unistd::Pid;
 
-        match kill(Pid::from_raw(pid), Signal::SIGTERM) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(format!("Failed to kill process {}: {}", pid, e)),
+        // Signal 0 only checks that the process exists; EPERM means it exists but isn't ours
+        let exists = match kill(Pid::from_raw(pid), None) {
+            Ok(()) => true,
+            Err(e) => e == nix::errno::Errno::EPERM,
+        };
+        exists && process_state(pid) != Some('Z')
+    }
+
+    /// Reports each process's CPU usage as the average of its last `window` samples,
+    /// or the instantaneous value when `None`.
+    pub fn set_cpu_averaging(&mut self, window: Option<usize>) {
+        self.cpu_average_window = window;
+    }
+
+    /// Sets the policy consulted before any signal is sent.
+    pub fn set_kill_policy(&mut self, policy: KillPolicy) {
+        self.kill_policy = policy;
+    }
+
+    /// Records quarantines in `path` instead of the shared state file, e.g. to keep
+    /// tests away from the user's quarantines.
+    pub fn set_quarantine_file(&mut self, path: PathBuf) {
+        self.quarantine_file = Some(path);
+    }
+
+    fn quarantine_file(&self) -> std::io::Result<PathBuf> {
+        match &self.quarantine_file {
+            Some(path) => Ok(path.clone()),
+            None => quarantine::state_path(),
+        }
+    }
+
+    /// Scheduling policy of a process and its real-time priority (0 under the
+    /// normal policies).
+    pub fn get_sched_policy(&self, pid: i32) -> Result<(SchedPolicy, i32), ProcessError> {
+        use nix::errno::Errno;
+        use nix::libc;
+
+        let action = "read the scheduling policy of";
+        // SAFETY: plain syscalls on a PID; `param` is a valid sched_param to fill in
+        let policy = Errno::result(unsafe { libc::sched_getscheduler(pid) })
+            .map_err(|e| ProcessError::from_errno(pid, action, e))?;
+        let mut param = libc::sched_param { sched_priority: 0 };
+        Errno::result(unsafe { libc::sched_getparam(pid, &mut param) })
+            .map_err(|e| ProcessError::from_errno(pid, action, e))?;
+        // Children going back to SCHED_OTHER on fork is a flag on top of the policy
+        let policy = match policy & !libc::SCHED_RESET_ON_FORK {
+            libc::SCHED_FIFO => SchedPolicy::Fifo,
+            libc::SCHED_RR => SchedPolicy::RoundRobin,
+            libc::SCHED_BATCH => SchedPolicy::Batch,
+            libc::SCHED_IDLE => SchedPolicy::Idle,
+            libc::SCHED_DEADLINE => SchedPolicy::Deadline,
+            _ => SchedPolicy::Other,
+        };
+        Ok((policy, param.sched_priority))
+    }
+
+    /// Changes a process's scheduling policy. `priority` must be 1-99 for the
+    /// real-time policies and 0 for the others; real-time policies need root or
+    /// CAP_SYS_NICE.
+    pub fn set_sched_policy(
+        &self,
+        pid: i32,
+        policy: SchedPolicy,
+        priority: i32,
+    ) -> Result<(), ProcessError> {
+        use nix::errno::Errno;
+        use nix::libc;
+
+        let raw_policy = match policy {
+            SchedPolicy::Other => libc::SCHED_OTHER,
+            SchedPolicy::Fifo => libc::SCHED_FIFO,
+            SchedPolicy::RoundRobin => libc::SCHED_RR,
+            SchedPolicy::Batch => libc::SCHED_BATCH,
+            SchedPolicy::Idle => libc::SCHED_IDLE,
+            // Takes runtime, deadline and period through sched_setattr instead
+            SchedPolicy::Deadline => {
+                return Err(ProcessError::InvalidArgument(
+                    "SCHED_DEADLINE can't be set here; use chrt --deadline".into(),
+                ))
+            }
+        };
+        let valid = if policy.is_realtime() { 1..=99 } else { 0..=0 };
+        if !valid.contains(&priority) {
+            return Err(ProcessError::InvalidArgument(format!(
+                "Priority for {} must be {} to {}",
+                policy.label(),
+                valid.start(),
+                valid.end()
+            )));
+        }
+        let param = libc::sched_param { sched_priority: priority };
+        // SAFETY: plain syscall on a PID; `param` outlives the call
+        Errno::result(unsafe { libc::sched_setscheduler(pid, raw_policy, &param) })
+            .map(drop)
+            .map_err(|e| ProcessError::from_errno(pid, "change the scheduling policy of", e))
+    }
+
+    /// Start time, working directory, environment and open file count of a process.
+    /// Fails only when the process doesn't exist; fields it isn't allowed to read
+    /// hold the error instead.
+    pub fn get_process_details(&self, pid: i32) -> Result<ProcessDetails, ProcessError> {
+        let (_, _, start_ticks) = read_sched_state(pid)?;
+        let read_error = |action, e| ProcessError::from_io(pid, action, e).to_string();
+        Ok(ProcessDetails {
+            started_at: clock_ticks_per_sec().map(|ticks| {
+                UNIX_EPOCH
+                    + Duration::from_secs(self.system.boot_time())
+                    + Duration::from_secs_f64(start_ticks as f64 / ticks)
+            }),
+            cwd: fs::read_link(format!("/proc/{}/cwd", pid))
+                .map(|path| path.display().to_string())
+                .map_err(|e| read_error("read the working directory of", e)),
+            environment: fs::read(format!("/proc/{}/environ", pid))
+                .map(|environ| parse_environ(&environ))
+                .map_err(|e| read_error("read the environment of", e)),
+            open_fds: fs::read_dir(format!("/proc/{}/fd", pid))
+                .map(|entries| entries.count())
+                .map_err(|e| read_error("list the open files of", e)),
+        })
+    }
+
+    /// How much more (positive) or less (negative) likely the OOM killer is to pick
+    /// the process, from -1000 (never) to 1000 (first).
+    pub fn get_oom_score_adj(&self, pid: i32) -> Result<i32, ProcessError> {
+        let contents = fs::read_to_string(format!("/proc/{}/oom_score_adj", pid))
+            .map_err(|e| ProcessError::from_io(pid, "read the OOM score adjustment of", e))?;
+        contents.trim().parse().map_err(|_| {
+            let message = format!("Unexpected oom_score_adj for PID {}: {}", pid, contents.trim());
+            std::io::Error::new(std::io::ErrorKind::InvalidData, message).into()
+        })
+    }
+
+    /// Sets the OOM score adjustment, -1000 to 1000. Anyone can raise their own
+    /// processes' value; lowering it below what it was needs root or
+    /// CAP_SYS_RESOURCE.
+    pub fn set_oom_score_adj(&self, pid: i32, value: i32) -> Result<(), ProcessError> {
+        if !(-1000..=1000).contains(&value) {
+            return Err(ProcessError::InvalidArgument(
+                "OOM score adjustment must be -1000 to 1000".into(),
+            ));
+        }
+        let current = self.get_oom_score_adj(pid)?;
+        let action = if value < current {
+            "lower the OOM score adjustment of"
+        } else {
+            "change the OOM score adjustment of"
+        };
+        fs::write(format!("/proc/{}/oom_score_adj", pid), value.to_string())
+            .map_err(|e| ProcessError::from_io(pid, action, e))
+    }
+
+    /// Kills a process with `signal`, usually one of `KILL_SIGNALS`, unless the kill
+    /// policy forbids it. Used by both the GUI and the `kill` command.
+    pub fn kill_process(
+        &self,
+        pid: i32,
+        signal: nix::sys::signal::Signal,
+    ) -> Result<(), ProcessError> {
+        self.send_signal(pid, signal)
+    }
+
+    /// Kills a process with `signal` and then every process below it, so a parent
+    /// that restarts its children can't replace them. The children are left alone
+    /// when the process itself can't be signalled; otherwise the ones that couldn't
+    /// be are returned, leaving out those that already exited with their parent.
+    pub fn kill_tree(
+        &self,
+        pid: i32,
+        signal: nix::sys::signal::Signal,
+    ) -> Result<Vec<(i32, ProcessError)>, ProcessError> {
+        // Found before the kill, while the children still have this parent
+        let descendants = self.descendants(pid);
+        self.kill_process(pid, signal)?;
+        Ok(descendants
+            .into_iter()
+            .filter_map(|child| match self.kill_process(child, signal) {
+                Ok(()) | Err(ProcessError::NotFound(_)) => None,
+                Err(e) => Some((child, e)),
+            })
+            .collect())
+    }
+
+    /// Describes what `send_signal` would do, e.g. "send SIGTERM to PID 1234 (chrome)",
+    /// without sending anything. Fails the same way when the kill policy forbids it.
+    pub fn describe_signal(
+        &self,
+        pid: i32,
+        signal: nix::sys::signal::Signal,
+    ) -> Result<String, ProcessError> {
+        let command = self.command_name(pid);
+        if !self.kill_policy.permits(pid, &read_names(pid)) {
+            return Err(ProcessError::PolicyDenied { pid, command });
+        }
+        Ok(format!("send {} to PID {} ({})", signal.as_str(), pid, command))
+    }
+
+    /// Sends `signal` to a process, unless the kill policy forbids it.
+    pub fn send_signal(
+        &self,
+        pid: i32,
+        signal: nix::sys::signal::Signal,
+    ) -> Result<(), ProcessError> {
+        use nix::sys::signal::kill;
+        use nix::unistd::Pid;
+
+        self.describe_signal(pid, signal)?;
+        kill(Pid::from_raw(pid), signal).map_err(|e| ProcessError::from_errno(pid, "signal", e))
+    }
+
+    /// Freezes a suspected runaway without killing it: stops it with SIGSTOP, sets its
+    /// nice value to 19 and pins it to the first CPU it may run on, so it stays off the
+    /// other cores even if something else resumes it. The original state is recorded
+    /// for `unquarantine`. Refused like a signal when the kill policy forbids it.
+    ///
+    /// The record is saved before the process is touched. If any step fails, the
+    /// ones already taken are undone and the record removed again, so a process is
+    /// never left stopped without a way to release it.
+    pub fn quarantine(&self, pid: i32) -> Result<QuarantineRecord, ProcessError> {
+        use nix::sched::{sched_getaffinity, CpuSet};
+        use nix::sys::signal::Signal;
+        use nix::unistd::Pid;
+
+        let (state, nice, start_time) = read_sched_state(pid)?;
+        self.describe_signal(pid, Signal::SIGSTOP)?;
+        let mut records = self.quarantined()?;
+        if records.iter().any(|record| record.pid == pid) {
+            return Err(ProcessError::InvalidArgument(format!(
+                "PID {} is already quarantined",
+                pid
+            )));
+        }
+        let allowed = sched_getaffinity(Pid::from_raw(pid))
+            .map_err(|e| ProcessError::from_errno(pid, "read the CPU affinity of", e))?;
+        let cpus: Vec<usize> =
+            (0..CpuSet::count()).filter(|&cpu| allowed.is_set(cpu).unwrap_or(false)).collect();
+
+        let record = QuarantineRecord {
+            pid,
+            start_time,
+            command: self.command_name(pid),
+            nice,
+            cpus,
+            was_stopped: state == 'T',
+        };
+        records.push(record.clone());
+        quarantine::save(&self.quarantine_file()?, &records)?;
+
+        let applied = self.send_signal(pid, Signal::SIGSTOP).and_then(|()| {
+            set_nice(pid, 19, "change the nice value of")?;
+            match record.cpus.first() {
+                Some(&first) => set_affinity(pid, &[first]),
+                None => Ok(()),
+            }
+        });
+        if let Err(e) = applied {
+            self.roll_back_quarantine(&record);
+            return Err(e);
+        }
+        Ok(record)
+    }
+
+    /// Undoes whatever part of a quarantine was applied and forgets its record. Each
+    /// step is tried even when an earlier one fails; failures are only logged, since
+    /// the caller reports the error that made it roll back.
+    fn roll_back_quarantine(&self, record: &QuarantineRecord) {
+        use nix::sys::signal::Signal;
+
+        let pid = record.pid;
+        if let Err(e) = set_affinity(pid, &record.cpus) {
+            warn!("Rolling back quarantine: {}", e);
+        }
+        if !record.was_stopped {
+            if let Err(e) = self.send_signal(pid, Signal::SIGCONT) {
+                warn!("Rolling back quarantine: {}", e);
+            }
+        }
+        if let Err(e) = set_nice(pid, record.nice, RESTORE_NICE) {
+            warn!("Rolling back quarantine: {}", e);
+        }
+        let removed = self.quarantine_file().and_then(|path| {
+            let mut records = quarantine::load(&path)?;
+            records.retain(|other| other != record);
+            quarantine::save(&path, &records)
+        });
+        if let Err(e) = removed {
+            warn!("Failed to remove the quarantine record of PID {}: {}", pid, e);
+        }
+    }
+
+    /// Releases a quarantined process: restores its CPU affinity, resumes it unless it
+    /// was already stopped before quarantine, and restores its nice value. Lowering a
+    /// nice value needs root or CAP_SYS_NICE; without them the process is resumed but
+    /// stays at 19, and `PermissionDenied` is returned with the `RESTORE_NICE` action.
+    pub fn unquarantine(&self, pid: i32) -> Result<QuarantineRecord, ProcessError> {
+        use nix::sys::signal::Signal;
+
+        let path = self.quarantine_file()?;
+        let mut records = quarantine::load(&path)?;
+        let Some(index) = records.iter().position(|record| record.pid == pid) else {
+            return Err(ProcessError::InvalidArgument(format!("PID {} isn't quarantined", pid)));
+        };
+        let record = records[index].clone();
+        match read_sched_state(pid) {
+            Ok((_, _, start_time)) if start_time == record.start_time => {}
+            // It exited, and the PID may belong to another process by now
+            Ok(_) | Err(ProcessError::NotFound(_)) => {
+                records.remove(index);
+                quarantine::save(&path, &records)?;
+                return Err(ProcessError::NotFound(pid));
+            }
+            Err(e) => return Err(e),
+        }
+
+        set_affinity(pid, &record.cpus)?;
+        if !record.was_stopped {
+            self.send_signal(pid, Signal::SIGCONT)?;
+        }
+        records.remove(index);
+        quarantine::save(&path, &records)?;
+        set_nice(pid, record.nice, RESTORE_NICE)?;
+        Ok(record)
+    }
+
+    /// Recorded quarantines of processes that are still running.
+    pub fn quarantined(&self) -> Result<Vec<QuarantineRecord>, ProcessError> {
+        let mut records = quarantine::load(&self.quarantine_file()?)?;
+        records.retain(|record| {
+            matches!(read_sched_state(record.pid), Ok((_, _, start_time))
+                if start_time == record.start_time)
+        });
+        Ok(records)
+    }
+
+    /// Command name from the last refresh; empty for a process it didn't see.
+    fn command_name(&self, pid: i32) -> String {
+        self.system
+            .process(sysinfo::Pid::from_u32(pid as u32))
+            .map(|process| process.name().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Total and idle CPU time of all CPUs since boot in clock ticks, from the aggregate
+/// line of `/proc/stat`.
+fn read_cpu_times() -> Option<(u64, u64)> {
+    let stat = fs::read_to_string("/proc/stat").ok()?;
+    let times: Vec<u64> = stat
+        .lines()
+        .next()?
+        .strip_prefix("cpu ")?
+        .split_whitespace()
+        .filter_map(|value| value.parse().ok())
+        .collect();
+    // user nice system idle iowait irq softirq steal (guest time is already in user)
+    if times.len() < 5 {
+        return None;
+    }
+    Some((times.iter().take(8).sum(), times[3] + times[4]))
+}
+
+/// Names a process goes by, read fresh from `/proc` so the kill policy sees the process
+/// the PID belongs to now: its command name, then the basenames of its executable and
+/// of argv[0]. Names that can't be read are left out.
+fn read_names(pid: i32) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Ok(comm) = fs::read_to_string(format!("/proc/{}/comm", pid)) {
+        names.push(comm.trim_end_matches('\n').to_string());
+    }
+    let exe = fs::read_link(format!("/proc/{}/exe", pid))
+        .ok()
+        .map(|exe| exe.to_string_lossy().trim_end_matches(" (deleted)").to_string());
+    let argv0 = fs::read(format!("/proc/{}/cmdline", pid)).ok().map(|cmdline| {
+        let argv0 = cmdline.split(|&byte| byte == 0).next().unwrap_or_default();
+        String::from_utf8_lossy(argv0).into_owned()
+    });
+    for path in exe.into_iter().chain(argv0) {
+        let name = path.rsplit('/').next().unwrap_or(&path).to_string();
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// State letter, nice value and start time of a process, from `/proc/<pid>/stat`.
+fn read_sched_state(pid: i32) -> Result<(char, i32, u64), ProcessError> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid))
+        .map_err(|e| ProcessError::from_io(pid, "read the state of", e))?;
+    let fields = stat_fields(&stat);
+    let state = fields.first().and_then(|state| state.chars().next());
+    // nice is field 19 and starttime field 22; the list starts at field 3
+    let nice = fields.get(16).and_then(|value| value.parse().ok());
+    let start_time = fields.get(19).and_then(|value| value.parse().ok());
+    match (state, nice, start_time) {
+        (Some(state), Some(nice), Some(start_time)) => Ok((state, nice, start_time)),
+        // Only a process that exited mid-read leaves the file short
+        _ => Err(ProcessError::NotFound(pid)),
+    }
+}
+
+fn set_nice(pid: i32, nice: i32, action: &'static str) -> Result<(), ProcessError> {
+    use nix::errno::Errno;
+    use nix::libc;
+
+    // SAFETY: plain syscall on a PID
+    Errno::result(unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) })
+        .map(drop)
+        .map_err(|e| ProcessError::from_errno(pid, action, e))
+}
+
+fn set_affinity(pid: i32, cpus: &[usize]) -> Result<(), ProcessError> {
+    use nix::sched::{sched_setaffinity, CpuSet};
+    use nix::unistd::Pid;
+
+    let action = "change the CPU affinity of";
+    let mut allowed = CpuSet::new();
+    for &cpu in cpus {
+        allowed.set(cpu).map_err(|e| ProcessError::from_errno(pid, action, e))?;
+    }
+    sched_setaffinity(Pid::from_raw(pid), &allowed)
+        .map_err(|e| ProcessError::from_errno(pid, action, e))
+}
+
+/// Signals offered for killing a process, by the `kill` command and the GUI's signal
+/// selector. SIGTERM, the default, comes first.
+pub const KILL_SIGNALS: [nix::sys::signal::Signal; 3] = {
+    use nix::sys::signal::Signal;
+    [Signal::SIGTERM, Signal::SIGKILL, Signal::SIGHUP]
+};
+
+/// One of `KILL_SIGNALS` by name, e.g. "SIGKILL".
+pub fn parse_signal(name: &str) -> Result<nix::sys::signal::Signal, ProcessError> {
+    KILL_SIGNALS
+        .into_iter()
+        .find(|signal| signal.as_str() == name)
+        .ok_or_else(|| ProcessError::InvalidSignal(name.to_string()))
+}
+
+/// Sleeps until `interval` has passed since `since`.
+fn wait_out(since: Instant, interval: Duration) {
+    if let Some(left) = interval.checked_sub(since.elapsed()) {
+        std::thread::sleep(left);
+    }
+}
+
+/// `None` when the process exited after sysinfo listed it: its `/proc` files are gone,
+/// and the fields read from them would all be zeros.
+fn build_process_info(pid: Pid, process: &Process, system: &System) -> Option<ProcessInfo> {
+    let pid = pid.as_u32() as i32;
+    let total_memory = system.total_memory();
+    // Fields sysinfo doesn't expose come from /proc/<pid>/status and stat
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let stat = stat_fields(&stat);
+    if stat.is_empty() {
+        return None;
+    }
+    let disk_usage = process.disk_usage();
+    let run_time_secs = process.run_time();
+    // The kernel appends " (deleted)" to the exe link once the file is gone. sysinfo
+    // leaves the path empty when the link can't be read (another user's process).
+    let exe = process.exe().display().to_string();
+    let (exe, exe_deleted) = match exe.strip_suffix(" (deleted)") {
+        Some(path) => (path.to_string(), true),
+        None => (exe, false),
+    };
+    Some(ProcessInfo {
+        pid,
+        // sysinfo only reads the parent once, so it would miss reparenting
+        ppid: status_value(&status, "PPid")
+            .and_then(|value| value.parse().ok())
+            .filter(|&ppid| ppid != 0),
+        // Filled in by `refresh_processes`, which remembers earlier parents
+        orphaned_from: None,
+        // Users created after startup aren't in sysinfo's list, so they show as uid:N
+        user: match process.user_id() {
+            Some(uid) => system
+                .get_user_by_id(uid)
+                .map_or_else(|| format!("uid:{}", **uid), |user| user.name().to_string()),
+            None => "Unknown".into(),
+        },
+        uid: process.user_id().map(|uid| **uid),
+        cpu_usage: process.cpu_usage(),
+        // Filled in by `refresh_processes`, which remembers earlier samples
+        observed_avg_cpu: process.cpu_usage(),
+        allowed_cpus: status_value(&status, "Cpus_allowed_list").map_or(0, count_cpu_list),
+        memory_usage: process.memory(),
+        memory_percent: if total_memory > 0 {
+            process.memory() as f32 / total_memory as f32 * 100.0
+        } else {
+            0.0
+        },
+        // Kernel threads have no VmHWM; the two are also read at slightly different times
+        peak_memory: status_kb(&status, "VmHWM").max(process.memory()),
+        virtual_memory: process.virtual_memory(),
+        swap_used: status_kb(&status, "VmSwap"),
+        status: process.status().to_string(),
+        command: process.name().to_string(),
+        exe,
+        exe_deleted,
+        cmdline: process.cmd().join(" "),
+        threads: status_value(&status, "Threads")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0),
+        // nice is field 19
+        nice: stat.get(16).and_then(|value| value.parse().ok()).unwrap_or(0),
+        run_time_secs,
+        // starttime is field 22
+        start_time: stat.get(19).and_then(|value| value.parse().ok()).unwrap_or(0),
+        efficiency: match cpu_time_secs(&stat) {
+            Some(cpu_time) if run_time_secs > 0 => (cpu_time / run_time_secs as f64) as f32,
+            _ => 0.0,
+        },
+        // Filled in by `refresh_processes`, which knows the package power
+        power_watts: None,
+        disk_read: disk_usage.read_bytes,
+        disk_write: disk_usage.written_bytes,
+        // Filled in by `refresh_processes`, which remembers earlier counters
+        net_rx: None,
+        net_tx: None,
+        namespaces: read_namespaces(pid),
+    })
+}
+
+/// Counts the CPUs in a kernel CPU list such as `0-3,8,10-11`; 0 if it's malformed.
+fn count_cpu_list(list: &str) -> usize {
+    let mut count = 0;
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        let (start, end) = range.split_once('-').unwrap_or((range, range));
+        match (start.parse::<usize>(), end.parse::<usize>()) {
+            (Ok(start), Ok(end)) if end >= start => count += end - start + 1,
+            _ => return 0,
+        }
+    }
+    count
+}
+
+/// Matches the labels used by the common CPU hwmon drivers: `coretemp` (Intel
+/// "Package id 0", "Core 0"), `k10temp` (AMD "Tctl"/"Tdie") and ARM `cpu_thermal`.
+fn is_cpu_sensor(label: &str) -> bool {
+    let label = label.to_lowercase();
+    ["cpu", "core", "package", "tctl", "tdie", "k10temp", "coretemp"]
+        .iter()
+        .any(|name| label.contains(name))
+}
+
+/// Reads the namespace links in `/proc/<pid>/ns/`.
+///
+/// Returns an empty map when the directory can't be read (e.g. permission denied
+/// for another user's process, or the process has exited).
+fn read_namespaces(pid: i32) -> HashMap<String, String> {
+    let entries = match fs::read_dir(format!("/proc/{}/ns", pid)) {
+        Ok(entries) => entries,
+        Err(_) => return HashMap::new(),
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let kind = entry.file_name().to_string_lossy().into_owned();
+            // `pid_for_children` etc. describe future children, not this process
+            if kind.ends_with("_for_children") {
+                return None;
+            }
+            let target = fs::read_link(entry.path()).ok()?;
+            Some((kind, target.to_string_lossy().into_owned()))
+        })
+        .collect()
+}
+
+/// Reads the energy counters of the top-level RAPL domains (one per CPU package,
+/// `/sys/class/powercap/intel-rapl:N`). Sub-domains like `intel-rapl:0:0` are part
+/// of their package and are skipped.
+fn read_rapl_counters() -> Option<Vec<RaplCounter>> {
+    let mut domains: Vec<_> = fs::read_dir("/sys/class/powercap")
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("intel-rapl:"))
+                .is_some_and(|index| !index.contains(':'))
+        })
+        .collect();
+    domains.sort();
+
+    let read = |path: PathBuf| -> Option<u64> {
+        fs::read_to_string(path).ok()?.trim().parse().ok()
+    };
+    let counters: Vec<RaplCounter> = domains
+        .into_iter()
+        .map(|domain| {
+            Some(RaplCounter {
+                energy_uj: read(domain.join("energy_uj"))?,
+                max_energy_range_uj: read(domain.join("max_energy_range_uj"))?,
+            })
+        })
+        .collect::<Option<_>>()?;
+    (!counters.is_empty()).then_some(counters)
+}
+
+/// Single-letter scheduler state from `/proc/<pid>/stat` (`R`, `S`, `Z`, ...).
+fn process_state(pid: i32) -> Option<char> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let (_, rest) = stat.rsplit_once(')')?;
+    rest.trim_start().chars().next()
+}
+
+/// Total bytes received and sent on all non-loopback interfaces of the process's
+/// network namespace, from `/proc/<pid>/net/dev`.
+fn read_net_dev(pid: i32) -> Option<(u64, u64)> {
+    let contents = fs::read_to_string(format!("/proc/{}/net/dev", pid)).ok()?;
+    Some(
+        parse_net_dev(&contents)
+            .into_iter()
+            .filter(|(interface, _)| interface != "lo")
+            .fold((0, 0), |totals, (_, (rx, tx))| (totals.0 + rx, totals.1 + tx)),
+    )
+}
+
+/// Total bytes read from and written to physical disks since boot, from
+/// `/proc/diskstats`. Partitions, loop devices, device-mapper volumes and RAM disks
+/// are left out, since their I/O is counted again on the disk underneath (or never
+/// reaches one); only block devices backed by hardware have a `device` link in sysfs.
+fn read_disk_io() -> Option<(u64, u64)> {
+    let contents = fs::read_to_string("/proc/diskstats").ok()?;
+    let totals = contents
+        .lines()
+        .filter_map(|line| {
+            // major minor name reads merged sectors_read ms writes merged sectors_written ...
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let name = fields.get(2)?;
+            if !PathBuf::from(format!("/sys/block/{}/device", name)).exists() {
+                return None;
+            }
+            let sectors_read: u64 = fields.get(5)?.parse().ok()?;
+            let sectors_written: u64 = fields.get(9)?.parse().ok()?;
+            // diskstats counts 512-byte sectors whatever the device's real sector size
+            Some((sectors_read * 512, sectors_written * 512))
+        })
+        .fold((0, 0), |totals, (read, written)| (totals.0 + read, totals.1 + written));
+    Some(totals)
+}
+
+/// Bytes received and sent by each interface listed in a `net/dev` file.
+fn parse_net_dev(contents: &str) -> Vec<(String, (u64, u64))> {
+    // Two header lines, then `iface: rx_bytes packets errs ... (8 rx fields) tx_bytes ...`
+    contents
+        .lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (interface, counters) = line.split_once(':')?;
+            let fields: Vec<u64> =
+                counters.split_whitespace().filter_map(|value| value.parse().ok()).collect();
+            Some((interface.trim().to_string(), (*fields.first()?, *fields.get(8)?)))
+        })
+        .collect()
+}
+
+/// Names of the `/proc/<pid>/stat` fields from 3 (state) on, as documented in proc(5).
+const STAT_FIELD_NAMES: [&str; 50] = [
+    "state", "ppid", "pgrp", "session", "tty_nr", "tpgid", "flags", "minflt", "cminflt",
+    "majflt", "cmajflt", "utime", "stime", "cutime", "cstime", "priority", "nice",
+    "num_threads", "itrealvalue", "starttime", "vsize", "rss", "rsslim", "startcode", "endcode",
+    "startstack", "kstkesp", "kstkeip", "signal", "blocked", "sigignore", "sigcatch", "wchan",
+    "nswap", "cnswap", "exit_signal", "processor", "rt_priority", "policy",
+    "delayacct_blkio_ticks", "guest_time", "cguest_time", "start_data", "end_data",
+    "start_brk", "arg_start", "arg_end", "env_start", "env_end", "exit_code",
+];
+
+/// The kernel's own view of a process, unprocessed, for `info --raw`.
+pub struct RawProcStats {
+    /// Labeled `/proc/<pid>/stat` fields; times are in clock ticks, `rss` in pages
+    pub stat: Vec<(&'static str, String)>,
+    /// `/proc/<pid>/status` lines, in file order
+    pub status: Vec<(String, String)>,
+}
+
+/// Reads and labels `/proc/<pid>/stat` and `/proc/<pid>/status`.
+///
+/// Fails with `NotFound` if the process doesn't exist or exits between the two
+/// reads, which would otherwise leave the output half-filled.
+pub fn read_raw_stats(pid: i32) -> Result<RawProcStats, ProcessError> {
+    let read = |file: &str| {
+        fs::read_to_string(format!("/proc/{}/{}", pid, file))
+            .map_err(|e| ProcessError::from_io(pid, "read the stats of", e))
+    };
+    let stat_contents = read("stat")?;
+    let status_contents = read("status")?;
+
+    let comm = match (stat_contents.find('('), stat_contents.rfind(')')) {
+        (Some(start), Some(end)) if start < end => &stat_contents[start + 1..end],
+        _ => {
+            let message = format!("Unexpected format in /proc/{}/stat", pid);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, message).into());
+        }
+    };
+    let mut stat = vec![("pid", pid.to_string()), ("comm", comm.to_string())];
+    // Older kernels have fewer fields; zip stops at whichever runs out first
+    stat.extend(
+        STAT_FIELD_NAMES
+            .iter()
+            .zip(stat_fields(&stat_contents))
+            .map(|(name, value)| (*name, value.to_string())),
+    );
+
+    let status = status_contents
+        .lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            Some((name.to_string(), value.trim().to_string()))
+        })
+        .collect();
+    Ok(RawProcStats { stat, status })
+}
+
+/// Nearest-rank p50, p95 and p99 of `samples`, or zeros when it's empty.
+fn percentiles(samples: &[f32]) -> (f32, f32, f32) {
+    if samples.is_empty() {
+        return (0.0, 0.0, 0.0);
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let rank = |percent: f32| {
+        let index = (percent / 100.0 * sorted.len() as f32).ceil() as usize;
+        sorted[index.clamp(1, sorted.len()) - 1]
+    };
+    (rank(50.0), rank(95.0), rank(99.0))
+}
+
+/// Splits the contents of `/proc/<pid>/stat` into fields, starting at field 3 (state).
+/// Empty if the contents can't be parsed.
+fn stat_fields(stat: &str) -> Vec<&str> {
+    // The command name is in parentheses and may contain spaces, so split after it
+    match stat.rsplit_once(')') {
+        Some((_, rest)) => rest.split_whitespace().collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Clock ticks per second, the unit of the times in `/proc/<pid>/stat`.
+fn clock_ticks_per_sec() -> Option<f64> {
+    static CLOCK_TICKS: OnceLock<Option<f64>> = OnceLock::new();
+    *CLOCK_TICKS.get_or_init(|| {
+        sysconf(SysconfVar::CLK_TCK).ok().flatten().map(|ticks| ticks as f64)
+    })
+}
+
+/// Splits the NUL-separated `KEY=value` entries of `/proc/<pid>/environ`; entries
+/// without `=` are kept with an empty value.
+fn parse_environ(environ: &[u8]) -> Vec<(String, String)> {
+    environ
+        .split(|&byte| byte == 0)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            match entry.split_once('=') {
+                Some((key, value)) => (key.to_string(), value.to_string()),
+                None => (entry.into_owned(), String::new()),
+            }
+        })
+        .collect()
+}
+
+/// Total user + system CPU time of a process in seconds, from its `stat_fields`.
+fn cpu_time_secs(fields: &[&str]) -> Option<f64> {
+    let ticks_per_sec = clock_ticks_per_sec()?;
+
+    // utime and stime are fields 14 and 15
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some((utime + stime) as f64 / ticks_per_sec)
+}
+
+/// Looks up a `Key:\tvalue` line in the contents of `/proc/<pid>/status`.
+fn status_value<'a>(status: &'a str, key: &str) -> Option<&'a str> {
+    status.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        (name == key).then(|| value.trim())
+    })
+}
+
+/// A size field of `/proc/<pid>/status`, reported as e.g. "1024 kB", in bytes; 0 when
+/// it's missing.
+fn status_kb(status: &str, key: &str) -> u64 {
+    status_value(status, key)
+        .and_then(|value| value.trim_end_matches("kB").trim().parse::<u64>().ok())
+        .map_or(0, |kb| kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: i32, start_time: u64, command: &str, ppid: i32, cpu: f32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid: Some(ppid),
+            cpu_usage: cpu,
+            observed_avg_cpu: cpu,
+            memory_usage: (cpu as u64 + 1) * 1024 * 1024,
+            command: command.to_string(),
+            start_time,
+            ..Default::default()
+        }
+    }
+
+    /// A throwaway process to quarantine, killed when dropped.
+    struct Sleeper(std::process::Child);
+
+    impl Sleeper {
+        fn spawn() -> Self {
+            Sleeper(std::process::Command::new("sleep").arg("30").spawn().unwrap())
+        }
+
+        fn pid(&self) -> i32 {
+            self.0.id() as i32
+        }
+
+        /// Waits for a signal to take effect; stopping and resuming aren't immediate.
+        fn wait_until_stopped(&self, stopped: bool) -> bool {
+            (0..100).any(|_| {
+                let (state, _, _) = read_sched_state(self.pid()).unwrap();
+                std::thread::sleep(Duration::from_millis(10));
+                (state == 'T') == stopped
+            })
+        }
+    }
+
+    impl Drop for Sleeper {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    fn quarantine_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("ltm-quarantine-{}-{}.json", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn quarantine_is_not_applied_when_the_record_cannot_be_saved() {
+        let sleeper = Sleeper::spawn();
+        let mut handler = ProcessHandler::new();
+        handler.set_quarantine_file(PathBuf::from("/nonexistent/ltm-quarantine.json"));
+
+        assert!(handler.quarantine(sleeper.pid()).is_err());
+        assert!(sleeper.wait_until_stopped(false));
+        assert_eq!(read_sched_state(sleeper.pid()).unwrap().1, 0);
+    }
+
+    #[test]
+    fn failed_quarantine_is_rolled_back() {
+        use nix::sys::signal::Signal;
+
+        let sleeper = Sleeper::spawn();
+        let path = quarantine_file("rollback");
+        let mut handler = ProcessHandler::new();
+        handler.set_quarantine_file(path.clone());
+
+        // As quarantine leaves things when setting the nice value fails
+        let record = QuarantineRecord {
+            pid: sleeper.pid(),
+            start_time: read_sched_state(sleeper.pid()).unwrap().2,
+            command: "sleep".to_string(),
+            nice: 0,
+            cpus: Vec::new(),
+            was_stopped: false,
+        };
+        quarantine::save(&path, std::slice::from_ref(&record)).unwrap();
+        handler.send_signal(sleeper.pid(), Signal::SIGSTOP).unwrap();
+        assert!(sleeper.wait_until_stopped(true));
+
+        handler.roll_back_quarantine(&record);
+        assert!(sleeper.wait_until_stopped(false));
+        assert!(handler.quarantined().unwrap().is_empty());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn quarantine_round_trip() {
+        let sleeper = Sleeper::spawn();
+        let path = quarantine_file("round-trip");
+        let mut handler = ProcessHandler::new();
+        handler.set_quarantine_file(path.clone());
+
+        let record = handler.quarantine(sleeper.pid()).unwrap();
+        assert!(sleeper.wait_until_stopped(true));
+        assert_eq!(read_sched_state(sleeper.pid()).unwrap().1, 19);
+        assert_eq!(handler.quarantined().unwrap(), std::slice::from_ref(&record));
+
+        // Lowering the nice value back needs privileges; everything else is undone
+        match handler.unquarantine(sleeper.pid()) {
+            Ok(released) => assert_eq!(released, record),
+            Err(e) => assert!(matches!(e, ProcessError::PermissionDenied { .. }), "{}", e),
+        }
+        assert!(sleeper.wait_until_stopped(false));
+        assert!(handler.quarantined().unwrap().is_empty());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn kill_policy_checks_the_names_read_from_proc() {
+        let sleeper = Sleeper::spawn();
+        let mut handler = ProcessHandler::new();
+        // The command name comes first; the executable may be e.g. a multi-call binary
+        assert_eq!(read_names(sleeper.pid())[0], "sleep");
+
+        handler.set_kill_policy(KillPolicy { allow: Vec::new(), deny: vec!["sleep".into()] });
+        assert!(matches!(
+            handler.describe_signal(sleeper.pid(), nix::sys::signal::Signal::SIGTERM),
+            Err(ProcessError::PolicyDenied { .. })
+        ));
+    }
+
+    #[test]
+    fn reused_pid_starts_with_fresh_state() {
+        let mut handler = ProcessHandler::new();
+        for _ in 0..3 {
+            handler.track_scan(vec![process(500, 1_000, "old", 1, 80.0)]);
         }
+        assert_eq!(handler.process_history(500).unwrap().cpu_usage.len(), 3);
+
+        // Same PID, later start time, different parent and command
+        let scan = handler.track_scan(vec![process(500, 2_000, "new", 42, 10.0)]);
+
+        let history = handler.process_history(500).unwrap();
+        assert_eq!(history.cpu_usage, [10.0]);
+        assert_eq!(history.memory_usage, [11 * 1024 * 1024]);
+        assert_eq!(scan[0].observed_avg_cpu, 10.0);
+        // A changed parent would mean orphaned if the old process's parent were kept
+        assert_eq!(scan[0].orphaned_from, None);
+        assert!(handler.restart_counts().is_empty());
+        assert_eq!(handler.known_commands.len(), 1);
+        assert_eq!(handler.known_commands[&scan[0].key()], "new");
     }
 }
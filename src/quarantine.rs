@@ -0,0 +1,191 @@
+// src/quarantine.rs
+
+//! What a quarantined process was like before, so it can be put back.
+//!
+//! Quarantining stops a process, sets its nice value to 19 and pins it to one CPU;
+//! `ProcessHandler::quarantine` does that and `unquarantine` undoes it. The original
+//! state is kept in a file rather than in memory, so a process quarantined from the
+//! GUI can be released with the CLI and the other way round. It lives in the runtime
+//! directory because PIDs don't survive a reboot either.
+//!
+//! Releasing a process acts on the PIDs in that file, so it is only trusted when
+//! nobody else could have written it: the directory must be ours and closed to
+//! others, and so must the file.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+const STATE_FILE: &str = "rust_task_manager-quarantine.json";
+
+/// The `PermissionDenied` action when `unquarantine` resumed a process but couldn't
+/// lower its nice value back.
+pub const RESTORE_NICE: &str = "restore the nice value of";
+
+/// A quarantined process and the state it had before.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    pub pid: i32,
+    /// Start time in clock ticks since boot, to tell the process from a later one
+    /// that reuses its PID
+    pub start_time: u64,
+    pub command: String,
+    /// Nice value before quarantine
+    pub nice: i32,
+    /// CPUs the process was allowed to run on
+    pub cpus: Vec<usize>,
+    /// Already stopped before quarantine, so releasing it doesn't resume it
+    pub was_stopped: bool,
+}
+
+/// `rust_task_manager-quarantine.json` in `runtime_dir()`.
+pub fn state_path() -> io::Result<PathBuf> {
+    Ok(runtime_dir()?.join(STATE_FILE))
+}
+
+/// `$XDG_RUNTIME_DIR`, or a private `/tmp/rust_task_manager-<uid>` directory, created
+/// if needed, when there is no runtime directory of our own (e.g. under `sudo`, which
+/// may keep the invoking user's). Fails when that directory is someone else's or open
+/// to others.
+pub fn runtime_dir() -> io::Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        let dir = PathBuf::from(dir);
+        if check_private(&dir, true).is_ok() {
+            return Ok(dir);
+        }
+    }
+    let dir = PathBuf::from(format!("/tmp/rust_task_manager-{}", nix::unistd::getuid()));
+    match fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+        _ => {}
+    }
+    check_private(&dir, true)?;
+    Ok(dir)
+}
+
+/// Fails unless `path` is a directory (or a regular file), not a symlink, owned by
+/// us and not writable by anyone else; a directory mustn't be readable by others
+/// either.
+fn check_private(path: &Path, dir: bool) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    let kind_ok = if dir { metadata.is_dir() } else { metadata.is_file() };
+    let open_bits = if dir { 0o077 } else { 0o022 };
+    let problem = if !kind_ok {
+        Some(if dir { "isn't a directory" } else { "isn't a regular file" })
+    } else if metadata.uid() != nix::unistd::getuid().as_raw() {
+        Some("belongs to another user")
+    } else if metadata.mode() & open_bits != 0 {
+        Some("is accessible to other users")
+    } else {
+        None
+    };
+    match problem {
+        Some(problem) => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("Not trusting {}: it {}", path.display(), problem),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Every quarantine recorded in `path`; none when the file doesn't exist yet.
+pub fn load(path: &Path) -> io::Result<Vec<QuarantineRecord>> {
+    match check_private(path, false) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        result => result?,
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Replaces the quarantines recorded in `path`, removing the file when there are
+/// none left.
+pub fn save(path: &Path, records: &[QuarantineRecord]) -> io::Result<()> {
+    if records.is_empty() {
+        return match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    // Written next to it and renamed over it, so readers never see half a file and
+    // a symlink planted at the path is replaced rather than followed
+    let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+    let written = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&temp)
+        .and_then(|mut file| {
+            file.write_all(serde_json::to_string_pretty(records)?.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_round_trip_and_the_last_removal_deletes_the_file() {
+        let path = std::env::temp_dir()
+            .join(format!("ltm-quarantine-records-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert!(load(&path).unwrap().is_empty());
+
+        let record = QuarantineRecord {
+            pid: 1234,
+            start_time: 5678,
+            command: "runaway".to_string(),
+            nice: -5,
+            cpus: vec![0, 2, 3],
+            was_stopped: false,
+        };
+        save(&path, std::slice::from_ref(&record)).unwrap();
+        assert_eq!(load(&path).unwrap(), [record]);
+
+        save(&path, &[]).unwrap();
+        assert!(!path.exists());
+        assert!(load(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn files_others_could_have_written_are_refused() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir =
+            std::env::temp_dir().join(format!("ltm-quarantine-trust-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir(&dir).unwrap();
+        let path = dir.join(STATE_FILE);
+
+        fs::write(&path, "[]").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o666)).unwrap();
+        assert_eq!(load(&path).unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+
+        // A planted symlink is neither trusted nor written through
+        let target = dir.join("target");
+        fs::write(&target, "untouched").unwrap();
+        fs::remove_file(&path).unwrap();
+        std::os::unix::fs::symlink(&target, &path).unwrap();
+        assert_eq!(load(&path).unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+        let record = QuarantineRecord {
+            pid: 1234,
+            start_time: 5678,
+            command: "runaway".to_string(),
+            nice: 0,
+            cpus: vec![0],
+            was_stopped: false,
+        };
+        save(&path, std::slice::from_ref(&record)).unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "untouched");
+        assert_eq!(load(&path).unwrap(), [record]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
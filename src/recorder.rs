@@ -0,0 +1,227 @@
+// src/recorder.rs
+
+//! Records refreshes to a file, one JSON object per line, for replaying an incident
+//! after the fact.
+//!
+//! Recording everything grows the file by every process on every refresh. In
+//! on-alert mode the recent samples are only kept in memory, like a dashcam, and
+//! written out when an alert fires, followed by the samples while it lasts and a
+//! few after it clears.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::data_structures::{ProcessInfo, PROCESS_LIST_VERSION};
+
+/// One refresh, written as a line of the recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sample {
+    /// Format version of `processes`, the same as `list --format json` prints, so a
+    /// reader can check each line before parsing it
+    pub version: u32,
+    /// RFC 3339 time of the refresh, in UTC
+    pub timestamp: String,
+    pub cpu_usage: f32,
+    pub memory_usage: f32,
+    /// Alerts active at this refresh; any alert starts an on-alert recording
+    pub alerts: Vec<String>,
+    pub processes: Vec<ProcessInfo>,
+}
+
+impl Sample {
+    /// A sample in the current format version.
+    pub fn new(
+        timestamp: String,
+        cpu_usage: f32,
+        memory_usage: f32,
+        alerts: Vec<String>,
+        processes: Vec<ProcessInfo>,
+    ) -> Self {
+        Self {
+            version: PROCESS_LIST_VERSION,
+            timestamp,
+            cpu_usage,
+            memory_usage,
+            alerts,
+            processes,
+        }
+    }
+}
+
+/// Which samples get written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordMode {
+    /// Every sample
+    Continuous,
+    /// Samples while an alert is active, the `pre_roll` ones before it and the
+    /// `post_roll` ones after it clears
+    OnAlert { pre_roll: usize, post_roll: usize },
+}
+
+pub struct Recorder {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    mode: RecordMode,
+    /// Recent samples not written yet, oldest first, at most `pre_roll` of them
+    pre_roll: VecDeque<Sample>,
+    /// Samples still to write after the last alert cleared
+    post_roll_left: usize,
+    /// Alerts don't start a recording while automation is paused
+    paused: bool,
+    /// An alert is being ignored because of the pause, already logged
+    ignoring_alert: bool,
+}
+
+impl Recorder {
+    /// Opens `path` for appending, so several recordings can share a file.
+    pub fn create(path: &Path, mode: RecordMode) -> Result<Self, String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            path: path.to_path_buf(),
+            mode,
+            pre_roll: VecDeque::new(),
+            post_roll_left: 0,
+            paused: false,
+            ignoring_alert: false,
+        })
+    }
+
+    /// Pauses or resumes starting recordings on alerts; see `crate::automation`.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Writes the sample, or buffers it as pre-roll, according to the mode. Returns
+    /// the number of samples written, buffered ones included.
+    pub fn push(&mut self, sample: Sample) -> Result<usize, String> {
+        let RecordMode::OnAlert { pre_roll, post_roll } = self.mode else {
+            return self.write(&[sample]);
+        };
+
+        let alerting = !sample.alerts.is_empty();
+        if alerting && self.paused && !self.ignoring_alert {
+            info!("Automation paused, not recording alert: {}", sample.alerts.join(", "));
+        }
+        self.ignoring_alert = alerting && self.paused;
+
+        if alerting && !self.paused {
+            self.post_roll_left = post_roll;
+            let mut samples: Vec<Sample> = self.pre_roll.drain(..).collect();
+            samples.push(sample);
+            self.write(&samples)
+        } else if self.post_roll_left > 0 {
+            self.post_roll_left -= 1;
+            self.write(&[sample])
+        } else {
+            self.pre_roll.push_back(sample);
+            if self.pre_roll.len() > pre_roll {
+                self.pre_roll.pop_front();
+            }
+            Ok(0)
+        }
+    }
+
+    /// Appends the samples and flushes, so the file is complete whenever the
+    /// recorder is stopped.
+    fn write(&mut self, samples: &[Sample]) -> Result<usize, String> {
+        let path = self.path.display();
+        let error = |e: &dyn std::fmt::Display| format!("Failed to write {}: {}", path, e);
+        for sample in samples {
+            serde_json::to_writer(&mut self.writer, sample).map_err(|e| error(&e))?;
+            self.writer.write_all(b"\n").map_err(|e| error(&e))?;
+        }
+        self.writer.flush().map_err(|e| error(&e))?;
+        Ok(samples.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records one sample per entry of `alerting` in on-alert mode, each stamped with
+    /// its index, and returns the indices that were written.
+    fn record_on_alert(
+        name: &str,
+        pre_roll: usize,
+        post_roll: usize,
+        alerting: &[bool],
+    ) -> Vec<String> {
+        record_paused(name, pre_roll, post_roll, alerting, &vec![false; alerting.len()])
+    }
+
+    /// Like `record_on_alert`, with automation paused for the samples where `paused`
+    /// is set.
+    fn record_paused(
+        name: &str,
+        pre_roll: usize,
+        post_roll: usize,
+        alerting: &[bool],
+        paused: &[bool],
+    ) -> Vec<String> {
+        let path = std::env::temp_dir()
+            .join(format!("ltm-recorder-{}-{}.jsonl", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mode = RecordMode::OnAlert { pre_roll, post_roll };
+        let mut recorder = Recorder::create(&path, mode).unwrap();
+        for (i, (&alert, &paused)) in alerting.iter().zip(paused).enumerate() {
+            recorder.set_paused(paused);
+            let alerts = if alert { vec!["CPU".to_string()] } else { Vec::new() };
+            recorder.push(Sample::new(i.to_string(), 0.0, 0.0, alerts, Vec::new())).unwrap();
+        }
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        contents
+            .lines()
+            .map(|line| {
+                let sample: Sample = serde_json::from_str(line).unwrap();
+                assert_eq!(sample.version, PROCESS_LIST_VERSION);
+                sample.timestamp
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nothing_is_written_without_an_alert() {
+        assert!(record_on_alert("quiet", 3, 2, &[false; 6]).is_empty());
+    }
+
+    #[test]
+    fn alert_writes_the_pre_roll_first() {
+        let written = record_on_alert("start", 2, 0, &[false, false, false, false, true]);
+        // Only the last two quiet samples were kept
+        assert_eq!(written, ["2", "3", "4"]);
+    }
+
+    #[test]
+    fn recording_stops_after_the_post_roll() {
+        let alerting = [true, true, false, false, false, false];
+        assert_eq!(record_on_alert("stop", 3, 2, &alerting), ["0", "1", "2", "3"]);
+    }
+
+    #[test]
+    fn samples_after_the_post_roll_become_pre_roll_again() {
+        let alerting = [true, false, false, false, false, true];
+        // 1 is post-roll; 2 falls out of the single pre-roll slot; 4 leads into the alert
+        assert_eq!(record_on_alert("again", 1, 1, &alerting), ["0", "1", "4", "5"]);
+    }
+
+    #[test]
+    fn alerts_while_paused_are_not_recorded() {
+        let alerting = [true, true, false, true];
+        let paused = [true, true, false, false];
+        // The ignored alerts are kept as pre-roll for the one after the pause
+        assert_eq!(record_paused("paused", 2, 0, &alerting, &paused), ["1", "2", "3"]);
+        assert!(record_paused("all-paused", 2, 0, &alerting, &[true; 4]).is_empty());
+    }
+}
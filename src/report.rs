@@ -0,0 +1,498 @@
+// src/report.rs
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::config::Config;
+use crate::data_structures::{
+    sort_processes, CpuSinceBoot, DiskInfo, ProcessInfo, SortField, SortOrder,
+};
+use crate::format::{format_bytes, format_bytes_delta, format_status_counts};
+use crate::process_handler::ProcessHandler;
+
+/// Number of processes listed in each of the top-CPU and top-memory sections.
+const TOP_PROCESSES: usize = 10;
+
+/// Delay between the two samples a report takes so CPU percentages are meaningful.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A one-off snapshot of the whole system, written by the `report` command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    /// RFC 3339 time the snapshot was taken, in UTC
+    pub generated_at: String,
+    pub cpu_usage: f32,
+    pub memory_usage: f32,
+    /// p50, p95 and p99 of CPU usage over the samples the report took
+    #[serde(default)]
+    pub cpu_percentiles: (f32, f32, f32),
+    /// p50, p95 and p99 of memory usage over the samples the report took
+    #[serde(default)]
+    pub memory_percentiles: (f32, f32, f32),
+    pub cpu_since_boot: Option<CpuSinceBoot>,
+    pub cpu_temperature: Option<f32>,
+    /// 1, 5 and 15 minute load averages
+    pub load_average: [f64; 3],
+    /// Number of processes in each state
+    #[serde(default)]
+    pub status_counts: HashMap<String, usize>,
+    pub top_cpu: Vec<ProcessInfo>,
+    pub top_memory: Vec<ProcessInfo>,
+    pub disks: Vec<DiskInfo>,
+    pub alerts: Vec<String>,
+    /// Every process at the time of the report, for `diff`. Missing from reports
+    /// written before it was added, which only diff their top processes.
+    #[serde(default)]
+    pub processes: Vec<ProcessInfo>,
+}
+
+impl Report {
+    /// Samples the system twice, `SAMPLE_INTERVAL` apart, and gathers everything
+    /// into a report. Blocks for the length of the interval.
+    pub fn capture(config: &Config) -> Report {
+        let mut handler = ProcessHandler::with_history_capacity(config.history_capacity);
+        handler.warm_up_cpu_sampling();
+        std::thread::sleep(SAMPLE_INTERVAL);
+        let mut processes = handler.refresh_processes();
+        handler.refresh();
+
+        let cpu_usage = handler.get_cpu_usage_history().last().copied().unwrap_or(0.0);
+        let memory_usage = handler.get_memory_usage_history().last().copied().unwrap_or(0.0);
+
+        let mut top = |field: SortField| -> Vec<ProcessInfo> {
+            sort_processes(&mut processes, field, SortOrder::Descending, config.command_display);
+            processes.iter().take(TOP_PROCESSES).cloned().collect()
+        };
+        let top_cpu = top(SortField::CPU);
+        let top_memory = top(SortField::Memory);
+        let mode = config.command_display;
+        sort_processes(&mut processes, SortField::PID, SortOrder::Ascending, mode);
+
+        Report {
+            generated_at: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            cpu_usage,
+            memory_usage,
+            cpu_percentiles: handler.cpu_percentiles(),
+            memory_percentiles: handler.memory_percentiles(),
+            cpu_since_boot: handler.cpu_since_boot(),
+            cpu_temperature: handler.cpu_temperature(),
+            load_average: handler.load_average(),
+            status_counts: handler.status_counts(),
+            top_cpu,
+            top_memory,
+            disks: handler.disk_usage(),
+            alerts: config.alerts(Some(cpu_usage), Some(memory_usage)),
+            processes,
+        }
+    }
+
+    /// Reads a report saved by `report --format json`.
+    pub fn load(path: &Path) -> Result<Report, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("{} is not a JSON report: {}", path.display(), e))
+    }
+
+    /// The processes to compare: all of them, or the top lists for older reports.
+    fn compared_processes(&self) -> Vec<&ProcessInfo> {
+        if !self.processes.is_empty() {
+            return self.processes.iter().collect();
+        }
+        self.top_cpu.iter().chain(&self.top_memory).collect()
+    }
+
+    /// What changed between this report and a later one; see `diff_processes`.
+    pub fn diff(&self, after: &Report, cpu_threshold: f32, memory_threshold: u64) -> ReportDiff {
+        let ProcessDiff { new_processes, exited_processes, changed } = diff_processes(
+            self.compared_processes(),
+            after.compared_processes(),
+            cpu_threshold,
+            memory_threshold,
+        );
+
+        let [one, five, fifteen] = after.load_average;
+        let [one_before, five_before, fifteen_before] = self.load_average;
+        ReportDiff {
+            before_generated_at: self.generated_at.clone(),
+            after_generated_at: after.generated_at.clone(),
+            cpu_usage_delta: after.cpu_usage - self.cpu_usage,
+            memory_usage_delta: after.memory_usage - self.memory_usage,
+            load_average_delta: [one - one_before, five - five_before, fifteen - fifteen_before],
+            cpu_temperature_delta: after
+                .cpu_temperature
+                .zip(self.cpu_temperature)
+                .map(|(after, before)| after - before),
+            new_processes,
+            exited_processes,
+            changed,
+        }
+    }
+
+    /// Plain-text rendering for reading or attaching to an incident ticket.
+    pub fn to_text(&self) -> String {
+        // Writing to a String can't fail
+        let mut out = String::new();
+        let _ = writeln!(out, "System report generated at {}", self.generated_at);
+        let _ = writeln!(out);
+        let _ = writeln!(out, "CPU usage:     {:.1}%", self.cpu_usage);
+        let _ = writeln!(out, "Memory usage:  {:.1}%", self.memory_usage);
+        for (name, (p50, p95, p99)) in
+            [("CPU", self.cpu_percentiles), ("Memory", self.memory_percentiles)]
+        {
+            let _ = writeln!(out, "{} p50/p95/p99: {:.1}/{:.1}/{:.1}%", name, p50, p95, p99);
+        }
+        if let Some(since_boot) = self.cpu_since_boot {
+            let _ = writeln!(out, "CPU since boot: {:.1}%", since_boot.usage_percent);
+        }
+        if let Some(temperature) = self.cpu_temperature {
+            let _ = writeln!(out, "CPU temperature: {:.0}°C", temperature);
+        }
+        let [one, five, fifteen] = self.load_average;
+        let _ = writeln!(out, "Load average:  {:.2} {:.2} {:.2}", one, five, fifteen);
+        if !self.status_counts.is_empty() {
+            let _ = writeln!(out, "{}", format_status_counts(&self.status_counts));
+        }
+
+        let _ = writeln!(out);
+        if self.alerts.is_empty() {
+            let _ = writeln!(out, "Alerts: none");
+        } else {
+            let _ = writeln!(out, "Alerts:");
+            for alert in &self.alerts {
+                let _ = writeln!(out, "  {}", alert);
+            }
+        }
+
+        for (title, processes) in [("Top CPU", &self.top_cpu), ("Top memory", &self.top_memory)] {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "{}:", title);
+            let _ = writeln!(out, "  {:<10} {:<10} {:<12} Command", "PID", "CPU%", "Memory");
+            for p in processes {
+                let _ = writeln!(
+                    out,
+                    "  {:<10} {:<10.2} {:<12} {}",
+                    p.pid,
+                    p.cpu_usage,
+                    format_bytes(p.memory_usage),
+                    p.command
+                );
+            }
+        }
+
+        let _ = writeln!(out);
+        let _ = writeln!(out, "Disks:");
+        for d in &self.disks {
+            let _ = writeln!(
+                out,
+                "  {:<25} {:>12} of {:<12} {:.1}% used",
+                d.mount_point,
+                format_bytes(d.total_space - d.available_space),
+                format_bytes(d.total_space),
+                d.usage_percent
+            );
+        }
+        out
+    }
+}
+
+/// Processes that appeared, exited or changed noticeably between two lists.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessDiff {
+    pub new_processes: Vec<ProcessSummary>,
+    pub exited_processes: Vec<ProcessSummary>,
+    pub changed: Vec<ProcessChange>,
+}
+
+/// What changed between two process lists, each part sorted by PID. Processes are
+/// matched by PID and command, so a reused PID counts as one exit and one new
+/// process. Running processes are listed when CPU usage moved by at least
+/// `cpu_threshold` percentage points or resident memory by at least
+/// `memory_threshold` bytes.
+pub fn diff_processes<'a>(
+    before: impl IntoIterator<Item = &'a ProcessInfo>,
+    after: impl IntoIterator<Item = &'a ProcessInfo>,
+    cpu_threshold: f32,
+    memory_threshold: u64,
+) -> ProcessDiff {
+    let before_processes = by_pid_and_command(before);
+    let after_processes = by_pid_and_command(after);
+
+    let summarize = |p: &ProcessInfo| ProcessSummary {
+        pid: p.pid,
+        command: p.command.clone(),
+        cpu_usage: p.cpu_usage,
+        memory_usage: p.memory_usage,
+    };
+    let mut new_processes: Vec<ProcessSummary> = after_processes
+        .iter()
+        .filter(|(key, _)| !before_processes.contains_key(key))
+        .map(|(_, p)| summarize(p))
+        .collect();
+    let mut exited_processes: Vec<ProcessSummary> = before_processes
+        .iter()
+        .filter(|(key, _)| !after_processes.contains_key(key))
+        .map(|(_, p)| summarize(p))
+        .collect();
+    let mut changed: Vec<ProcessChange> = before_processes
+        .iter()
+        .filter_map(|(key, before)| {
+            let after = after_processes.get(key)?;
+            let cpu_delta = after.cpu_usage - before.cpu_usage;
+            let memory_delta = after.memory_usage as i64 - before.memory_usage as i64;
+            let significant = cpu_delta.abs() >= cpu_threshold
+                || memory_delta.unsigned_abs() >= memory_threshold;
+            significant.then(|| ProcessChange {
+                pid: before.pid,
+                command: before.command.clone(),
+                cpu_before: before.cpu_usage,
+                cpu_after: after.cpu_usage,
+                memory_before: before.memory_usage,
+                memory_after: after.memory_usage,
+            })
+        })
+        .collect();
+    new_processes.sort_by_key(|p| p.pid);
+    exited_processes.sort_by_key(|p| p.pid);
+    changed.sort_by_key(|c| c.pid);
+    ProcessDiff { new_processes, exited_processes, changed }
+}
+
+fn by_pid_and_command<'a>(
+    processes: impl IntoIterator<Item = &'a ProcessInfo>,
+) -> HashMap<(i32, &'a str), &'a ProcessInfo> {
+    processes.into_iter().map(|p| ((p.pid, p.command.as_str()), p)).collect()
+}
+
+/// A process as listed in a report diff.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessSummary {
+    pub pid: i32,
+    pub command: String,
+    pub cpu_usage: f32,
+    pub memory_usage: u64,
+}
+
+/// A process present in both reports whose usage changed noticeably.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessChange {
+    pub pid: i32,
+    pub command: String,
+    pub cpu_before: f32,
+    pub cpu_after: f32,
+    pub memory_before: u64,
+    pub memory_after: u64,
+}
+
+/// Differences between two reports, written by the `diff` command.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportDiff {
+    pub before_generated_at: String,
+    pub after_generated_at: String,
+    /// Percentage points
+    pub cpu_usage_delta: f32,
+    /// Percentage points
+    pub memory_usage_delta: f32,
+    pub load_average_delta: [f64; 3],
+    pub cpu_temperature_delta: Option<f32>,
+    pub new_processes: Vec<ProcessSummary>,
+    pub exited_processes: Vec<ProcessSummary>,
+    pub changed: Vec<ProcessChange>,
+}
+
+impl ReportDiff {
+    pub fn to_text(&self) -> String {
+        // Writing to a String can't fail
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "Changes from {} to {}",
+            self.before_generated_at, self.after_generated_at
+        );
+        let _ = writeln!(out);
+        let _ = writeln!(out, "CPU usage:     {:+.1} points", self.cpu_usage_delta);
+        let _ = writeln!(out, "Memory usage:  {:+.1} points", self.memory_usage_delta);
+        let [one, five, fifteen] = self.load_average_delta;
+        let _ = writeln!(out, "Load average:  {:+.2} {:+.2} {:+.2}", one, five, fifteen);
+        if let Some(temperature) = self.cpu_temperature_delta {
+            let _ = writeln!(out, "CPU temperature: {:+.0}°C", temperature);
+        }
+
+        for (title, processes) in
+            [("New processes", &self.new_processes), ("Exited processes", &self.exited_processes)]
+        {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "{} ({}):", title, processes.len());
+            for p in processes {
+                let _ = writeln!(
+                    out,
+                    "  {:<10} {:<10.2} {:<12} {}",
+                    p.pid,
+                    p.cpu_usage,
+                    format_bytes(p.memory_usage),
+                    p.command
+                );
+            }
+        }
+
+        let _ = writeln!(out);
+        let _ = writeln!(out, "Changed processes ({}):", self.changed.len());
+        let _ = writeln!(out, "  {:<10} {:<18} {:<14} Command", "PID", "CPU%", "Memory");
+        for c in &self.changed {
+            let _ = writeln!(
+                out,
+                "  {:<10} {:<18} {:<14} {}",
+                c.pid,
+                format!("{:.1} -> {:.1}", c.cpu_before, c.cpu_after),
+                format_bytes_delta(c.memory_after as i64 - c.memory_before as i64),
+                c.command
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1 << 30;
+
+    fn process(pid: i32, command: &str, cpu_usage: f32, memory_usage: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            command: command.to_string(),
+            cpu_usage,
+            memory_usage,
+            ..Default::default()
+        }
+    }
+
+    fn snapshot() -> Report {
+        let processes = vec![
+            process(1, "init", 0.0, 10 << 20),
+            process(200, "postgres", 12.5, 2 * GIB),
+            process(300, "node", 85.0, GIB),
+        ];
+        Report {
+            generated_at: "2026-10-15T08:00:00Z".to_string(),
+            cpu_usage: 93.5,
+            memory_usage: 41.0,
+            cpu_percentiles: (40.0, 90.0, 99.0),
+            memory_percentiles: (40.0, 41.0, 41.5),
+            cpu_since_boot: Some(CpuSinceBoot { usage_percent: 12.3, idle_percent: 87.7 }),
+            cpu_temperature: Some(71.0),
+            load_average: [1.5, 1.25, 0.75],
+            status_counts: HashMap::from([("Running".to_string(), 1), ("Sleeping".to_string(), 2)]),
+            top_cpu: vec![processes[2].clone(), processes[1].clone()],
+            top_memory: vec![processes[1].clone(), processes[2].clone()],
+            disks: vec![DiskInfo {
+                name: "sda1".to_string(),
+                mount_point: "/".to_string(),
+                file_system: "ext4".to_string(),
+                total_space: 100 * GIB,
+                available_space: 25 * GIB,
+                usage_percent: 75.0,
+            }],
+            alerts: vec!["CPU usage 93.5% exceeds 90%".to_string()],
+            processes,
+        }
+    }
+
+    /// The lines of `text` from the one starting with `title` to the next blank line.
+    fn section<'a>(text: &'a str, title: &str) -> Vec<&'a str> {
+        text.lines()
+            .skip_while(|line| !line.starts_with(title))
+            .take_while(|line| !line.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn text_report_has_every_section() {
+        let text = snapshot().to_text();
+
+        let summary = section(&text, "CPU usage:");
+        assert_eq!(
+            summary,
+            [
+                "CPU usage:     93.5%",
+                "Memory usage:  41.0%",
+                "CPU p50/p95/p99: 40.0/90.0/99.0%",
+                "Memory p50/p95/p99: 40.0/41.0/41.5%",
+                "CPU since boot: 12.3%",
+                "CPU temperature: 71°C",
+                "Load average:  1.50 1.25 0.75",
+                "3 processes: 2 sleeping, 1 running",
+            ]
+        );
+        assert_eq!(section(&text, "Alerts"), ["Alerts:", "  CPU usage 93.5% exceeds 90%"]);
+
+        let top_cpu = section(&text, "Top CPU:");
+        assert_eq!(top_cpu.len(), 4);
+        assert!(top_cpu[1].trim_start().starts_with("PID"));
+        assert!(top_cpu[2].contains("85.00") && top_cpu[2].ends_with("node"), "{}", top_cpu[2]);
+        assert!(top_cpu[3].ends_with("postgres"));
+        let top_memory = section(&text, "Top memory:");
+        assert!(top_memory[2].contains(&format_bytes(2 * GIB)));
+        assert!(top_memory[2].ends_with("postgres"));
+
+        let disks = section(&text, "Disks:");
+        assert_eq!(disks.len(), 2);
+        assert!(disks[1].trim_start().starts_with('/'));
+        assert!(disks[1].contains(&format_bytes(75 * GIB)) && disks[1].ends_with("75.0% used"));
+    }
+
+    #[test]
+    fn quiet_report_says_there_are_no_alerts() {
+        let report = Report { alerts: Vec::new(), cpu_temperature: None, ..snapshot() };
+        let text = report.to_text();
+        assert!(text.lines().any(|line| line == "Alerts: none"));
+        assert!(!text.contains("CPU temperature"));
+    }
+
+    #[test]
+    fn json_report_loads_back() {
+        let path = std::env::temp_dir().join(format!("ltm-report-{}.json", std::process::id()));
+        std::fs::write(&path, serde_json::to_string(&snapshot()).unwrap()).unwrap();
+        let loaded = Report::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.to_text(), snapshot().to_text());
+        assert_eq!(loaded.processes.len(), 3);
+    }
+
+    #[test]
+    fn diff_lists_new_exited_and_changed_processes() {
+        let before = snapshot();
+        let mut after = snapshot();
+        after.generated_at = "2026-10-15T09:00:00Z".to_string();
+        after.cpu_usage = 50.0;
+        // node calmed down, postgres only moved a little, and PID 200 was reused
+        after.processes = vec![
+            process(1, "init", 0.0, 10 << 20),
+            process(200, "bash", 0.0, 4 << 20),
+            process(300, "node", 5.0, GIB),
+            process(400, "cron", 0.1, 2 << 20),
+        ];
+
+        let diff = before.diff(&after, 10.0, 100 << 20);
+        let pids = |processes: &[ProcessSummary]| -> Vec<i32> {
+            processes.iter().map(|p| p.pid).collect()
+        };
+        assert_eq!(pids(&diff.new_processes), [200, 400]);
+        assert_eq!(pids(&diff.exited_processes), [200]);
+        assert_eq!(diff.exited_processes[0].command, "postgres");
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!((diff.changed[0].pid, diff.changed[0].cpu_after), (300, 5.0));
+        assert_eq!(diff.cpu_usage_delta, -43.5);
+
+        let text = diff.to_text();
+        assert!(text.starts_with("Changes from 2026-10-15T08:00:00Z to 2026-10-15T09:00:00Z"));
+        assert_eq!(section(&text, "New processes").len(), 3);
+        assert_eq!(section(&text, "Changed processes")[0], "Changed processes (1):");
+    }
+}
@@ -2,88 +2,703 @@
 
 use iced::{
     alignment::Alignment,
-    executor,
-    mouse::Cursor,
+    event, executor,
+    keyboard::{self, KeyCode},
+    mouse::{self, Cursor},
+    subscription, theme,
     time::every,
+    window,
     widget::{
         button::Button,
-        canvas::{self, Canvas, Frame, Geometry, Path, Stroke, Style, Text as CanvasText},
-        scrollable::Scrollable,
+        canvas::{
+            self, Canvas, Frame, Geometry, LineDash, Path, Stroke, Style, Text as CanvasText,
+        },
+        container, mouse_area,
+        pick_list::PickList,
+        progress_bar::ProgressBar,
+        slider::Slider,
+        scrollable::{self, RelativeOffset, Scrollable},
         text_input::TextInput,
-        Column, Container, Row, Text, // Removed Length and Length::Fixed from here
+        tooltip::{self, Tooltip},
+        Column, Container, Row, Space, Text, // Removed Length and Length::Fixed from here
     },
-    Application, Command, Element, Length, // Import Length here
+    Application, Background, Command, Element, Event, Length, // Import Length here
     Rectangle, Renderer, Subscription, Theme,
 };
-use crate::data_structures::ProcessInfo;
-use crate::process_handler::ProcessHandler;
+use crate::automation;
+use crate::cgroup::CgroupLimits;
+use crate::config::{Config, GuiSettings, RowDensity, ThemeChoice};
+use crate::data_structures::{
+    collapse_process_tree, most_common_first, sort_process_tree, sort_processes, CommandDisplay,
+    CpuSinceBoot, DiskInfo, ProcessDetails, ProcessHistory, ProcessInfo, SchedPolicy, SortField,
+    SortOrder,
+};
+use crate::error::ProcessError;
+use crate::filter::{FilterMode, FilterPreset, ProcessFilter, TermMatch};
+use crate::format::{
+    byte_units, format_bytes, format_bytes_delta, format_cgroup_memory, format_cpu_list,
+    format_sched_policy, format_status_counts,
+};
+use crate::ipc;
+use crate::process_handler::{ProcessHandler, KILL_SIGNALS};
+use crate::quarantine::RESTORE_NICE;
+use crate::report::{diff_processes, ProcessChange, ProcessDiff, ProcessSummary};
+use nix::sys::signal::Signal;
+use nix::unistd::geteuid;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+const DISK_REFRESH_INTERVAL: Duration = Duration::from_secs(10); // Disk usage changes slowly
+const KILL_COOLDOWN: Duration = Duration::from_millis(500); // Ignore repeated kills of the same PID
+const GROWTH_WINDOW: usize = 10; // Samples the memory growth leaderboard looks back over
+const GROWTH_LEADERS: usize = 10; // Entries shown in the growth panel
+const HOG_WINDOW: usize = 60; // Samples the "top hog" CPU average covers
+const HOG_MIN_CPU: f32 = 5.0; // Average CPU% below which no process is called a hog
+const SESSION_CPU_ENTRIES: usize = 10; // Commands shown in the session CPU panel
+const SIGNIFICANT_SWAP: u64 = 100 * 1024 * 1024; // Swapped-out bytes highlighted in the list
+const SIGNIFICANT_SHRINK: u64 = 100 * 1024 * 1024; // Peak minus current memory highlighted
+const FLAPPING_RESTARTS: u32 = 3; // Restarts after which a command is flagged as crash looping
+const SELF_CHECK_WINDOW: usize = 10; // Samples our own CPU usage is averaged over
+const PAUSE_DIFF_SHOWN: Duration = Duration::from_secs(30); // How long the pause diff stays up
+const PAUSE_DIFF_ROWS: usize = 5; // Entries shown per section of the pause diff
+const PAUSE_DIFF_CPU: f32 = 5.0; // CPU change, in points, listed in the pause diff
+const PAUSE_DIFF_MEMORY: u64 = 50 * 1024 * 1024; // Memory change listed in the pause diff
+const SPARKLINE_POINTS: usize = 60; // Most points drawn per sparkline in the details panel
+
+/// A min/max filter set with a pair of sliders.
+///
+/// `max` is infinite while its slider sits at the top, so the range keeps including
+/// the heaviest processes when usage grows past the previously observed maximum.
+#[derive(Clone, Copy, Debug)]
+struct UsageRange {
+    min: f32,
+    max: f32,
+}
+
+impl UsageRange {
+    const UNBOUNDED: UsageRange = UsageRange { min: 0.0, max: f32::INFINITY };
+
+    fn contains(&self, value: f32) -> bool {
+        value >= self.min && value <= self.max
+    }
+
+    /// Moves one end of the range; `top` is the current upper bound of the sliders.
+    fn set(&mut self, end: RangeEnd, value: f32, top: f32) {
+        match end {
+            RangeEnd::Min => {
+                self.min = value;
+                self.max = self.max.max(value);
+            }
+            RangeEnd::Max => {
+                self.max = if value >= top { f32::INFINITY } else { value };
+                self.min = self.min.min(value);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum RangeEnd {
+    Min,
+    Max,
+}
+
+/// Table columns that are dropped as the window narrows.
+/// PID, CPU, memory and command are always shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OptionalColumn {
+    Efficiency,
+    User,
+    VirtualMemory,
+    PeakMemory,
+    Swap,
+    Status,
+    ObservedCpu,
+    Threads,
+    Nice,
+    DiskIo,
+    Executable,
+}
+
+impl OptionalColumn {
+    /// Narrowest window width (in logical pixels) at which the column is still shown.
+    /// Lower-priority columns have higher breakpoints, so they disappear first.
+    fn min_window_width(self) -> f32 {
+        match self {
+            OptionalColumn::Executable => 1800.0,
+            OptionalColumn::Efficiency => 1650.0,
+            OptionalColumn::User => 1500.0,
+            OptionalColumn::VirtualMemory => 1350.0,
+            OptionalColumn::PeakMemory => 1300.0,
+            OptionalColumn::Swap => 1250.0,
+            OptionalColumn::Status => 1200.0,
+            OptionalColumn::ObservedCpu => 1100.0,
+            OptionalColumn::Threads => 1050.0,
+            OptionalColumn::Nice => 1000.0,
+            OptionalColumn::DiskIo => 950.0,
+        }
+    }
+}
+
+const EXE_COLUMN_WIDTH: f32 = 220.0;
+const ACTIONS_COLUMN_WIDTH: f32 = 180.0; // Kill button and signal picker
+
+// Series colors for the two compared processes
+const COMPARE_COLORS: [iced::Color; 2] = [
+    iced::Color::from_rgb(0.1, 0.4, 0.9),
+    iced::Color::from_rgb(0.95, 0.5, 0.1),
+];
+
+/// Colors and sizes that change with the high-contrast setting.
+#[derive(Clone, Copy)]
+struct VisualStyle {
+    cpu_line: iced::Color,
+    memory_line: iced::Color,
+    spike: iced::Color,
+    grid: iced::Color,
+    axis: iced::Color,
+    label: iced::Color,
+    /// Min-max bars behind downsampled chart lines
+    range: iced::Color,
+    line_width: f32,
+    label_size: f32,
+    /// Added to the row density's text size in the process list
+    extra_text_size: f32,
+    warning: iced::Color,
+    danger: iced::Color,
+    info: iced::Color,
+    ok: iced::Color,
+    /// PID color of a process that just started, fading with age
+    fresh: iced::Color,
+    /// The part of a command that matched the search query
+    search_match: iced::Color,
+}
+
+impl VisualStyle {
+    const STANDARD: VisualStyle = VisualStyle {
+        cpu_line: iced::Color::from_rgb(0.0, 0.5, 0.5),
+        memory_line: iced::Color::from_rgb(0.5, 0.0, 0.5),
+        spike: iced::Color::from_rgb(1.0, 0.0, 0.0),
+        grid: iced::Color::from_rgb(0.9, 0.9, 0.9),
+        axis: iced::Color::BLACK,
+        label: iced::Color::from_rgb(0.2, 0.2, 0.2),
+        range: iced::Color::from_rgba(0.5, 0.5, 0.5, 0.4),
+        line_width: 2.0,
+        label_size: 18.0,
+        extra_text_size: 0.0,
+        warning: iced::Color::from_rgb(0.9, 0.5, 0.0),
+        danger: iced::Color::from_rgb(0.8, 0.0, 0.0),
+        info: iced::Color::from_rgb(0.1, 0.5, 0.9),
+        ok: iced::Color::from_rgb(0.0, 0.5, 0.0),
+        fresh: iced::Color::from_rgb(0.0, 0.75, 0.3),
+        search_match: iced::Color::from_rgb(0.6, 0.2, 0.8),
+    };
+
+    // Saturated colors that stay apart for common color-vision deficiencies
+    const HIGH_CONTRAST: VisualStyle = VisualStyle {
+        cpu_line: iced::Color::from_rgb(0.0, 0.25, 0.9),
+        memory_line: iced::Color::from_rgb(0.85, 0.4, 0.0),
+        spike: iced::Color::from_rgb(0.85, 0.0, 0.0),
+        grid: iced::Color::from_rgb(0.6, 0.6, 0.6),
+        axis: iced::Color::BLACK,
+        label: iced::Color::BLACK,
+        range: iced::Color::from_rgba(0.0, 0.0, 0.0, 0.5),
+        line_width: 4.0,
+        label_size: 22.0,
+        extra_text_size: 4.0,
+        warning: iced::Color::from_rgb(0.7, 0.3, 0.0),
+        danger: iced::Color::from_rgb(0.75, 0.0, 0.0),
+        info: iced::Color::from_rgb(0.0, 0.2, 0.8),
+        ok: iced::Color::from_rgb(0.0, 0.4, 0.0),
+        fresh: iced::Color::from_rgb(0.75, 0.0, 0.6),
+        search_match: iced::Color::from_rgb(0.45, 0.0, 0.65),
+    };
+}
+
+/// Black on white with a dark blue accent, used in high-contrast mode whatever the theme.
+const HIGH_CONTRAST_PALETTE: iced::theme::Palette = iced::theme::Palette {
+    background: iced::Color::WHITE,
+    text: iced::Color::BLACK,
+    primary: iced::Color::from_rgb(0.0, 0.2, 0.7),
+    success: iced::Color::from_rgb(0.0, 0.4, 0.0),
+    danger: iced::Color::from_rgb(0.75, 0.0, 0.0),
+};
 
-const SPIKE_THRESHOLD: f32 = 20.0; // Spike threshold in percentage
+fn process_list_id() -> scrollable::Id {
+    scrollable::Id::new("process_list")
+}
 
 pub struct TaskManager {
+    config: Config,
     process_handler: Arc<Mutex<ProcessHandler>>,
     processes: Vec<ProcessInfo>,
     filtered_processes: Vec<ProcessInfo>,
     cpu_usage_history: Vec<f32>,
+    /// Lowest and highest CPU usage between refreshes, when fine sampling is on
+    cpu_range_history: Vec<(f32, f32)>,
     memory_usage_history: Vec<f32>,
+    /// Kills, quarantines and alerts marked on the charts, by history index
+    chart_events: Vec<(usize, String)>,
+    cpu_percentiles: (f32, f32, f32),
+    memory_percentiles: (f32, f32, f32),
+    cpu_since_boot: Option<CpuSinceBoot>,
+    cpu_temperature: Option<f32>,
+    /// Received and sent bytes per second, lined up with `cpu_usage_history`
+    network_history: Vec<(u64, u64)>,
+    /// Bytes read from and written to disk per second, lined up the same way
+    disk_io_history: Vec<(u64, u64)>,
+    status_counts: HashMap<String, usize>,
+    details_extras: Option<DetailsExtras>,
+    compare_mode: bool,
+    compare_pids: [Option<i32>; 2],
+    compared_histories: Vec<(i32, ProcessHistory)>,
+    /// Process clicked in the list, whose history the charts show instead of the
+    /// system's
+    selected_pid: Option<i32>,
+    selected_history: Option<ProcessSeries>,
     search_query: String,
+    filter_mode: FilterMode,
+    term_match: TermMatch,
+    /// Include processes on the ignore list
+    show_ignored: bool,
+    cpu_range: UsageRange,
+    /// Resident memory range in MiB
+    memory_range: UsageRange,
+    /// Preset picked in the dropdown, and the name typed for saving one
+    selected_preset: Option<String>,
+    preset_name: String,
     sort_field: SortField,
     sort_order: SortOrder,
     show_graphs: bool,
+    /// Show processes nested under their parents, siblings sorted by the sort column
+    tree_view: bool,
+    /// Depth of each row of `filtered_processes` in tree view; empty otherwise
+    tree_depths: Vec<usize>,
+    /// Number of processes below each row in tree view, including hidden ones
+    tree_descendants: Vec<usize>,
+    /// Processes whose children are hidden in tree view
+    collapsed: HashSet<i32>,
+    /// Kill buttons also kill every process below the one clicked
+    kill_subtrees: bool,
+    /// Draw CPU and memory as two lines on one chart instead of side by side
+    overlay_charts: bool,
+    disks: Vec<DiskInfo>,
+    show_disks: bool,
+    /// PID and signed memory change of the processes whose memory changed most
+    growth_leaders: Vec<(i32, i64)>,
+    show_growth: bool,
+    /// PID and average CPU of the top consumer over the last `HOG_WINDOW` samples
+    top_hog: Option<(i32, f32)>,
+    /// The task manager's own average CPU usage and resident memory
+    own_usage: Option<(f32, u64)>,
+    /// Refreshing half as often, with coarser charts, because `own_usage` went over
+    /// `self_cpu_limit`
+    overhead_reduced: bool,
+    /// Busiest commands by CPU-seconds since the app started
+    session_cpu: Vec<(String, f64)>,
+    show_session_cpu: bool,
+    /// Show CPU usage relative to each process's allowed cores instead of one core
+    cpu_of_allowed_cores: bool,
+    /// Text of the watch list box, and the PIDs parsed from it
+    watch_input: String,
+    watch_pids: Vec<i32>,
+    exited_pids: Vec<i32>,
+    /// Commands that keep exiting and being restarted, most restarts first
+    flapping: Vec<(String, u32)>,
+    stable_rows: bool,
+    /// When refreshing was paused and the processes at the time; `None` while live
+    paused: Option<(Instant, Vec<ProcessInfo>)>,
+    /// How long the last pause lasted and the processes at its start, kept after
+    /// resuming until the next refresh is compared with them
+    resume_baseline: Option<(Duration, Vec<ProcessInfo>)>,
+    pause_diff: Option<PauseDiff>,
+    manual_refresh_pending: bool,
+    /// A Tick's refresh is running; Ticks meanwhile queue one more refresh after it
+    refresh_running: bool,
+    refresh_queued: bool,
+    selected_index: Option<usize>,
+    details_pid: Option<i32>,
+    /// Process awaiting kill confirmation, and the signal the confirmation sends
+    pending_kill: Option<(i32, Signal)>,
+    last_kill_requests: HashMap<i32, Instant>,
+    window_width: f32,
+    window_height: f32,
+    /// Scroll position of the process list in pixels, as last reported by the list
+    list_offset: f32,
+    /// Effective UID of this process; only root may signal other users' processes
+    euid: u32,
+    show_privilege_warning: bool,
+    /// Whether this or an earlier GUI paused automation; see `crate::automation`
+    automation_paused: bool,
+    /// Set once the settings have been written on close, so they're saved exactly once
+    settings_saved: bool,
+    /// GUI settings when the window opened; only the ones changed since are saved
+    opened_with: GuiSettings,
+    /// Whether this instance owns the IPC socket and must remove it on exit
+    serving_ipc: bool,
+    /// Kill buttons only report what they would do
+    preview_kills: bool,
+    /// Outcome of the last kill, shown until dismissed
+    status_message: Option<String>,
+    /// Shift is held down, turning a click on Refresh into a deep refresh
+    shift_held: bool,
+    /// Last key press, click or scroll, for the idle timeout
+    last_interaction: Instant,
+    /// Refreshing at `idle_refresh_interval_ms` because the window is unfocused or unused
+    idle: bool,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
+    Tick,
     Refresh,
-    RefreshComplete(Vec<ProcessInfo>, Vec<f32>, Vec<f32>),
-    KillProcess(i32),
-    KillComplete(Result<(), String>),
+    RefreshComplete(Box<RefreshSnapshot>),
+    KillProcess(i32, Signal),
+    KillComplete(i32, Signal, Result<(), String>),
     SearchChanged(String),
+    WatchListChanged(String),
     SortBy(SortField),
     ToggleGraphs,
+    RefreshDisks,
+    DisksRefreshed(Vec<DiskInfo>),
+    ToggleDisks,
+    ToggleStableRows,
+    TogglePause,
+    DismissPauseDiff,
+    SelectNext,
+    SelectPrevious,
+    ActivateSelection,
+    RequestKillSelected,
+    /// Changes the signal the pending kill confirmation sends
+    ChooseKillSignal(Signal),
+    ConfirmKill,
+    CloseDetails,
+    Dismiss,
+    ToggleCommandDisplay,
+    ToggleCompareMode,
+    CompareProcess(i32),
+    ClearComparison,
+    /// Charts the process's history and opens its details, or charts the system's
+    /// history again if the process is already charted
+    SelectProcess(i32),
+    SelectedHistoryLoaded(i32, Option<ProcessSeries>),
+    WindowResized(u32, u32),
+    ListScrolled(scrollable::Viewport),
+    DismissPrivilegeWarning,
+    DeepRefresh,
+    CloseRequested,
+    ToggleFilterMode,
+    ToggleTermMatch,
+    ToggleShowIgnored,
+    /// Adds the process's command to the ignore list, or takes it off if it's there
+    ToggleIgnored(i32),
+    ToggleGrowth,
+    ToggleSessionCpu,
+    ToggleRowDensity,
+    ToggleHighContrast,
+    /// Pauses or resumes alert-triggered actions, here and in running CLI commands
+    TogglePauseAutomation,
+    ToggleChartLayout,
+    ToggleTreeView,
+    ToggleCpuSmoothing,
+    ToggleCpuNormalization,
+    KillChecked(i32, Signal, bool),
+    KillPreviewed(Result<String, String>),
+    /// Quarantines the process, or releases it if it's quarantined
+    ToggleQuarantine(i32, bool),
+    QuarantineComplete(Result<String, String>),
+    TogglePreviewKills,
+    ToggleKillSubtrees,
+    /// Shows or hides the process's children in tree view
+    ToggleCollapsed(i32),
+    /// The PIDs below the killed process that couldn't be signalled, with why
+    TreeKillComplete(i32, Signal, Result<Vec<(i32, String)>, String>),
+    DismissStatus,
+    ClearHistory,
+    CpuRangeChanged(RangeEnd, f32),
+    PresetSelected(String),
+    PresetNameChanged(String),
+    SavePreset,
+    DeletePreset,
+    MemoryRangeChanged(RangeEnd, f32),
+    ModifiersChanged(keyboard::Modifiers),
+    UserActivity,
+    FocusChanged(bool),
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum SortOrder {
-    Ascending,
-    Descending,
+/// What changed while refreshing was paused, shown for `PAUSE_DIFF_SHOWN` after
+/// resuming.
+#[derive(Debug, Clone)]
+struct PauseDiff {
+    paused_for: Duration,
+    diff: ProcessDiff,
+    shown_at: Instant,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum SortField {
-    PID,
-    CPU,
-    Memory,
-    Command,
+/// What the details panel shows beyond `ProcessInfo`, read only for its process.
+#[derive(Debug, Clone)]
+pub struct DetailsExtras {
+    pid: i32,
+    cgroup: Option<CgroupLimits>,
+    sched_policy: Result<(SchedPolicy, i32), String>,
+    oom_score_adj: Result<i32, String>,
+    quarantined: bool,
+    /// `None` once the process has exited
+    details: Option<ProcessDetails>,
+    history: Option<ProcessHistory>,
+}
+
+/// CPU and memory history of one process, on the same scales as the system charts.
+#[derive(Debug, Clone)]
+pub struct ProcessSeries {
+    pid: i32,
+    cpu_usage: Vec<f32>,
+    /// Resident memory as a percentage of installed memory
+    memory_usage: Vec<f32>,
+}
+
+impl ProcessSeries {
+    /// `None` once the process has exited.
+    fn capture(handler: &ProcessHandler, pid: i32) -> Option<Self> {
+        let history = handler.process_history(pid)?;
+        let total_memory = handler.total_memory().max(1) as f32;
+        Some(Self {
+            pid,
+            cpu_usage: history.cpu_usage.iter().copied().collect(),
+            memory_usage: history
+                .memory_usage
+                .iter()
+                .map(|&bytes| bytes as f32 / total_memory * 100.0)
+                .collect(),
+        })
+    }
+}
+
+/// Data gathered by one background refresh; empty until the first one completes.
+#[derive(Debug, Clone, Default)]
+pub struct RefreshSnapshot {
+    processes: Vec<ProcessInfo>,
+    cpu_usage_history: Vec<f32>,
+    cpu_range_history: Vec<(f32, f32)>,
+    memory_usage_history: Vec<f32>,
+    chart_events: Vec<(usize, String)>,
+    /// p50, p95 and p99 of the two histories
+    cpu_percentiles: (f32, f32, f32),
+    memory_percentiles: (f32, f32, f32),
+    cpu_since_boot: Option<CpuSinceBoot>,
+    cpu_temperature: Option<f32>,
+    /// Received and sent bytes per second, lined up with `cpu_usage_history`
+    network_history: Vec<(u64, u64)>,
+    /// Bytes read from and written to disk per second, lined up the same way
+    disk_io_history: Vec<(u64, u64)>,
+    /// Number of processes in each state
+    status_counts: HashMap<String, usize>,
+    details_extras: Option<DetailsExtras>,
+    compared_histories: Vec<(i32, ProcessHistory)>,
+    selected_history: Option<ProcessSeries>,
+    growth_leaders: Vec<(i32, i64)>,
+    /// PID and average CPU of the top consumer over the last `HOG_WINDOW` samples
+    top_hog: Option<(i32, f32)>,
+    own_usage: Option<(f32, u64)>,
+    session_cpu: Vec<(String, f64)>,
+    /// Watched PIDs that no longer exist
+    exited_pids: Vec<i32>,
+    /// Commands restarted at least `FLAPPING_RESTARTS` times, most restarts first
+    flapping: Vec<(String, u32)>,
+}
+
+impl RefreshSnapshot {
+    /// With a non-empty `watch_pids`, only those processes are refreshed.
+    fn capture(
+        handler: &mut ProcessHandler,
+        compare_pids: &[i32],
+        watch_pids: &[i32],
+        details_pid: Option<i32>,
+        selected_pid: Option<i32>,
+    ) -> Self {
+        let (processes, exited_pids) = if watch_pids.is_empty() {
+            (handler.refresh_processes(), Vec::new())
+        } else {
+            let mut processes = Vec::new();
+            let mut exited_pids = Vec::new();
+            for (pid, info) in handler.refresh_watched(watch_pids) {
+                match info {
+                    Some(info) => processes.push(info),
+                    None => exited_pids.push(pid),
+                }
+            }
+            (processes, exited_pids)
+        };
+        let mut flapping: Vec<(String, u32)> = handler
+            .restart_counts()
+            .into_iter()
+            .filter(|(_, restarts)| *restarts >= FLAPPING_RESTARTS)
+            .collect();
+        flapping.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let compared_histories = compare_pids
+            .iter()
+            .filter_map(|&pid| handler.process_history(pid).map(|history| (pid, history.clone())))
+            .collect();
+        Self {
+            processes,
+            cpu_usage_history: handler.get_cpu_usage_history().to_vec(),
+            cpu_range_history: handler.cpu_range_history().to_vec(),
+            memory_usage_history: handler.get_memory_usage_history().to_vec(),
+            chart_events: handler.chart_events(),
+            cpu_percentiles: handler.cpu_percentiles(),
+            memory_percentiles: handler.memory_percentiles(),
+            cpu_since_boot: handler.cpu_since_boot(),
+            cpu_temperature: handler.cpu_temperature(),
+            network_history: handler.network_history().to_vec(),
+            disk_io_history: handler.disk_io_history().to_vec(),
+            status_counts: handler.status_counts(),
+            details_extras: details_pid.map(|pid| DetailsExtras {
+                pid,
+                cgroup: handler.cgroup_limits(pid),
+                sched_policy: handler.get_sched_policy(pid).map_err(|e| e.to_string()),
+                oom_score_adj: handler.get_oom_score_adj(pid).map_err(|e| e.to_string()),
+                quarantined: handler
+                    .quarantined()
+                    .is_ok_and(|records| records.iter().any(|record| record.pid == pid)),
+                details: handler.get_process_details(pid).ok(),
+                history: handler.process_history(pid).cloned(),
+            }),
+            compared_histories,
+            selected_history: selected_pid.and_then(|pid| ProcessSeries::capture(handler, pid)),
+            growth_leaders: handler
+                .memory_growth_leaderboard(GROWTH_WINDOW)
+                .into_iter()
+                .take(GROWTH_LEADERS)
+                .collect(),
+            top_hog: handler
+                .top_cpu_consumer(HOG_WINDOW)
+                .filter(|&(_, cpu)| cpu >= HOG_MIN_CPU),
+            own_usage: handler.own_usage(SELF_CHECK_WINDOW),
+            session_cpu: handler
+                .session_cpu_by_command()
+                .into_iter()
+                .take(SESSION_CPU_ENTRIES)
+                .collect(),
+            exited_pids,
+            flapping,
+        }
+    }
 }
 
 impl Application for TaskManager {
     type Executor = executor::Default;
     type Message = Message;
     type Theme = Theme;
-    type Flags = ();
+    type Flags = Config;
 
-    fn new(_flags: ()) -> (Self, Command<Message>) {
-        let process_handler = ProcessHandler::new();
+    fn new(config: Config) -> (Self, Command<Message>) {
+        let mut process_handler = ProcessHandler::with_history_capacity(config.history_capacity);
+        process_handler.set_kill_policy(config.kill_policy.clone());
+        if config.cpu_fine_sample_ms > 0 {
+            process_handler
+                .start_fine_sampling(Duration::from_millis(config.cpu_fine_sample_ms));
+        }
         let handler = Arc::new(Mutex::new(process_handler));
-        let processes = handler.lock().unwrap().refresh_processes();
-        let cpu_usage_history = handler.lock().unwrap().get_cpu_usage_history().to_vec();
-        let memory_usage_history = handler.lock().unwrap().get_memory_usage_history().to_vec();
+        // The first scan waits out a CPU sample interval, so it runs like any other Tick
+        // instead of holding up the first frame
+        let snapshot = RefreshSnapshot::default();
+        let disks = handler.lock().unwrap().disk_usage();
+        // Lets `list --attach` reuse our scans; the GUI works the same without it
+        let serving_ipc = match ipc::spawn_server(Arc::clone(&handler)) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("CLI sharing disabled: {}", e);
+                false
+            }
+        };
+
+        let opened_with = config.gui_settings();
 
         (
             TaskManager {
+                sort_field: opened_with.sort,
+                sort_order: opened_with.order,
+                config,
                 process_handler: handler,
-                processes: processes.clone(),
-                filtered_processes: processes,
-                cpu_usage_history,
-                memory_usage_history,
+                processes: snapshot.processes.clone(),
+                filtered_processes: snapshot.processes,
+                cpu_usage_history: snapshot.cpu_usage_history,
+                cpu_range_history: snapshot.cpu_range_history,
+                memory_usage_history: snapshot.memory_usage_history,
+                chart_events: snapshot.chart_events,
+                cpu_percentiles: snapshot.cpu_percentiles,
+                memory_percentiles: snapshot.memory_percentiles,
+                cpu_since_boot: snapshot.cpu_since_boot,
+                cpu_temperature: snapshot.cpu_temperature,
+                network_history: snapshot.network_history,
+                disk_io_history: snapshot.disk_io_history,
+                status_counts: snapshot.status_counts,
+                details_extras: snapshot.details_extras,
+                compare_mode: false,
+                compare_pids: [None; 2],
+                compared_histories: snapshot.compared_histories,
+                selected_pid: None,
+                selected_history: None,
+                growth_leaders: snapshot.growth_leaders,
+                top_hog: snapshot.top_hog,
+                own_usage: snapshot.own_usage,
+                overhead_reduced: false,
+                show_growth: false,
+                session_cpu: snapshot.session_cpu,
+                watch_input: String::new(),
+                watch_pids: Vec::new(),
+                exited_pids: snapshot.exited_pids,
+                flapping: snapshot.flapping,
+                show_session_cpu: false,
+                cpu_of_allowed_cores: false,
                 search_query: String::new(),
-                sort_field: SortField::PID,
-                sort_order: SortOrder::Ascending,
+                filter_mode: FilterMode::Substring,
+                term_match: TermMatch::Phrase,
+                show_ignored: false,
+                cpu_range: UsageRange::UNBOUNDED,
+                memory_range: UsageRange::UNBOUNDED,
+                selected_preset: None,
+                preset_name: String::new(),
                 show_graphs: true,
+                tree_view: false,
+                tree_depths: Vec::new(),
+                tree_descendants: Vec::new(),
+                collapsed: HashSet::new(),
+                kill_subtrees: false,
+                overlay_charts: false,
+                disks,
+                show_disks: false,
+                stable_rows: false,
+                paused: None,
+                resume_baseline: None,
+                pause_diff: None,
+                manual_refresh_pending: false,
+                refresh_running: false,
+                refresh_queued: false,
+                selected_index: None,
+                details_pid: None,
+                pending_kill: None,
+                last_kill_requests: HashMap::new(),
+                // Matches iced's default window size until the first resize event
+                window_width: 1024.0,
+                window_height: 768.0,
+                list_offset: 0.0,
+                euid: geteuid().as_raw(),
+                show_privilege_warning: !geteuid().is_root(),
+                automation_paused: automation::is_paused_from_gui(),
+                settings_saved: false,
+                opened_with,
+                serving_ipc,
+                preview_kills: false,
+                status_message: None,
+                shift_held: false,
+                last_interaction: Instant::now(),
+                idle: false,
             },
-            Command::none(),
+            Command::perform(async { Message::Tick }, |msg| msg),
         )
     }
 
@@ -91,58 +706,416 @@ impl Application for TaskManager {
         String::from("Rust Task Manager")
     }
 
+    fn theme(&self) -> Theme {
+        if self.config.high_contrast {
+            return Theme::custom(HIGH_CONTRAST_PALETTE);
+        }
+        match self.config.theme {
+            ThemeChoice::Light => Theme::Light,
+            ThemeChoice::Dark => Theme::Dark,
+        }
+    }
+
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
+            Message::Refresh if self.shift_held => self.update(Message::DeepRefresh),
             Message::Refresh => {
+                self.manual_refresh_pending = true;
+                Command::perform(async { Message::Tick }, |msg| msg)
+            }
+            Message::DeepRefresh => {
+                self.manual_refresh_pending = true;
                 let handler = Arc::clone(&self.process_handler);
                 Command::perform(
                     async move {
-                        let mut handler = handler.lock().unwrap();
-                        handler.refresh();
-                        let processes = handler.refresh_processes();
-                        let cpu_usage_history = handler.get_cpu_usage_history().to_vec();
-                        let memory_usage_history = handler.get_memory_usage_history().to_vec();
-                        Message::RefreshComplete(
-                            processes,
-                            cpu_usage_history,
-                            memory_usage_history,
-                        )
+                        // Sample once and wait, so the following Tick reports real CPU
+                        // usage for processes that started since the last refresh
+                        let interval = tokio::task::spawn_blocking(move || {
+                            let mut handler = handler.lock().unwrap();
+                            handler.warm_up_cpu_sampling();
+                            handler.cpu_sample_interval()
+                        })
+                        .await
+                        .expect("CPU warm-up panicked");
+                        tokio::time::sleep(interval).await;
+                        Message::Tick
                     },
                     |msg| msg,
                 )
             }
-            Message::RefreshComplete(processes, cpu_usage_history, memory_usage_history) => {
-                self.processes = processes;
-                self.cpu_usage_history = cpu_usage_history;
-                self.memory_usage_history = memory_usage_history;
-                self.apply_filter_and_sort();
+            Message::ModifiersChanged(modifiers) => {
+                self.shift_held = modifiers.shift();
+                Command::none()
+            }
+            Message::UserActivity | Message::FocusChanged(true) => {
+                self.last_interaction = Instant::now();
+                if self.idle {
+                    debug!("Resuming normal refresh rate");
+                    self.idle = false;
+                    // Don't make the user wait out the old slow interval
+                    return self.update(Message::Tick);
+                }
+                Command::none()
+            }
+            Message::FocusChanged(false) => {
+                if self.config.idle_timeout_secs > 0 {
+                    debug!("Window unfocused, slowing refresh");
+                    self.idle = true;
+                }
                 Command::none()
             }
-            Message::KillProcess(pid) => {
+            Message::Tick => {
+                if self.paused.is_some() {
+                    return Command::none();
+                }
+                // Slow refreshes would otherwise pile up behind the handler lock
+                if self.refresh_running {
+                    self.refresh_queued = true;
+                    return Command::none();
+                }
+                if self
+                    .pause_diff
+                    .as_ref()
+                    .is_some_and(|pause_diff| pause_diff.shown_at.elapsed() >= PAUSE_DIFF_SHOWN)
+                {
+                    self.pause_diff = None;
+                }
+                let idle_timeout = Duration::from_secs(self.config.idle_timeout_secs);
+                if !self.idle
+                    && !idle_timeout.is_zero()
+                    && self.last_interaction.elapsed() >= idle_timeout
+                {
+                    debug!("No input for {:?}, slowing refresh", idle_timeout);
+                    self.idle = true;
+                }
                 let handler = Arc::clone(&self.process_handler);
+                let compare_pids: Vec<i32> = self.compare_pids.iter().flatten().copied().collect();
+                let watch_pids = self.watch_pids.clone();
+                let details_pid = self.details_pid;
+                let selected_pid = self.selected_pid;
+                let cpu_averaging =
+                    self.config.smooth_cpu.then_some(self.config.cpu_average_window);
+                let cpu_alert = self.config.cpu_alert_threshold;
+                let memory_alert = self.config.memory_alert_threshold;
+                // A refresh blocks while it waits out the CPU sample interval, holding the
+                // handler lock, so it runs on a blocking thread rather than an executor one
+                let refresh = move || {
+                    let started = Instant::now();
+                    let mut handler = handler.lock().unwrap();
+                    handler.set_cpu_averaging(cpu_averaging);
+                    handler.refresh();
+                    // Marked when usage crosses the threshold, not on every sample over it
+                    let crossed = |history: &[f32], threshold: f32| match history {
+                        [.., before, now] => *before <= threshold && *now > threshold,
+                        _ => false,
+                    };
+                    if crossed(handler.get_cpu_usage_history(), cpu_alert) {
+                        handler.mark_event("CPU alert");
+                    }
+                    if crossed(handler.get_memory_usage_history(), memory_alert) {
+                        handler.mark_event("memory alert");
+                    }
+                    let snapshot = RefreshSnapshot::capture(
+                        &mut handler,
+                        &compare_pids,
+                        &watch_pids,
+                        details_pid,
+                        selected_pid,
+                    );
+                    debug!(
+                        elapsed_ms = started.elapsed().as_millis() as u64,
+                        processes = snapshot.processes.len(),
+                        "Refresh complete"
+                    );
+                    Message::RefreshComplete(Box::new(snapshot))
+                };
+                self.refresh_running = true;
                 Command::perform(
                     async move {
-                        let handler = handler.lock().unwrap();
-                        let result = handler.kill_process(pid);
-                        Message::KillComplete(result)
+                        tokio::task::spawn_blocking(refresh).await.expect("refresh panicked")
                     },
                     |msg| msg,
                 )
             }
-            Message::KillComplete(result) => {
+            Message::RefreshComplete(snapshot) => {
+                self.refresh_running = false;
+                let queued = if std::mem::take(&mut self.refresh_queued) {
+                    Command::perform(async { Message::Tick }, |msg| msg)
+                } else {
+                    Command::none()
+                };
+                // A refresh already running when the list was paused
+                if self.paused.is_some() {
+                    return Command::none();
+                }
+                if let Some((paused_for, before)) = self.resume_baseline.take() {
+                    let diff = diff_processes(
+                        &before,
+                        &snapshot.processes,
+                        PAUSE_DIFF_CPU,
+                        PAUSE_DIFF_MEMORY,
+                    );
+                    self.pause_diff = Some(PauseDiff {
+                        paused_for,
+                        diff,
+                        shown_at: Instant::now(),
+                    });
+                }
+                self.processes = snapshot.processes;
+                self.cpu_usage_history = snapshot.cpu_usage_history;
+                self.cpu_range_history = snapshot.cpu_range_history;
+                self.memory_usage_history = snapshot.memory_usage_history;
+                self.chart_events = snapshot.chart_events;
+                self.cpu_percentiles = snapshot.cpu_percentiles;
+                self.memory_percentiles = snapshot.memory_percentiles;
+                self.cpu_since_boot = snapshot.cpu_since_boot;
+                self.cpu_temperature = snapshot.cpu_temperature;
+                self.network_history = snapshot.network_history;
+                self.disk_io_history = snapshot.disk_io_history;
+                self.status_counts = snapshot.status_counts;
+                self.details_extras = snapshot.details_extras;
+                self.compared_histories = snapshot.compared_histories;
+                self.growth_leaders = snapshot.growth_leaders;
+                self.top_hog = snapshot.top_hog;
+                self.own_usage = snapshot.own_usage;
+                self.check_overhead();
+                self.session_cpu = snapshot.session_cpu;
+                self.exited_pids = snapshot.exited_pids;
+                self.flapping = snapshot.flapping;
+                if let Some(pid) = self.selected_pid {
+                    if self.processes.iter().any(|p| p.pid == pid) {
+                        // Captured before a click on another row is for the old process
+                        if let Some(series) = snapshot.selected_history.filter(|s| s.pid == pid) {
+                            self.selected_history = Some(series);
+                        }
+                    } else {
+                        // Back to the system charts once the charted process exits
+                        self.selected_pid = None;
+                        self.selected_history = None;
+                    }
+                }
+                // In stable mode only an explicit refresh is allowed to reorder rows
+                let skip_sort = self.stable_rows && !self.manual_refresh_pending;
+                self.manual_refresh_pending = false;
+                self.apply_filter_and_sort(skip_sort);
+                queued
+            }
+            Message::KillProcess(pid, signal) => {
+                let now = Instant::now();
+                self.last_kill_requests
+                    .retain(|_, requested| now.duration_since(*requested) < KILL_COOLDOWN);
+                if self.last_kill_requests.contains_key(&pid) {
+                    debug!(pid, "Ignoring repeated kill request");
+                    return Command::none();
+                }
+                self.last_kill_requests.insert(pid, now);
+
+                let handler = Arc::clone(&self.process_handler);
+                let subtree = self.kill_subtrees;
+                if self.preview_kills {
+                    return with_handler(handler, move |handler| {
+                        let below = if subtree { handler.descendants(pid).len() } else { 0 };
+                        let preview = handler.describe_signal(pid, signal).map(|action| {
+                            match below {
+                                0 => action,
+                                n => format!("{} and the {} processes below it", action, n),
+                            }
+                        });
+                        Message::KillPreviewed(preview.map_err(|e| e.to_string()))
+                    });
+                }
+                if subtree {
+                    return with_handler(handler, move |handler| {
+                        let result = handler
+                            .kill_tree(pid, signal)
+                            .map(|failed| {
+                                failed
+                                    .into_iter()
+                                    .map(|(child, e)| (child, kill_error_message(e)))
+                                    .collect()
+                            })
+                            .map_err(kill_error_message);
+                        if result.is_ok() {
+                            handler.mark_event(format!("kill tree {}", pid));
+                        }
+                        Message::TreeKillComplete(pid, signal, result)
+                    });
+                }
+                with_handler(handler, move |handler| {
+                    let result = handler.kill_process(pid, signal).map_err(kill_error_message);
+                    if result.is_ok() {
+                        handler.mark_event(format!("kill {}", pid));
+                    }
+                    Message::KillComplete(pid, signal, result)
+                })
+            }
+            Message::KillPreviewed(result) => {
+                self.status_message = Some(match result {
+                    Ok(action) => format!("Preview: would {}", action),
+                    Err(e) => format!("Preview: {}", e),
+                });
+                Command::none()
+            }
+            Message::ToggleQuarantine(pid, quarantined) => {
+                let handler = Arc::clone(&self.process_handler);
+                with_handler(handler, move |handler| {
+                    let result = if quarantined {
+                        match handler.unquarantine(pid) {
+                            Ok(record) => Ok(format!(
+                                "Released PID {}: nice {}, CPUs {}",
+                                pid,
+                                record.nice,
+                                format_cpu_list(&record.cpus)
+                            )),
+                            Err(ProcessError::PermissionDenied { action: RESTORE_NICE, .. }) => {
+                                Ok(format!(
+                                    "Resumed PID {}, but its nice value stays at 19 \
+                                     (lowering it needs root)",
+                                    pid
+                                ))
+                            }
+                            Err(e) => Err(e.to_string()),
+                        }
+                    } else {
+                        handler.quarantine(pid).map_err(|e| e.to_string()).map(|_| {
+                            format!("Quarantined PID {}: stopped, nice 19, one CPU", pid)
+                        })
+                    };
+                    if result.is_ok() {
+                        let action = if quarantined { "release" } else { "quarantine" };
+                        handler.mark_event(format!("{} {}", action, pid));
+                    }
+                    Message::QuarantineComplete(result)
+                })
+            }
+            Message::QuarantineComplete(result) => {
+                match result {
+                    Ok(message) => {
+                        info!("{}", message);
+                        self.status_message = Some(message);
+                    }
+                    Err(e) => {
+                        warn!("{}", e);
+                        self.status_message = Some(e);
+                    }
+                }
+                Command::perform(async { Message::Tick }, |msg| msg)
+            }
+            Message::ToggleKillSubtrees => {
+                self.kill_subtrees = !self.kill_subtrees;
+                Command::none()
+            }
+            Message::ToggleCollapsed(pid) => {
+                if !self.collapsed.remove(&pid) {
+                    self.collapsed.insert(pid);
+                }
+                self.apply_filter_and_sort(false);
+                Command::none()
+            }
+            Message::TogglePreviewKills => {
+                self.preview_kills = !self.preview_kills;
+                Command::none()
+            }
+            Message::KillComplete(pid, signal, result) => {
+                let refresh = Command::perform(async { Message::Tick }, |msg| msg);
                 match result {
                     Ok(_) => {
-                        println!("Process killed successfully.");
+                        info!(pid, %signal, "Signal sent");
+                        // SIGTERM and SIGHUP can be caught or ignored, and even SIGKILL
+                        // waits out uninterruptible sleep, so check back later
+                        let handler = Arc::clone(&self.process_handler);
+                        let delay = Duration::from_millis(self.config.kill_check_delay_ms);
+                        let check = Command::perform(
+                            async move {
+                                tokio::time::sleep(delay).await;
+                                let alive = tokio::task::spawn_blocking(move || {
+                                    handler.lock().unwrap().is_alive(pid)
+                                })
+                                .await
+                                .expect("kill check panicked");
+                                Message::KillChecked(pid, signal, alive)
+                            },
+                            |msg| msg,
+                        );
+                        Command::batch([refresh, check])
                     }
                     Err(e) => {
-                        println!("{}", e);
+                        warn!(pid, "{}", e);
+                        self.status_message = Some(e);
+                        refresh
+                    }
+                }
+            }
+            Message::TreeKillComplete(pid, signal, result) => match result {
+                Ok(failed) if !failed.is_empty() => {
+                    // Reported instead of checking on the process, which would replace it
+                    warn!(pid, failed = failed.len(), "Signal not sent to the whole tree");
+                    let reasons: Vec<String> = failed
+                        .iter()
+                        .map(|(child, e)| format!("PID {}: {}", child, e))
+                        .collect();
+                    self.status_message = Some(format!(
+                        "Sent {} to PID {} but not to {} below it: {}",
+                        signal,
+                        pid,
+                        failed.len(),
+                        reasons.join("; ")
+                    ));
+                    Command::perform(async { Message::Tick }, |msg| msg)
+                }
+                result => self.update(Message::KillComplete(pid, signal, result.map(|_| ()))),
+            },
+            Message::KillChecked(pid, signal, alive) => {
+                self.status_message = Some(if alive {
+                    warn!(pid, %signal, "Process still alive after signal");
+                    if signal == Signal::SIGKILL {
+                        format!("PID {} still alive after SIGKILL (stuck in the kernel?)", pid)
+                    } else {
+                        format!("PID {} still alive after {} — try SIGKILL?", pid, signal)
                     }
+                } else {
+                    format!("PID {} terminated", pid)
+                });
+                Command::none()
+            }
+            Message::ClearHistory => {
+                // Empty the charts right away rather than after the next refresh
+                self.cpu_usage_history.clear();
+                self.cpu_range_history.clear();
+                self.memory_usage_history.clear();
+                self.network_history.clear();
+                self.disk_io_history.clear();
+                self.chart_events.clear();
+                self.compared_histories.clear();
+                if let Some(series) = &mut self.selected_history {
+                    series.cpu_usage.clear();
+                    series.memory_usage.clear();
                 }
-                Command::perform(async { Message::Refresh }, |msg| msg)
+                self.growth_leaders.clear();
+                self.session_cpu.clear();
+                self.flapping.clear();
+                let handler = Arc::clone(&self.process_handler);
+                with_handler(handler, |handler| {
+                    handler.clear_history();
+                    Message::Tick
+                })
+            }
+            Message::DismissStatus => {
+                self.status_message = None;
+                Command::none()
+            }
+            Message::WatchListChanged(input) => {
+                // Tokens that aren't PIDs are ignored while the user is still typing
+                self.watch_pids = input
+                    .split([',', ' '])
+                    .filter_map(|token| token.trim().parse().ok())
+                    .collect();
+                self.watch_input = input;
+                Command::perform(async { Message::Tick }, |msg| msg)
             }
             Message::SearchChanged(query) => {
                 self.search_query = query;
-                self.apply_filter_and_sort();
+                self.apply_filter_and_sort(false);
                 Command::none()
             }
             Message::SortBy(field) => {
@@ -153,19 +1126,366 @@ impl Application for TaskManager {
                     };
                 } else {
                     self.sort_field = field;
-                    self.sort_order = SortOrder::Ascending;
+                    self.sort_order = self.config.initial_order(field);
                 }
-                self.apply_filter_and_sort();
+                self.apply_filter_and_sort(false);
                 Command::none()
             }
             Message::ToggleGraphs => {
                 self.show_graphs = !self.show_graphs;
                 Command::none()
             }
+            Message::ToggleTreeView => {
+                self.tree_view = !self.tree_view;
+                self.apply_filter_and_sort(false);
+                Command::none()
+            }
+            Message::ToggleChartLayout => {
+                self.overlay_charts = !self.overlay_charts;
+                Command::none()
+            }
+            Message::RefreshDisks => {
+                let handler = Arc::clone(&self.process_handler);
+                with_handler(handler, |handler| {
+                    handler.refresh_disks();
+                    Message::DisksRefreshed(handler.disk_usage())
+                })
+            }
+            Message::DisksRefreshed(disks) => {
+                self.disks = disks;
+                Command::none()
+            }
+            Message::SelectNext => {
+                if self.filtered_processes.is_empty() {
+                    return Command::none();
+                }
+                let last = self.filtered_processes.len() - 1;
+                let index = self.selected_index.map_or(0, |index| (index + 1).min(last));
+                self.select(index)
+            }
+            Message::SelectPrevious => {
+                if self.filtered_processes.is_empty() {
+                    return Command::none();
+                }
+                let index = self.selected_index.map_or(0, |index| index.saturating_sub(1));
+                self.select(index)
+            }
+            Message::ActivateSelection => {
+                // Enter confirms a pending kill, otherwise it opens the details panel
+                if self.pending_kill.is_some() {
+                    return self.update(Message::ConfirmKill);
+                }
+                self.details_pid = self.selected_process().map(|p| p.pid);
+                Command::none()
+            }
+            Message::RequestKillSelected => {
+                self.pending_kill = self.selected_process().map(|p| (p.pid, Signal::SIGTERM));
+                Command::none()
+            }
+            Message::ChooseKillSignal(signal) => {
+                if let Some((_, pending)) = &mut self.pending_kill {
+                    *pending = signal;
+                }
+                Command::none()
+            }
+            Message::ConfirmKill => match self.pending_kill.take() {
+                Some((pid, signal)) => self.update(Message::KillProcess(pid, signal)),
+                None => Command::none(),
+            },
+            Message::CloseDetails => {
+                self.details_pid = None;
+                Command::none()
+            }
+            Message::Dismiss => {
+                if self.pending_kill.is_some() {
+                    self.pending_kill = None;
+                } else {
+                    self.details_pid = None;
+                }
+                Command::none()
+            }
+            Message::ToggleCommandDisplay => {
+                self.config.command_display = match self.config.command_display {
+                    CommandDisplay::Name => CommandDisplay::Path,
+                    CommandDisplay::Path => CommandDisplay::Name,
+                };
+                self.apply_filter_and_sort(false);
+                Command::none()
+            }
+            Message::ToggleCompareMode => {
+                self.compare_mode = !self.compare_mode;
+                Command::none()
+            }
+            Message::CompareProcess(pid) => {
+                match self.compare_slot(pid) {
+                    // Clicking a compared process again removes it
+                    Some(slot) => self.compare_pids[slot] = None,
+                    None => match self.compare_pids {
+                        [None, _] => self.compare_pids[0] = Some(pid),
+                        [_, None] => self.compare_pids[1] = Some(pid),
+                        // Both slots taken: drop the oldest choice
+                        [_, Some(second)] => self.compare_pids = [Some(second), Some(pid)],
+                    },
+                }
+                Command::none()
+            }
+            Message::ClearComparison => {
+                self.compare_pids = [None; 2];
+                self.compared_histories.clear();
+                Command::none()
+            }
+            Message::SelectProcess(pid) => {
+                self.selected_history = None;
+                if self.selected_pid == Some(pid) {
+                    self.selected_pid = None;
+                    return Command::none();
+                }
+                self.selected_pid = Some(pid);
+                self.selected_index = self.filtered_processes.iter().position(|p| p.pid == pid);
+                self.details_pid = Some(pid);
+                // Chart the history gathered so far now rather than after the next refresh
+                let handler = Arc::clone(&self.process_handler);
+                with_handler(handler, move |handler| {
+                    Message::SelectedHistoryLoaded(pid, ProcessSeries::capture(handler, pid))
+                })
+            }
+            Message::SelectedHistoryLoaded(pid, series) => {
+                if self.selected_pid == Some(pid) && series.is_some() {
+                    self.selected_history = series;
+                }
+                Command::none()
+            }
+            Message::CloseRequested => {
+                if !self.settings_saved {
+                    self.settings_saved = true;
+                    let current = GuiSettings {
+                        sort: self.sort_field,
+                        order: self.sort_order,
+                        ..self.config.gui_settings()
+                    };
+                    match self.config.save_gui_settings(&self.opened_with, &current) {
+                        Ok(()) => info!("Saved settings"),
+                        Err(e) => warn!("Failed to save settings: {}", e),
+                    }
+                }
+                if self.serving_ipc {
+                    ipc::remove_socket();
+                }
+                window::close()
+            }
+            Message::DismissPrivilegeWarning => {
+                self.show_privilege_warning = false;
+                Command::none()
+            }
+            Message::WindowResized(width, height) => {
+                self.window_width = width as f32;
+                self.window_height = height as f32;
+                Command::none()
+            }
+            Message::ListScrolled(viewport) => {
+                self.list_offset = viewport.absolute_offset().y;
+                Command::none()
+            }
+            Message::CpuRangeChanged(end, value) => {
+                let top = self.cpu_slider_top();
+                self.cpu_range.set(end, value, top);
+                self.apply_filter_and_sort(false);
+                Command::none()
+            }
+            Message::MemoryRangeChanged(end, value) => {
+                let top = self.memory_slider_top();
+                self.memory_range.set(end, value, top);
+                self.apply_filter_and_sort(false);
+                Command::none()
+            }
+            Message::PresetSelected(name) => {
+                if let Some(preset) = self.config.presets.get(&name) {
+                    self.search_query = preset.query.clone();
+                    self.sort_field = preset.sort;
+                    self.sort_order = preset.order;
+                    self.cpu_range = UsageRange {
+                        min: preset.cpu_min,
+                        max: preset.cpu_max.unwrap_or(f32::INFINITY),
+                    };
+                    self.memory_range = UsageRange {
+                        min: preset.memory_min_mb,
+                        max: preset.memory_max_mb.unwrap_or(f32::INFINITY),
+                    };
+                    self.apply_filter_and_sort(false);
+                }
+                self.preset_name = name.clone();
+                self.selected_preset = Some(name);
+                Command::none()
+            }
+            Message::PresetNameChanged(name) => {
+                self.preset_name = name;
+                Command::none()
+            }
+            Message::SavePreset => {
+                let name = self.preset_name.trim().to_string();
+                if name.is_empty() {
+                    self.status_message = Some("Enter a name to save the preset under".into());
+                    return Command::none();
+                }
+                let finite = |value: f32| value.is_finite().then_some(value);
+                let preset = FilterPreset {
+                    query: self.search_query.clone(),
+                    sort: self.sort_field,
+                    order: self.sort_order,
+                    cpu_min: self.cpu_range.min,
+                    cpu_max: finite(self.cpu_range.max),
+                    memory_min_mb: self.memory_range.min,
+                    memory_max_mb: finite(self.memory_range.max),
+                };
+                self.config.presets.insert(name.clone(), preset);
+                self.status_message = Some(match self.config.save_presets() {
+                    Ok(()) => format!("Saved preset {}", name),
+                    Err(e) => format!("Preset {} kept for this session only: {}", name, e),
+                });
+                self.selected_preset = Some(name);
+                Command::none()
+            }
+            Message::DeletePreset => {
+                if let Some(name) = self.selected_preset.take() {
+                    self.config.presets.remove(&name);
+                    self.preset_name.clear();
+                    if let Err(e) = self.config.save_presets() {
+                        self.status_message = Some(e);
+                    }
+                }
+                Command::none()
+            }
+            Message::ToggleCpuSmoothing => {
+                self.config.smooth_cpu = !self.config.smooth_cpu;
+                Command::perform(async { Message::Tick }, |msg| msg)
+            }
+            Message::ToggleHighContrast => {
+                self.config.high_contrast = !self.config.high_contrast;
+                Command::none()
+            }
+            Message::TogglePauseAutomation => {
+                let paused = !self.automation_paused;
+                match automation::set_paused_from_gui(paused) {
+                    Ok(()) => {
+                        info!(paused, "Automation pause toggled");
+                        self.automation_paused = paused;
+                        self.status_message = Some(
+                            if paused {
+                                "Automation paused: alerts only log what they would trigger"
+                            } else {
+                                "Automation resumed"
+                            }
+                            .to_string(),
+                        );
+                    }
+                    Err(e) => {
+                        let action = if paused { "pause" } else { "resume" };
+                        warn!("Failed to {} automation: {}", action, e);
+                        self.status_message =
+                            Some(format!("Failed to {} automation: {}", action, e));
+                    }
+                }
+                Command::none()
+            }
+            Message::ToggleCpuNormalization => {
+                self.cpu_of_allowed_cores = !self.cpu_of_allowed_cores;
+                Command::none()
+            }
+            Message::ToggleRowDensity => {
+                self.config.row_density = match self.config.row_density {
+                    RowDensity::Comfortable => RowDensity::Compact,
+                    RowDensity::Compact => RowDensity::Comfortable,
+                };
+                Command::none()
+            }
+            Message::ToggleSessionCpu => {
+                self.show_session_cpu = !self.show_session_cpu;
+                Command::none()
+            }
+            Message::ToggleGrowth => {
+                self.show_growth = !self.show_growth;
+                Command::none()
+            }
+            Message::ToggleFilterMode => {
+                self.filter_mode = match self.filter_mode {
+                    FilterMode::Substring => FilterMode::Glob,
+                    FilterMode::Glob => FilterMode::Fuzzy,
+                    FilterMode::Fuzzy => FilterMode::Substring,
+                };
+                self.apply_filter_and_sort(false);
+                Command::none()
+            }
+            Message::ToggleTermMatch => {
+                self.term_match = match self.term_match {
+                    TermMatch::Phrase => TermMatch::Any,
+                    TermMatch::Any => TermMatch::All,
+                    TermMatch::All => TermMatch::Phrase,
+                };
+                self.apply_filter_and_sort(false);
+                Command::none()
+            }
+            Message::ToggleShowIgnored => {
+                self.show_ignored = !self.show_ignored;
+                self.apply_filter_and_sort(false);
+                Command::none()
+            }
+            Message::ToggleIgnored(pid) => {
+                let Some(process) = self.processes.iter().find(|p| p.pid == pid) else {
+                    return Command::none();
+                };
+                let command = process.command.clone();
+                let action = if self.config.is_ignored(pid, &command) {
+                    // PID ranges can't be split, so a process inside one stays hidden
+                    let pid_entry = pid.to_string();
+                    self.config.ignore.retain(|entry| {
+                        let entry = entry.trim();
+                        entry != command && entry != pid_entry
+                    });
+                    "Unhid"
+                } else {
+                    self.config.ignore.push(command.clone());
+                    "Hid"
+                };
+                self.status_message = Some(match self.config.save_ignore_list() {
+                    Ok(()) => format!("{} {}", action, command),
+                    Err(e) => format!("{} {} for this session only: {}", action, command, e),
+                });
+                self.apply_filter_and_sort(false);
+                Command::none()
+            }
+            Message::ToggleStableRows => {
+                self.stable_rows = !self.stable_rows;
+                Command::none()
+            }
+            Message::TogglePause => match self.paused.take() {
+                Some((paused_at, processes)) => {
+                    debug!("Resuming refresh");
+                    self.resume_baseline = Some((paused_at.elapsed(), processes));
+                    self.update(Message::Tick)
+                }
+                None => {
+                    debug!("Pausing refresh");
+                    self.paused = Some((Instant::now(), self.processes.clone()));
+                    self.pause_diff = None;
+                    Command::none()
+                }
+            },
+            Message::DismissPauseDiff => {
+                self.pause_diff = None;
+                Command::none()
+            }
+            Message::ToggleDisks => {
+                self.show_disks = !self.show_disks;
+                if self.show_disks {
+                    Command::perform(async { Message::RefreshDisks }, |msg| msg)
+                } else {
+                    Command::none()
+                }
+            }
         }
     }
 
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let header = Row::new()
             .padding(10)
             .spacing(20)
@@ -181,100 +1501,704 @@ impl Application for TaskManager {
                 .size(20)
                 .width(Length::Fixed(300.0)), // Use Length::Fixed here
             )
+            .push(
+                TextInput::new("Watch PIDs (e.g. 12,34)", &self.watch_input)
+                    .on_input(Message::WatchListChanged)
+                    .padding(10)
+                    .size(20)
+                    .width(Length::Fixed(220.0)),
+            )
             .push(
                 Button::new(Text::new(if self.show_graphs { "Hide Graphs" } else { "Show Graphs" }))
                     .on_press(Message::ToggleGraphs)
                     .padding(10),
             )
             .push(
-                Button::new(Text::new("Refresh"))
-                    .on_press(Message::Refresh)
+                Button::new(Text::new(if self.tree_view { "Flat View" } else { "Tree View" }))
+                    .on_press(Message::ToggleTreeView)
                     .padding(10),
-            );
-
-        let cpu_usage_chart = Canvas::new(CpuUsageChart::new(self.cpu_usage_history.clone()))
-            .width(Length::FillPortion(1))
-            .height(Length::Fixed(200.0));
-
-        let memory_usage_chart =
-            Canvas::new(MemoryUsageChart::new(self.memory_usage_history.clone()))
-                .width(Length::FillPortion(1))
-                .height(Length::Fixed(200.0));
-
-        let charts_row = Row::new()
-            .push(cpu_usage_chart)
-            .push(memory_usage_chart)
-            .spacing(20)
-            .padding(10)
-            .height(Length::Fixed(220.0));
-
-        let header_row = Row::new()
-            .spacing(20)
-            .padding(10)
-            .push(
-                Button::new(Text::new("PID"))
-                    .on_press(Message::SortBy(SortField::PID))
-                    .padding(5),
             )
-            .push(Text::new("User").width(Length::Fixed(100.0)))
             .push(
-                Button::new(Text::new("CPU %"))
-                    .on_press(Message::SortBy(SortField::CPU))
+                Button::new(Text::new(if self.overlay_charts { "Split Charts" } else { "Overlay Charts" }))
+                    .on_press(Message::ToggleChartLayout)
+                    .padding(10),
+            )
+            .push(
+                Tooltip::new(
+                    Button::new(Text::new(if self.paused.is_some() { "Resume" } else { "Pause" }))
+                        .on_press(Message::TogglePause)
+                        .padding(10),
+                    "Space: pause or resume; resuming shows what changed in between",
+                    tooltip::Position::Bottom,
+                )
+                .style(theme::Container::Box),
+            )
+            .push(
+                Tooltip::new(
+                    Button::new(Text::new("Refresh"))
+                        .on_press(Message::Refresh)
+                        .padding(10),
+                    "Click: quick refresh\nShift+click: deep refresh, measures CPU from a \
+                     fresh sample instead of since the last refresh",
+                    tooltip::Position::Bottom,
+                )
+                .style(theme::Container::Box),
+            );
+
+        let toolbar = Row::new()
+            .padding([0, 10])
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(
+                Button::new(Text::new(if self.show_disks { "Hide Disks" } else { "Show Disks" }))
+                    .on_press(Message::ToggleDisks)
+                    .padding(5),
+            )
+            .push(
+                Button::new(Text::new(if self.preview_kills { "Stop Preview" } else { "Preview Kills" }))
+                    .on_press(Message::TogglePreviewKills)
+                    .padding(5),
+            )
+            .push(
+                Tooltip::new(
+                    Button::new(Text::new(if self.kill_subtrees {
+                        "Kill Single Processes"
+                    } else {
+                        "Kill Subtrees"
+                    }))
+                    .on_press(Message::ToggleKillSubtrees)
+                    .padding(5),
+                    "Kill buttons also kill every process below the one clicked, \
+                     as shown in tree view",
+                    tooltip::Position::Bottom,
+                )
+                .style(theme::Container::Box),
+            )
+            .push(
+                Button::new(Text::new(if self.show_growth { "Hide Growth" } else { "Memory Growth" }))
+                    .on_press(Message::ToggleGrowth)
+                    .padding(5),
+            )
+            .push(
+                Button::new(Text::new("Clear History"))
+                    .on_press(Message::ClearHistory)
+                    .padding(5),
+            )
+            .push(
+                Button::new(Text::new(if self.show_session_cpu { "Hide Session CPU" } else { "Session CPU" }))
+                    .on_press(Message::ToggleSessionCpu)
+                    .padding(5),
+            )
+            .push(
+                Button::new(Text::new(if self.compare_mode { "Stop Comparing" } else { "Compare" }))
+                    .on_press(Message::ToggleCompareMode)
                     .padding(5),
             )
             .push(
-                Button::new(Text::new("Memory"))
-                    .on_press(Message::SortBy(SortField::Memory))
+                Button::new(Text::new(match self.filter_mode {
+                    FilterMode::Substring => "Search: Text",
+                    FilterMode::Glob => "Search: Wildcards",
+                    FilterMode::Fuzzy => "Search: Fuzzy",
+                }))
+                .on_press(Message::ToggleFilterMode)
+                .padding(5),
+            )
+            .push(
+                Button::new(Text::new(match self.term_match {
+                    TermMatch::Phrase => "Terms: Phrase",
+                    TermMatch::Any => "Terms: Any",
+                    TermMatch::All => "Terms: All",
+                }))
+                .on_press(Message::ToggleTermMatch)
+                .padding(5),
+            )
+            .push(
+                Tooltip::new(
+                    Button::new(Text::new(if self.show_ignored {
+                        "Hide Ignored"
+                    } else {
+                        "Show Hidden"
+                    }))
+                    .on_press(Message::ToggleShowIgnored)
                     .padding(5),
+                    "Right-click a process to hide it, or to unhide it while hidden ones are shown",
+                    tooltip::Position::Bottom,
+                )
+                .style(theme::Container::Box),
+            )
+            .push(
+                Button::new(Text::new(if self.stable_rows { "Stable Rows: On" } else { "Stable Rows: Off" }))
+                    .on_press(Message::ToggleStableRows)
+                    .padding(5),
+            )
+            .push(
+                Button::new(Text::new(if self.config.smooth_cpu {
+                    format!("CPU: Averaged ({})", self.config.cpu_average_window)
+                } else {
+                    "CPU: Instant".to_string()
+                }))
+                .on_press(Message::ToggleCpuSmoothing)
+                .padding(5),
+            )
+            .push(
+                Button::new(Text::new(if self.cpu_of_allowed_cores {
+                    "CPU: Of Allowed Cores"
+                } else {
+                    "CPU: Of One Core"
+                }))
+                .on_press(Message::ToggleCpuNormalization)
+                .padding(5),
             )
+            .push(
+                Button::new(Text::new(match self.config.row_density {
+                    RowDensity::Comfortable => "Compact Rows",
+                    RowDensity::Compact => "Comfortable Rows",
+                }))
+                .on_press(Message::ToggleRowDensity)
+                .padding(5),
+            )
+            .push(
+                Button::new(Text::new(if self.config.high_contrast {
+                    "Standard Contrast"
+                } else {
+                    "High Contrast"
+                }))
+                .on_press(Message::ToggleHighContrast)
+                .padding(5),
+            )
+            .push(if self.config.pause_automation {
+                // Paused by the config or a flag, which the GUI can't undo
+                Button::new(Text::new("Automation Paused")).padding(5)
+            } else {
+                Button::new(Text::new(if self.automation_paused {
+                    "Resume Automation"
+                } else {
+                    "Pause Automation"
+                }))
+                .on_press(Message::TogglePauseAutomation)
+                .padding(5)
+            })
+            .push(
+                Button::new(Text::new(match self.config.command_display {
+                    CommandDisplay::Name => "Show Full Paths",
+                    CommandDisplay::Path => "Show Names",
+                }))
+                .on_press(Message::ToggleCommandDisplay)
+                .padding(5),
+            );
+
+        // A clicked process's history replaces the system's; its samples don't line up
+        // with the system chart events, so those are left out
+        let (cpu_history, cpu_ranges, memory_history, chart_events, charted) =
+            match &self.selected_history {
+                Some(series) => (
+                    &series.cpu_usage[..],
+                    &[][..],
+                    &series.memory_usage[..],
+                    &[][..],
+                    self.processes.iter().find(|p| p.pid == series.pid),
+                ),
+                None => (
+                    &self.cpu_usage_history[..],
+                    &self.cpu_range_history[..],
+                    &self.memory_usage_history[..],
+                    &self.chart_events[..],
+                    None,
+                ),
+            };
+        let (cpu_title, memory_title) = match charted {
+            Some(process) => {
+                let name = format!(
+                    "{} ({})",
+                    process.display_command(self.config.command_display),
+                    process.pid
+                );
+                (format!("{} CPU (%)", name), format!("{} Memory (%)", name))
+            }
+            None => ("CPU Usage (%)".to_string(), "Memory Usage (%)".to_string()),
+        };
+
+        let mut charts_row = Row::new().spacing(20).padding(10).height(Length::Fixed(220.0));
+        if self.overlay_charts {
+            charts_row = charts_row.push(
+                Canvas::new(OverlayChart::new(
+                    cpu_history,
+                    cpu_ranges,
+                    memory_history,
+                    chart_events,
+                    self.chart_render_budget(),
+                    self.style(),
+                ))
+                .width(Length::Fill)
+                .height(Length::Fixed(200.0)),
+            );
+        } else {
+            let cpu_usage_chart = Canvas::new(CpuUsageChart::new(
+                &cpu_title,
+                cpu_history,
+                cpu_ranges,
+                chart_events,
+                self.config.spike_threshold,
+                self.chart_render_budget(),
+                self.style(),
+            ))
+            .width(Length::FillPortion(1))
+            .height(Length::Fixed(200.0));
+
+            let memory_usage_chart = Canvas::new(MemoryUsageChart::new(
+                &memory_title,
+                memory_history,
+                chart_events,
+                self.config.spike_threshold,
+                self.chart_render_budget(),
+                self.style(),
+            ))
+            .width(Length::FillPortion(1))
+            .height(Length::Fixed(200.0));
+
+            charts_row = charts_row.push(cpu_usage_chart).push(memory_usage_chart);
+        }
+        // System-wide even with a process charted; the kernel doesn't count its traffic
+        let style = self.style();
+        let (kilo, kilo_label) = byte_units().kilo();
+        // In the units the summary bar shows the same rates in
+        let rates_chart = |name: &str, history: &[(u64, u64)]| {
+            let kilo_per_sec = |direction: fn(&(u64, u64)) -> u64| {
+                history.iter().map(|rates| direction(rates) as f32 / kilo as f32).collect()
+            };
+            Canvas::new(ComparisonChart::new(
+                &format!("{} {}/s", name, kilo_label),
+                vec![
+                    (kilo_per_sec(|&(first, _)| first), style.cpu_line),
+                    (kilo_per_sec(|&(_, second)| second), style.memory_line),
+                ],
+                1.0,
+                self.chart_render_budget(),
+                style,
+            ))
+            .width(Length::FillPortion(1))
+            .height(Length::Fixed(200.0))
+        };
+        charts_row = charts_row
+            .push(rates_chart("Network", &self.network_history))
+            .push(rates_chart("Disk", &self.disk_io_history));
+
+        let mut header_row = Row::new()
+            .spacing(20)
+            .padding(10)
+            .push(sort_button("PID", SortField::PID, 60.0));
+        if self.shows(OptionalColumn::User) {
+            header_row = header_row.push(Text::new("User").width(Length::Fixed(100.0)));
+        }
+        header_row = header_row.push(sort_button("CPU %", SortField::CPU, 80.0));
+        if self.shows(OptionalColumn::ObservedCpu) {
+            header_row = header_row.push(sort_button("Avg CPU", SortField::ObservedCpu, 80.0));
+        }
+        if self.shows(OptionalColumn::Efficiency) {
+            header_row = header_row.push(Text::new("Life CPU").width(Length::Fixed(80.0)));
+        }
+        header_row = header_row
+            .push(sort_button("Memory", SortField::Memory, 100.0))
+            .push(sort_button("MEM %", SortField::MemoryPercent, 70.0));
+        if self.shows(OptionalColumn::PeakMemory) {
+            header_row = header_row.push(sort_button("Peak", SortField::PeakMemory, 100.0));
+        }
+        if self.shows(OptionalColumn::VirtualMemory) {
+            header_row = header_row.push(Text::new("Virtual").width(Length::Fixed(100.0)));
+        }
+        if self.shows(OptionalColumn::Swap) {
+            header_row = header_row.push(Text::new("Swap").width(Length::Fixed(90.0)));
+        }
+        if self.shows(OptionalColumn::Status) {
+            header_row = header_row.push(Text::new("Status").width(Length::Fixed(90.0)));
+        }
+        if self.shows(OptionalColumn::Threads) {
+            header_row = header_row.push(sort_button("Threads", SortField::Threads, 60.0));
+        }
+        if self.shows(OptionalColumn::Nice) {
+            header_row = header_row.push(sort_button("Nice", SortField::Priority, 50.0));
+        }
+        if self.shows(OptionalColumn::DiskIo) {
+            header_row = header_row
+                .push(sort_button("Read", SortField::DiskRead, 90.0))
+                .push(sort_button("Write", SortField::DiskWrite, 90.0));
+        }
+        if self.shows(OptionalColumn::Executable) {
+            header_row =
+                header_row.push(Text::new("Executable").width(Length::Fixed(EXE_COLUMN_WIDTH)));
+        }
+        header_row = header_row
             .push(
                 Button::new(Text::new("Command"))
                     .on_press(Message::SortBy(SortField::Command))
-                    .padding(5),
+                    .padding(5)
+                    .width(Length::Fill),
             )
-            .push(Text::new("Actions").width(Length::Fixed(80.0)));
+            .push(Text::new("Actions").width(Length::Fixed(ACTIONS_COLUMN_WIDTH)));
 
-        let process_list = self.filtered_processes.iter().fold(
-            Column::new().spacing(10).padding(10),
-            |column, process| {
-                column.push(
-                    Container::new(
-                        Row::new()
-                            .spacing(20)
-                            .align_items(Alignment::Center)
-                            .push(
-                                Text::new(process.pid.to_string()).width(Length::Fixed(60.0)),
-                            )
-                            .push(Text::new(&process.user).width(Length::Fixed(100.0)))
-                            .push(
-                                Text::new(format!("{:.2}%", process.cpu_usage))
-                                    .width(Length::Fixed(80.0)),
-                            )
-                            .push(
-                                Text::new(format!("{} KB", process.memory_usage))
-                                    .width(Length::Fixed(100.0)),
-                            )
-                            .push(Text::new(&process.command).width(Length::Fill))
-                            .push(
-                                Button::new(Text::new("Kill"))
-                                    .on_press(Message::KillProcess(process.pid))
-                                    .padding(5),
-                            ),
+        let command_chars = self.command_column_chars();
+        let search = (!self.search_query.is_empty()).then(|| {
+            ProcessFilter::with_terms(&self.search_query, self.filter_mode, self.term_match)
+        });
+        let style = self.style();
+        let text_color = self.theme().palette().text;
+        let row_spacing = self.config.row_density.row_padding() as f32;
+        let row_height = self.row_pitch() - row_spacing;
+        let visible_rows = self.visible_row_range();
+        // Rows outside the window are replaced by spacers of the same total height, so the
+        // scrollbar still reflects the whole list
+        let mut process_list =
+            Column::new().spacing(row_spacing).padding(10).width(Length::Fill);
+        if visible_rows.start > 0 {
+            process_list = process_list.push(Space::with_height(Length::Fixed(
+                visible_rows.start as f32 * self.row_pitch() - row_spacing,
+            )));
+        }
+        let hidden_below = self.filtered_processes.len() - visible_rows.end;
+        let process_list = self.filtered_processes[visible_rows.clone()]
+            .iter()
+            .zip(visible_rows)
+            .fold(process_list, |column, (process, index)| {
+                // Without an on_press the button is drawn disabled
+                let button_padding = self.config.row_density.row_padding();
+                let mut kill_button = Button::new(Text::new("Kill")).padding(button_padding);
+                let mut actions = Row::new().spacing(5);
+                if self.can_signal(process) {
+                    let pid = process.pid;
+                    kill_button = kill_button.on_press(Message::KillProcess(pid, Signal::SIGTERM));
+                    // Sends the chosen signal right away, like the Kill button
+                    let signal_picker = PickList::new(&KILL_SIGNALS[..], None, move |signal| {
+                        Message::KillProcess(pid, signal)
+                    })
+                    .placeholder("Signal")
+                    .padding(button_padding);
+                    actions = actions.push(kill_button).push(signal_picker);
+                } else {
+                    actions = actions.push(kill_button);
+                }
+                if self.compare_mode {
+                    let label = match self.compare_slot(process.pid) {
+                        Some(0) => "A",
+                        Some(_) => "B",
+                        None => "Compare",
+                    };
+                    actions = actions.push(
+                        Button::new(Text::new(label))
+                            .on_press(Message::CompareProcess(process.pid))
+                            .padding(button_padding),
+                    );
+                }
+
+                let text_size = self.row_text_size();
+                let cell = |content: String, width: Length| {
+                    Text::new(content).size(text_size).width(width)
+                };
+
+                // New processes stand out, so a fork storm shows as a burst of bright PIDs
+                let mut pid = cell(process.pid.to_string(), Length::Fixed(60.0));
+                if self.config.age_gradient_secs > 0 {
+                    let age = process.run_time_secs as f32 / self.config.age_gradient_secs as f32;
+                    pid = pid.style(mix(style.fresh, text_color, age.min(1.0)));
+                }
+                let mut cells = Row::new()
+                    .spacing(20)
+                    .align_items(Alignment::Center)
+                    .push(pid);
+                if self.shows(OptionalColumn::User) {
+                    cells = cells.push(cell(process.user.clone(), Length::Fixed(100.0)));
+                }
+                let cpu_usage = match process.cpu_usage_of_allowed() {
+                    Some(usage) if self.cpu_of_allowed_cores => usage,
+                    _ => process.cpu_usage,
+                };
+                cells = cells.push(cell(format!("{:.2}%", cpu_usage), Length::Fixed(80.0)));
+                if self.shows(OptionalColumn::ObservedCpu) {
+                    cells = cells.push(cell(
+                        format!("{:.2}%", process.observed_avg_cpu),
+                        Length::Fixed(80.0),
+                    ));
+                }
+                if self.shows(OptionalColumn::Efficiency) {
+                    cells = cells.push(cell(
+                        format!("{:.1}%", process.efficiency * 100.0),
+                        Length::Fixed(80.0),
+                    ));
+                }
+                cells = cells
+                    .push(cell(format_bytes(process.memory_usage), Length::Fixed(100.0)))
+                    .push(cell(format!("{:.1}%", process.memory_percent), Length::Fixed(70.0)));
+                if self.shows(OptionalColumn::PeakMemory) {
+                    let mut peak = cell(format_bytes(process.peak_memory), Length::Fixed(100.0));
+                    // Ballooned and shrank back, which the memory column no longer shows
+                    let shrunk = process.peak_memory.saturating_sub(process.memory_usage);
+                    if shrunk >= SIGNIFICANT_SHRINK {
+                        peak = peak.style(style.warning);
+                    }
+                    cells = cells.push(peak);
+                }
+                if self.shows(OptionalColumn::VirtualMemory) {
+                    cells = cells
+                        .push(cell(format_bytes(process.virtual_memory), Length::Fixed(100.0)));
+                }
+                if self.shows(OptionalColumn::Swap) {
+                    let mut swap = cell(format_bytes(process.swap_used), Length::Fixed(90.0));
+                    // Heavily swapped processes stall on page faults
+                    if process.swap_used >= SIGNIFICANT_SWAP {
+                        swap = swap.style(style.warning);
+                    }
+                    cells = cells.push(swap);
+                }
+                if self.shows(OptionalColumn::Status) {
+                    cells = cells.push(cell(process.status.clone(), Length::Fixed(90.0)));
+                }
+                if self.shows(OptionalColumn::Threads) {
+                    cells = cells.push(cell(process.threads.to_string(), Length::Fixed(60.0)));
+                }
+                if self.shows(OptionalColumn::Nice) {
+                    let mut nice = cell(process.nice.to_string(), Length::Fixed(50.0));
+                    // Negative nice means higher than default priority
+                    if process.nice < 0 {
+                        nice = nice.style(style.info);
+                    }
+                    cells = cells.push(nice);
+                }
+                if self.shows(OptionalColumn::DiskIo) {
+                    cells = cells
+                        .push(cell(format_bytes(process.disk_read), Length::Fixed(90.0)))
+                        .push(cell(format_bytes(process.disk_write), Length::Fixed(90.0)));
+                }
+                if self.shows(OptionalColumn::Executable) {
+                    let chars = (EXE_COLUMN_WIDTH / (text_size * 0.6)) as usize;
+                    cells = cells.push(cell(
+                        truncate(&process.exe, chars),
+                        Length::Fixed(EXE_COLUMN_WIDTH),
+                    ));
+                }
+                let command = process.display_command(self.config.command_display);
+                let full_command =
+                    if process.cmdline.is_empty() { command } else { &process.cmdline };
+                let matched = search.as_ref().map_or_else(Vec::new, |s| s.match_ranges(command));
+                let depth = self.tree_depths.get(index).copied().unwrap_or(0);
+                let indent = match depth {
+                    0 => String::new(),
+                    depth => format!("{}└ ", "  ".repeat(depth - 1)),
+                };
+                let command = format!("{}{}", indent, command);
+                let shown = truncate(&command, command_chars);
+                // Shift past the indent, and drop what truncation cut off
+                let kept = if shown == command {
+                    shown.len()
+                } else {
+                    shown.len() - '…'.len_utf8()
+                };
+                let matched: Vec<Range<usize>> = matched
+                    .into_iter()
+                    .map(|range| range.start + indent.len()..(range.end + indent.len()).min(kept))
+                    .filter(|range| !range.is_empty())
+                    .collect();
+                let mut command_cell = Row::new()
+                    .spacing(10)
+                    .width(Length::Fill)
+                    .push(highlighted_text(&shown, &matched, text_size, style.search_match));
+                let descendants = self.tree_descendants.get(index).copied().unwrap_or(0);
+                if descendants > 0 {
+                    let label = if self.collapsed.contains(&process.pid) {
+                        format!("▸ {} hidden", descendants)
+                    } else {
+                        "▾".to_string()
+                    };
+                    command_cell = command_cell.push(
+                        Button::new(Text::new(label).size(text_size))
+                            .on_press(Message::ToggleCollapsed(process.pid))
+                            .padding([0, 5])
+                            .style(theme::Button::Text),
+                    );
+                }
+                if process.orphaned_from.is_some() {
+                    command_cell = command_cell.push(
+                        Text::new("orphaned")
+                            .size(text_size)
+                            .style(style.warning),
+                    );
+                }
+                // A running binary whose file is gone can be malware hiding its tracks,
+                // or a service still on the pre-upgrade version
+                if process.exe_deleted {
+                    command_cell = command_cell.push(
+                        Text::new("exe deleted")
+                            .size(text_size)
+                            .style(style.danger),
+                    );
+                }
+                // Averaged over a minute, so it stays put while instantaneous usage flickers
+                if let Some((_, cpu)) = self.top_hog.filter(|&(pid, _)| pid == process.pid) {
+                    command_cell = command_cell.push(
+                        Text::new(format!("🔥 top hog ({:.0}% avg)", cpu))
+                            .size(text_size)
+                            .style(style.warning),
+                    );
+                }
+                cells = cells
+                    .push(
+                        Tooltip::new(
+                            command_cell,
+                            full_command,
+                            tooltip::Position::Bottom,
+                        )
+                        .style(theme::Container::Box),
                     )
-                    .padding(5),
+                    .push(actions);
+
+                let row = Container::new(cells)
+                    .padding(self.config.row_density.row_padding())
+                    .height(Length::Fixed(row_height));
+
+                let row = if self.selected_index == Some(index) {
+                    row.style(selected_row_appearance)
+                } else if self.config.high_contrast {
+                    // Outlines separate rows more clearly than faint stripes
+                    row.style(outlined_row_appearance)
+                } else if index % 2 == 1 {
+                    row.style(striped_row_appearance)
+                } else {
+                    row
+                };
+                column.push(
+                    mouse_area(row)
+                        .on_press(Message::SelectProcess(process.pid))
+                        .on_right_press(Message::ToggleIgnored(process.pid)),
                 )
-            },
-        );
+            });
+        let process_list = if hidden_below > 0 {
+            process_list.push(Space::with_height(Length::Fixed(
+                hidden_below as f32 * self.row_pitch() - row_spacing,
+            )))
+        } else {
+            process_list
+        };
 
-        let scrollable_content = Scrollable::new(process_list);
+        let scrollable_content = Scrollable::new(process_list)
+            .id(process_list_id())
+            .on_scroll(Message::ListScrolled);
 
         let mut content = Column::new()
-            .push(header);
+            .push(header)
+            .push(toolbar)
+            .push(self.range_filters())
+            .push(self.preset_bar())
+            .push(self.summary_bar());
+
+        if self.show_privilege_warning {
+            content = content.push(privilege_warning());
+        }
+
+        if let Some(status) = &self.status_message {
+            content = content.push(
+                Row::new()
+                    .padding(5)
+                    .spacing(20)
+                    .align_items(Alignment::Center)
+                    .push(Text::new(status))
+                    .push(
+                        Button::new(Text::new("Dismiss"))
+                            .on_press(Message::DismissStatus)
+                            .padding(5),
+                    ),
+            );
+        }
+
+        if !self.exited_pids.is_empty() {
+            let pids: Vec<String> = self.exited_pids.iter().map(|pid| pid.to_string()).collect();
+            content = content.push(
+                Container::new(
+                    Text::new(format!("Watched process exited: {}", pids.join(", ")))
+                        .style(self.style().warning),
+                )
+                .padding(5),
+            );
+        }
+
+        if !self.flapping.is_empty() {
+            let commands: Vec<String> = self
+                .flapping
+                .iter()
+                .map(|(command, restarts)| format!("{} ({} restarts)", command, restarts))
+                .collect();
+            content = content.push(
+                Container::new(
+                    Text::new(format!("Crash loop suspected: {}", commands.join(", ")))
+                        .style(self.style().danger),
+                )
+                .padding(5),
+            );
+        }
+
+        if let Some(alert) = self.alert_message() {
+            content = content.push(
+                Container::new(
+                    Text::new(alert)
+                        .size(18)
+                        .style(self.style().danger),
+                )
+                .padding(5),
+            );
+        }
+
+        if let Some((pid, signal)) = self.pending_kill {
+            content = content.push(self.kill_confirmation(pid, signal));
+        }
+
+        if let Some(pause_diff) = &self.pause_diff {
+            content = content.push(self.pause_diff_panel(pause_diff));
+        }
 
         if self.show_graphs {
+            if let Some(process) = charted {
+                content = content.push(
+                    Row::new()
+                        .spacing(10)
+                        .padding([0, 10])
+                        .align_items(Alignment::Center)
+                        .push(Text::new(format!(
+                            "Charting {} (PID {}); click its row again to go back",
+                            process.display_command(self.config.command_display),
+                            process.pid
+                        )))
+                        .push(
+                            Button::new(Text::new("Show System"))
+                                .on_press(Message::SelectProcess(process.pid))
+                                .padding(5),
+                        ),
+                );
+            }
             content = content.push(charts_row);
         }
 
+        if self.compare_mode {
+            content = content.push(self.comparison_panel());
+        }
+
+        if let Some(process) = self
+            .details_pid
+            .and_then(|pid| self.processes.iter().find(|p| p.pid == pid))
+        {
+            // Read with the next refresh, so missing for a moment after opening
+            let extras = self.details_extras.as_ref().filter(|extras| extras.pid == process.pid);
+            content = content.push(details_panel(process, extras, self.style()));
+        }
+
+        if self.show_disks {
+            content = content.push(self.disk_panel());
+        }
+
+        if self.show_growth {
+            content = content.push(self.growth_panel());
+        }
+
+        if self.show_session_cpu {
+            content = content.push(self.session_cpu_panel());
+        }
+
         content = content
             .push(header_row)
             .push(scrollable_content);
@@ -289,81 +2213,983 @@ impl Application for TaskManager {
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        every(std::time::Duration::from_secs(1)).map(|_| Message::Refresh)
+        let mut refresh_interval_ms = if self.idle {
+            self.config.idle_refresh_interval_ms
+        } else {
+            self.config.refresh_interval_ms
+        };
+        if self.overhead_reduced {
+            refresh_interval_ms *= 2;
+        }
+        let mut subscriptions = Vec::new();
+        if self.paused.is_none() {
+            subscriptions
+                .push(every(Duration::from_millis(refresh_interval_ms)).map(|_| Message::Tick));
+        }
+        if self.show_disks {
+            subscriptions.push(every(DISK_REFRESH_INTERVAL).map(|_| Message::RefreshDisks));
+        }
+        subscriptions.push(subscription::events_with(handle_event));
+        if self.config.idle_timeout_secs > 0 {
+            subscriptions.push(subscription::events_with(activity_event));
+        }
+        Subscription::batch(subscriptions)
     }
 }
 
 impl TaskManager {
-    fn apply_filter_and_sort(&mut self) {
-        if self.search_query.is_empty() {
-            self.filtered_processes = self.processes.clone();
+    /// Turns the reduced update rate on when our own CPU usage goes over
+    /// `self_cpu_limit`, and off once it's below a third of it. Halving the rate
+    /// roughly halves the cost, so switching back at half the limit would flip-flop.
+    fn check_overhead(&mut self) {
+        let limit = self.config.self_cpu_limit;
+        let Some((cpu, _)) = self.own_usage.filter(|_| limit > 0.0) else {
+            self.overhead_reduced = false;
+            return;
+        };
+        if !self.overhead_reduced && cpu > limit {
+            info!(cpu, limit, "Reducing update rate to limit overhead");
+            self.overhead_reduced = true;
+        } else if self.overhead_reduced && cpu < limit / 3.0 {
+            info!(cpu, limit, "Restoring normal update rate");
+            self.overhead_reduced = false;
+        }
+    }
+
+    /// Most points a chart draws, halved while the update rate is reduced.
+    fn chart_render_budget(&self) -> usize {
+        if self.overhead_reduced {
+            (self.config.chart_render_budget / 2).max(2)
         } else {
-            let query = self.search_query.to_lowercase();
-            self.filtered_processes = self
-                .processes
-                .iter()
-                .filter(|p| {
-                    p.pid.to_string().contains(&query)
-                        || p.command.to_lowercase().contains(&query)
-                })
-                .cloned()
-                .collect();
+            self.config.chart_render_budget
         }
+    }
 
-        match self.sort_field {
-            SortField::PID => {
-                if self.sort_order == SortOrder::Ascending {
-                    self.filtered_processes.sort_by_key(|p| p.pid);
-                } else {
-                    self.filtered_processes
-                        .sort_by_key(|p| std::cmp::Reverse(p.pid));
-                }
+    /// Whether a kill is expected to be permitted. Processes whose owner is unknown
+    /// are given the benefit of the doubt; the kill itself reports any EPERM.
+    fn can_signal(&self, process: &ProcessInfo) -> bool {
+        let owned = self.euid == 0 || process.uid.is_none_or(|uid| uid == self.euid);
+        owned && self.config.kill_policy.permits(process.pid, &process.names())
+    }
+
+    fn style(&self) -> VisualStyle {
+        if self.config.high_contrast {
+            VisualStyle::HIGH_CONTRAST
+        } else {
+            VisualStyle::STANDARD
+        }
+    }
+
+    fn row_text_size(&self) -> f32 {
+        self.config.row_density.text_size() + self.style().extra_text_size
+    }
+
+    /// Height of one process row plus the gap below it. Rows have a fixed height so the
+    /// list can tell which rows are scrolled into view without laying them all out.
+    fn row_pitch(&self) -> f32 {
+        let padding = self.config.row_density.row_padding() as f32;
+        // The Kill button's text keeps the default size, and it's padded like the row
+        let line_height = self.row_text_size().max(16.0) * 1.4;
+        (line_height + 4.0 * padding).ceil() + padding
+    }
+
+    /// Indices of the rows that get widgets: the ones in view plus a window height
+    /// above and below.
+    ///
+    /// The list reports its offset only when the user scrolls, so the stored offset can
+    /// be off by up to a viewport after a programmatic scroll or when the list shrinks;
+    /// the margin keeps the rows actually shown inside the range.
+    fn visible_row_range(&self) -> std::ops::Range<usize> {
+        let len = self.filtered_processes.len();
+        let window_rows = (self.window_height / self.row_pitch()).ceil() as usize + 1;
+        let top = (self.list_offset.max(0.0) / self.row_pitch()) as usize;
+        let start = top.saturating_sub(window_rows).min(len.saturating_sub(3 * window_rows));
+        start..(start + 3 * window_rows).min(len)
+    }
+
+    /// Roughly how many characters of the command fit next to the visible columns.
+    fn command_column_chars(&self) -> usize {
+        const ROW_SPACING: f32 = 20.0;
+        // PID, CPU, memory, MEM% and the actions, plus window and row padding
+        let mut used =
+            60.0 + 80.0 + 100.0 + 70.0 + ACTIONS_COLUMN_WIDTH + 5.0 * ROW_SPACING + 60.0;
+        if self.compare_mode {
+            used += 80.0;
+        }
+        let optional = [
+            (OptionalColumn::Efficiency, 80.0),
+            (OptionalColumn::User, 100.0),
+            (OptionalColumn::PeakMemory, 100.0),
+            (OptionalColumn::VirtualMemory, 100.0),
+            (OptionalColumn::Swap, 90.0),
+            (OptionalColumn::Status, 90.0),
+            (OptionalColumn::ObservedCpu, 80.0),
+            (OptionalColumn::Threads, 60.0),
+            (OptionalColumn::Nice, 50.0),
+            (OptionalColumn::DiskIo, 180.0 + ROW_SPACING),
+            (OptionalColumn::Executable, EXE_COLUMN_WIDTH),
+        ];
+        for (column, width) in optional {
+            if self.shows(column) {
+                used += width + ROW_SPACING;
             }
-            SortField::CPU => {
-                if self.sort_order == SortOrder::Ascending {
-                    self.filtered_processes.sort_by(|a, b| {
-                        a.cpu_usage.partial_cmp(&b.cpu_usage).unwrap()
-                    });
-                } else {
-                    self.filtered_processes.sort_by(|a, b| {
-                        b.cpu_usage.partial_cmp(&a.cpu_usage).unwrap()
-                    });
-                }
+        }
+        // Average glyph width is a bit over half the font size
+        let glyph_width = self.row_text_size() * 0.6;
+        (((self.window_width - used) / glyph_width) as usize).max(10)
+    }
+
+    fn shows(&self, column: OptionalColumn) -> bool {
+        self.window_width >= column.min_window_width()
+    }
+
+    fn selected_process(&self) -> Option<&ProcessInfo> {
+        self.selected_index
+            .and_then(|index| self.filtered_processes.get(index))
+    }
+
+    /// Moves the selection to `index` and scrolls the list so it stays visible.
+    fn select(&mut self, index: usize) -> Command<Message> {
+        self.selected_index = Some(index);
+        let last = self.filtered_processes.len().saturating_sub(1).max(1);
+        // Snapping doesn't report the new offset, so estimate it for the virtual list
+        let content_height = self.filtered_processes.len() as f32 * self.row_pitch();
+        self.list_offset =
+            index as f32 / last as f32 * (content_height - self.window_height).max(0.0);
+        scrollable::snap_to(
+            process_list_id(),
+            RelativeOffset {
+                x: 0.0,
+                y: index as f32 / last as f32,
+            },
+        )
+    }
+
+    /// Slot (0 for A, 1 for B) holding `pid` in the comparison, if any.
+    fn compare_slot(&self, pid: i32) -> Option<usize> {
+        self.compare_pids.iter().position(|&slot| slot == Some(pid))
+    }
+
+    fn comparison_panel(&self) -> Element<'_, Message> {
+        let budget = self.chart_render_budget();
+        let mut panel = Column::new().padding(10).spacing(10).push(
+            Row::new()
+                .spacing(20)
+                .align_items(Alignment::Center)
+                .push(Text::new("Process Comparison").size(20))
+                .push(
+                    Button::new(Text::new("Clear"))
+                        .on_press(Message::ClearComparison)
+                        .padding(5),
+                ),
+        );
+
+        // Pair each slot with its process (if still running) and history
+        let compared: Vec<(usize, i32, Option<&ProcessInfo>, Option<&ProcessHistory>)> = self
+            .compare_pids
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, pid)| pid.map(|pid| (slot, pid)))
+            .map(|(slot, pid)| {
+                let process = self.processes.iter().find(|p| p.pid == pid);
+                let history = self
+                    .compared_histories
+                    .iter()
+                    .find(|(history_pid, _)| *history_pid == pid)
+                    .map(|(_, history)| history);
+                (slot, pid, process, history)
+            })
+            .collect();
+
+        if compared.is_empty() {
+            return panel
+                .push(Text::new("Pick up to two processes with their Compare button."))
+                .into();
+        }
+
+        let cpu_series = compared
+            .iter()
+            .map(|(slot, _, _, history)| {
+                let values = history
+                    .map(|h| h.cpu_usage.iter().copied().collect())
+                    .unwrap_or_default();
+                (values, COMPARE_COLORS[*slot])
+            })
+            .collect();
+        let memory_series = compared
+            .iter()
+            .map(|(slot, _, _, history)| {
+                let values = history
+                    .map(|h| {
+                        h.memory_usage
+                            .iter()
+                            .map(|&bytes| bytes as f32 / (1024.0 * 1024.0))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (values, COMPARE_COLORS[*slot])
+            })
+            .collect();
+
+        let charts = Row::new()
+            .spacing(20)
+            .height(Length::Fixed(200.0))
+            .push(
+                Canvas::new(ComparisonChart::new(
+                    "CPU Usage (%)",
+                    cpu_series,
+                    100.0,
+                    budget,
+                    self.style(),
+                ))
+                .width(Length::FillPortion(1))
+                    .height(Length::Fill),
+            )
+            .push(
+                Canvas::new(ComparisonChart::new(
+                    "Memory (MiB)",
+                    memory_series,
+                    1.0,
+                    budget,
+                    self.style(),
+                ))
+                .width(Length::FillPortion(1))
+                    .height(Length::Fill),
+            );
+
+        let mut table = Column::new().spacing(5).push(
+            Row::new()
+                .spacing(20)
+                .push(Text::new("").width(Length::Fixed(40.0)))
+                .push(Text::new("PID").width(Length::Fixed(80.0)))
+                .push(Text::new("Command").width(Length::Fixed(200.0)))
+                .push(Text::new("CPU %").width(Length::Fixed(100.0)))
+                .push(Text::new("Memory").width(Length::Fixed(120.0))),
+        );
+        for (slot, pid, process, _) in &compared {
+            let (command, cpu, memory) = match process {
+                Some(p) => (
+                    p.display_command(self.config.command_display).to_string(),
+                    format!("{:.2}%", p.cpu_usage),
+                    format_bytes(p.memory_usage),
+                ),
+                None => ("(exited)".to_string(), "-".to_string(), "-".to_string()),
+            };
+            table = table.push(
+                Row::new()
+                    .spacing(20)
+                    .push(
+                        Text::new(if *slot == 0 { "A" } else { "B" })
+                            .style(COMPARE_COLORS[*slot])
+                            .width(Length::Fixed(40.0)),
+                    )
+                    .push(Text::new(pid.to_string()).width(Length::Fixed(80.0)))
+                    .push(Text::new(command).width(Length::Fixed(200.0)))
+                    .push(Text::new(cpu).width(Length::Fixed(100.0)))
+                    .push(Text::new(memory).width(Length::Fixed(120.0))),
+            );
+        }
+        if let [(_, _, Some(a), _), (_, _, Some(b), _)] = compared.as_slice() {
+            let memory_delta = a.memory_usage as i64 - b.memory_usage as i64;
+            let sign = if memory_delta < 0 { "-" } else { "+" };
+            table = table.push(
+                Row::new()
+                    .spacing(20)
+                    .push(Text::new("A-B").width(Length::Fixed(40.0)))
+                    .push(Text::new("").width(Length::Fixed(80.0)))
+                    .push(Text::new("").width(Length::Fixed(200.0)))
+                    .push(
+                        Text::new(format!("{:+.2}%", a.cpu_usage - b.cpu_usage))
+                            .width(Length::Fixed(100.0)),
+                    )
+                    .push(
+                        Text::new(format!("{}{}", sign, format_bytes(memory_delta.unsigned_abs())))
+                            .width(Length::Fixed(120.0)),
+                    ),
+            );
+        }
+
+        panel = panel.push(charts).push(table);
+        panel.into()
+    }
+
+    /// Upper end of the CPU sliders: the busiest process right now, at least 1%.
+    fn cpu_slider_top(&self) -> f32 {
+        self.processes.iter().map(|p| p.cpu_usage).fold(1.0, f32::max).ceil()
+    }
+
+    /// Upper end of the memory sliders in MB: the largest process right now.
+    fn memory_slider_top(&self) -> f32 {
+        self.processes
+            .iter()
+            .map(|p| bytes_to_mb(p.memory_usage))
+            .fold(1.0, f32::max)
+            .ceil()
+    }
+
+    fn range_filters(&self) -> Element<'_, Message> {
+        let cpu_top = self.cpu_slider_top();
+        let memory_top = self.memory_slider_top();
+
+        let range_sliders = |range: UsageRange,
+                             top: f32,
+                             step: f32,
+                             on_change: fn(RangeEnd, f32) -> Message| {
+            let slider = |end: RangeEnd, value: f32| {
+                Slider::new(0.0..=top, value.min(top), move |value| on_change(end, value))
+                    .step(step)
+                    .width(Length::Fixed(120.0))
+            };
+            Row::new()
+                .spacing(5)
+                .push(slider(RangeEnd::Min, range.min))
+                .push(slider(RangeEnd::Max, range.max))
+        };
+        let describe = |range: UsageRange, unit: &str| {
+            if range.max.is_finite() {
+                format!("{:.0}–{:.0} {}", range.min, range.max, unit)
+            } else {
+                format!("≥ {:.0} {}", range.min, unit)
             }
-            SortField::Memory => {
-                if self.sort_order == SortOrder::Ascending {
-                    self.filtered_processes
-                        .sort_by(|a, b| a.memory_usage.cmp(&b.memory_usage));
-                } else {
-                    self.filtered_processes
-                        .sort_by(|a, b| b.memory_usage.cmp(&a.memory_usage));
-                }
+        };
+
+        Row::new()
+            .padding([5, 10])
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(Text::new("CPU"))
+            .push(range_sliders(self.cpu_range, cpu_top, 0.5, Message::CpuRangeChanged))
+            .push(Text::new(describe(self.cpu_range, "%")).width(Length::Fixed(100.0)))
+            .push(Text::new("Memory"))
+            .push(range_sliders(self.memory_range, memory_top, 1.0, Message::MemoryRangeChanged))
+            .push(Text::new(describe(self.memory_range, "MiB")).width(Length::Fixed(140.0)))
+            .into()
+    }
+
+    fn preset_bar(&self) -> Element<'_, Message> {
+        let names: Vec<String> = self.config.presets.keys().cloned().collect();
+        let mut delete = Button::new(Text::new("Delete Preset")).padding(5);
+        if self.selected_preset.is_some() {
+            delete = delete.on_press(Message::DeletePreset);
+        }
+        Row::new()
+            .padding([5, 10])
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(Text::new("Preset"))
+            .push(
+                PickList::new(names, self.selected_preset.clone(), Message::PresetSelected)
+                    .placeholder("Choose a preset")
+                    .width(Length::Fixed(200.0)),
+            )
+            .push(
+                TextInput::new("Preset name", &self.preset_name)
+                    .on_input(Message::PresetNameChanged)
+                    .on_submit(Message::SavePreset)
+                    .padding(5)
+                    .width(Length::Fixed(180.0)),
+            )
+            .push(Button::new(Text::new("Save Preset")).on_press(Message::SavePreset).padding(5))
+            .push(delete)
+            .into()
+    }
+
+    fn summary_bar(&self) -> Element<'_, Message> {
+        let mut summary = Row::new()
+            .padding(5)
+            .spacing(30)
+            .align_items(Alignment::Center);
+
+        if let Some(cpu) = self.cpu_usage_history.last() {
+            summary = summary.push(Text::new(format!("CPU now: {:.1}%", cpu)));
+        }
+        // The since-boot average puts a momentary spike into perspective
+        if let Some(since_boot) = self.cpu_since_boot {
+            summary = summary
+                .push(Text::new(format!("CPU since boot: {:.1}%", since_boot.usage_percent)))
+                .push(Text::new(format!("Idle since boot: {:.1}%", since_boot.idle_percent)));
+        }
+        if let Some(memory) = self.memory_usage_history.last() {
+            summary = summary.push(Text::new(format!("Memory: {:.1}%", memory)));
+        }
+        // Tails over the recorded history show sustained load that the current value hides
+        if !self.cpu_usage_history.is_empty() {
+            let percentiles = [
+                ("CPU", self.cpu_percentiles),
+                ("Memory", self.memory_percentiles),
+            ];
+            for (name, (p50, p95, p99)) in percentiles {
+                summary = summary.push(Text::new(format!(
+                    "{} p50/p95/p99: {:.0}/{:.0}/{:.0}%",
+                    name, p50, p95, p99
+                )));
             }
-            SortField::Command => {
-                if self.sort_order == SortOrder::Ascending {
-                    self.filtered_processes
-                        .sort_by(|a, b| a.command.cmp(&b.command));
+        }
+        // Colored like the network and disk charts' lines, so they double as legends
+        if let Some(&(rx, tx)) = self.network_history.last() {
+            let style = self.style();
+            summary = summary
+                .push(Text::new(format!("Net RX: {}/s", format_bytes(rx))).style(style.cpu_line))
+                .push(Text::new(format!("TX: {}/s", format_bytes(tx))).style(style.memory_line));
+        }
+        if let Some(&(read, written)) = self.disk_io_history.last() {
+            let style = self.style();
+            let read = Text::new(format!("Disk read: {}/s", format_bytes(read)));
+            let written = Text::new(format!("write: {}/s", format_bytes(written)));
+            summary = summary
+                .push(read.style(style.cpu_line))
+                .push(written.style(style.memory_line));
+        }
+        // Hidden entirely on machines without CPU temperature sensors
+        if let Some(temperature) = self.cpu_temperature {
+            let mut text = Text::new(format!("CPU temp: {:.0}°C", temperature));
+            if temperature > self.config.temperature_alert_threshold {
+                text = text.style(self.style().danger);
+            }
+            summary = summary.push(text);
+        }
+        if !self.status_counts.is_empty() {
+            summary = summary
+                .push(
+                    Canvas::new(StateBreakdownChart::new(&self.status_counts, self.style()))
+                        .width(Length::Fixed(150.0))
+                        .height(Length::Fixed(14.0)),
+                )
+                .push(Text::new(format_status_counts(&self.status_counts)));
+        }
+        // The observer effect: what watching the system costs it
+        if let Some((cpu, memory)) = self.own_usage {
+            let mut text = Text::new(format!(
+                "Task manager: {:.1}% CPU, {}{}",
+                cpu,
+                format_bytes(memory),
+                if self.overhead_reduced {
+                    " (reducing update rate to limit overhead)"
                 } else {
-                    self.filtered_processes
-                        .sort_by(|a, b| b.command.cmp(&a.command));
+                    ""
                 }
+            ));
+            if self.overhead_reduced {
+                text = text.style(self.style().warning);
+            }
+            summary = summary.push(text);
+        }
+
+        summary.into()
+    }
+
+    fn kill_confirmation(&self, pid: i32, signal: Signal) -> Element<'_, Message> {
+        let command = self
+            .processes
+            .iter()
+            .find(|p| p.pid == pid)
+            .map(|p| p.command.as_str())
+            .unwrap_or("unknown");
+
+        Row::new()
+            .padding(10)
+            .spacing(20)
+            .align_items(Alignment::Center)
+            .push(Text::new(format!("Kill PID {} ({}) with", pid, command)).size(18))
+            .push(
+                PickList::new(&KILL_SIGNALS[..], Some(signal), Message::ChooseKillSignal)
+                    .padding(5),
+            )
+            .push(
+                Button::new(Text::new("Kill"))
+                    .on_press(Message::ConfirmKill)
+                    .padding(5),
+            )
+            .push(
+                Button::new(Text::new("Cancel"))
+                    .on_press(Message::Dismiss)
+                    .padding(5),
+            )
+            .into()
+    }
+
+    fn disk_panel(&self) -> Element<'_, Message> {
+        let rows = self.disks.iter().fold(
+            Column::new().spacing(5).padding(10),
+            |column, disk| {
+                let used_space = disk.total_space - disk.available_space;
+                column.push(
+                    Row::new()
+                        .spacing(20)
+                        .align_items(Alignment::Center)
+                        .push(Text::new(&disk.mount_point).width(Length::Fixed(200.0)))
+                        .push(Text::new(&disk.file_system).width(Length::Fixed(80.0)))
+                        .push(
+                            Text::new(format!(
+                                "{} / {}",
+                                format_bytes(used_space),
+                                format_bytes(disk.total_space)
+                            ))
+                            .width(Length::Fixed(200.0)),
+                        )
+                        .push(
+                            ProgressBar::new(0.0..=100.0, disk.usage_percent)
+                                .width(Length::Fixed(200.0))
+                                .height(Length::Fixed(12.0)),
+                        )
+                        .push(Text::new(format!("{:.1}%", disk.usage_percent))),
+                )
+            },
+        );
+
+        Column::new()
+            .padding(10)
+            .push(Text::new("Disk Usage").size(20))
+            .push(rows)
+            .into()
+    }
+
+    fn growth_panel(&self) -> Element<'_, Message> {
+        let rows = self.growth_leaders.iter().fold(
+            Column::new().spacing(5).padding(10),
+            |column, &(pid, delta)| {
+                let command = self
+                    .processes
+                    .iter()
+                    .find(|p| p.pid == pid)
+                    .map(|p| p.display_command(self.config.command_display))
+                    .unwrap_or("exited");
+                let color = if delta > 0 { self.style().danger } else { self.style().ok };
+                column.push(
+                    Row::new()
+                        .spacing(20)
+                        .push(Text::new(pid.to_string()).width(Length::Fixed(60.0)))
+                        .push(
+                            Text::new(format_bytes_delta(delta))
+                                .style(color)
+                                .width(Length::Fixed(120.0)),
+                        )
+                        .push(Text::new(command)),
+                )
+            },
+        );
+
+        Column::new()
+            .padding(10)
+            .push(
+                Text::new(format!("Memory growth over the last {} samples", GROWTH_WINDOW))
+                    .size(20),
+            )
+            .push(rows)
+            .into()
+    }
+
+    /// What appeared, exited and changed most while refreshing was paused.
+    fn pause_diff_panel<'a>(&'a self, pause_diff: &'a PauseDiff) -> Element<'a, Message> {
+        let style = self.style();
+        let ProcessDiff { new_processes, exited_processes, changed } = &pause_diff.diff;
+        let list = |processes: &[ProcessSummary]| {
+            let mut names: Vec<String> = processes
+                .iter()
+                .take(PAUSE_DIFF_ROWS)
+                .map(|p| format!("{} ({})", p.command, p.pid))
+                .collect();
+            if processes.len() > PAUSE_DIFF_ROWS {
+                names.push(format!("{} more", processes.len() - PAUSE_DIFF_ROWS));
+            }
+            names.join(", ")
+        };
+
+        let mut panel = Column::new().padding(10).spacing(5).push(
+            Row::new()
+                .spacing(20)
+                .align_items(Alignment::Center)
+                .push(
+                    Text::new(format!(
+                        "Changes during the {}s pause",
+                        pause_diff.paused_for.as_secs()
+                    ))
+                    .size(20),
+                )
+                .push(
+                    Button::new(Text::new("Dismiss"))
+                        .on_press(Message::DismissPauseDiff)
+                        .padding(5),
+                ),
+        );
+        if new_processes.is_empty() && exited_processes.is_empty() && changed.is_empty() {
+            return panel.push(Text::new("Nothing changed noticeably")).into();
+        }
+        if !new_processes.is_empty() {
+            panel = panel.push(
+                Text::new(format!("Appeared ({}): {}", new_processes.len(), list(new_processes)))
+                    .style(style.ok),
+            );
+        }
+        if !exited_processes.is_empty() {
+            panel = panel.push(
+                Text::new(format!(
+                    "Exited ({}): {}",
+                    exited_processes.len(),
+                    list(exited_processes)
+                ))
+                .style(style.warning),
+            );
+        }
+
+        // Biggest first, each change measured against its threshold so CPU and
+        // memory compare
+        let size = |c: &ProcessChange| {
+            let cpu = (c.cpu_after - c.cpu_before).abs() / PAUSE_DIFF_CPU;
+            let memory = c.memory_after.abs_diff(c.memory_before) as f32 / PAUSE_DIFF_MEMORY as f32;
+            cpu.max(memory)
+        };
+        let mut biggest: Vec<&ProcessChange> = changed.iter().collect();
+        biggest.sort_by(|a, b| size(b).total_cmp(&size(a)));
+        panel.push(biggest.into_iter().take(PAUSE_DIFF_ROWS).fold(
+            Column::new().spacing(5),
+            |column, change| {
+                let delta_color = |grew: bool| if grew { style.danger } else { style.ok };
+                let memory_delta = change.memory_after as i64 - change.memory_before as i64;
+                column.push(
+                    Row::new()
+                        .spacing(20)
+                        .push(Text::new(change.pid.to_string()).width(Length::Fixed(60.0)))
+                        .push(
+                            Text::new(format!(
+                                "CPU {:.1} -> {:.1}%",
+                                change.cpu_before, change.cpu_after
+                            ))
+                            .style(delta_color(change.cpu_after > change.cpu_before))
+                            .width(Length::Fixed(160.0)),
+                        )
+                        .push(
+                            Text::new(format_bytes_delta(memory_delta))
+                                .style(delta_color(memory_delta > 0))
+                                .width(Length::Fixed(120.0)),
+                        )
+                        .push(Text::new(&change.command)),
+                )
+            },
+        ))
+        .into()
+    }
+
+    /// Where CPU time went since the app started, as bars relative to the busiest command.
+    fn session_cpu_panel(&self) -> Element<'_, Message> {
+        let busiest = self.session_cpu.first().map_or(0.0, |(_, seconds)| *seconds) as f32;
+        let rows = self.session_cpu.iter().fold(
+            Column::new().spacing(5).padding(10),
+            |column, (command, seconds)| {
+                column.push(
+                    Row::new()
+                        .spacing(20)
+                        .align_items(Alignment::Center)
+                        .push(Text::new(command).width(Length::Fixed(200.0)))
+                        .push(
+                            ProgressBar::new(0.0..=busiest.max(f32::MIN_POSITIVE), *seconds as f32)
+                                .width(Length::Fixed(300.0))
+                                .height(Length::Fixed(12.0)),
+                        )
+                        .push(Text::new(format!("{:.1} s", seconds))),
+                )
+            },
+        );
+
+        Column::new()
+            .padding(10)
+            .push(Text::new("CPU time this session").size(20))
+            .push(rows)
+            .into()
+    }
+
+    fn alert_message(&self) -> Option<String> {
+        let alerts = self.config.alerts(
+            self.cpu_usage_history.last().copied(),
+            self.memory_usage_history.last().copied(),
+        );
+        if alerts.is_empty() {
+            None
+        } else {
+            Some(format!("Alert: {}", alerts.join(", ")))
+        }
+    }
+
+    /// Filters and sorts `processes` into `filtered_processes`.
+    ///
+    /// With `skip_sort` set, rows keep the position they had before this refresh
+    /// (new processes are appended at the bottom) so only their values change.
+    /// The keyboard selection follows the selected process to its new row.
+    fn apply_filter_and_sort(&mut self, skip_sort: bool) {
+        let selected_pid = self.selected_process().map(|p| p.pid);
+        self.sort_processes(skip_sort);
+
+        // Keep the selection on the same process, or clamp it if that process is gone
+        self.selected_index = selected_pid.and_then(|pid| {
+            self.filtered_processes
+                .iter()
+                .position(|p| p.pid == pid)
+                .or_else(|| {
+                    let last = self.filtered_processes.len().checked_sub(1)?;
+                    self.selected_index.map(|index| index.min(last))
+                })
+        });
+    }
+
+    fn sort_processes(&mut self, skip_sort: bool) {
+        let previous_positions: HashMap<i32, usize> = if skip_sort {
+            self.filtered_processes
+                .iter()
+                .enumerate()
+                .map(|(index, p)| (p.pid, index))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        let filter = (!self.search_query.is_empty()).then(|| {
+            ProcessFilter::with_terms(&self.search_query, self.filter_mode, self.term_match)
+        });
+        self.filtered_processes = self
+            .processes
+            .iter()
+            .filter(|p| filter.as_ref().is_none_or(|filter| filter.matches(p)))
+            .filter(|p| self.show_ignored || !self.config.is_ignored(p.pid, &p.command))
+            .filter(|p| {
+                self.cpu_range.contains(p.cpu_usage)
+                    && self.memory_range.contains(bytes_to_mb(p.memory_usage))
+            })
+            .cloned()
+            .collect();
+
+        self.tree_depths.clear();
+        self.tree_descendants.clear();
+        // Stable rows can't keep old positions in a tree; new children go under their parent
+        if skip_sort && !self.tree_view {
+            self.filtered_processes.sort_by_key(|p| {
+                (previous_positions.get(&p.pid).copied().unwrap_or(usize::MAX), p.pid)
+            });
+            return;
+        }
+
+        // Fuzzy matches are ranked best first, with the sort column breaking ties. A
+        // ranking has no room for the hierarchy, so it's shown flat even in tree view.
+        if let Some(filter) = filter.filter(|_| self.filter_mode == FilterMode::Fuzzy) {
+            sort_processes(
+                &mut self.filtered_processes,
+                self.sort_field,
+                self.sort_order,
+                self.config.command_display,
+            );
+            self.filtered_processes.sort_by_cached_key(|p| std::cmp::Reverse(filter.score(p)));
+            return;
+        }
+
+        if self.tree_view {
+            self.tree_depths = sort_process_tree(
+                &mut self.filtered_processes,
+                self.sort_field,
+                self.sort_order,
+                self.config.command_display,
+            );
+            // Forget exited processes so a new one reusing the PID starts expanded
+            if !self.collapsed.is_empty() {
+                let alive: HashSet<i32> = self.processes.iter().map(|p| p.pid).collect();
+                self.collapsed.retain(|pid| alive.contains(pid));
             }
+            self.tree_descendants = collapse_process_tree(
+                &mut self.filtered_processes,
+                &mut self.tree_depths,
+                &self.collapsed,
+            );
+            return;
+        }
+        sort_processes(
+            &mut self.filtered_processes,
+            self.sort_field,
+            self.sort_order,
+            self.config.command_display,
+        );
+    }
+}
+
+// CPU Usage Chart with Spike Detection
+struct CpuUsageChart {
+    title: String,
+    cpu_usage_history: Vec<ChartBucket>,
+    /// Chart events by bucket
+    events: Vec<(usize, String)>,
+    spike_threshold: f32,
+    style: VisualStyle,
+}
+
+impl CpuUsageChart {
+    /// `cpu_range_history` widens each point's min-max bar to what the fine sampler saw.
+    fn new(
+        title: &str,
+        cpu_usage_history: &[f32],
+        cpu_range_history: &[(f32, f32)],
+        events: &[(usize, String)],
+        spike_threshold: f32,
+        render_budget: usize,
+        style: VisualStyle,
+    ) -> Self {
+        Self {
+            title: title.to_string(),
+            cpu_usage_history: downsample_with_ranges(
+                cpu_usage_history,
+                cpu_range_history,
+                render_budget,
+            ),
+            events: bucket_events(events, cpu_usage_history.len(), render_budget),
+            spike_threshold,
+            style,
+        }
+    }
+}
+
+impl<Message> canvas::Program<Message> for CpuUsageChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+
+        if self.cpu_usage_history.len() < 2 {
+            return vec![frame.into_geometry()];
+        }
+
+        // A process using several cores goes past 100%; widen the scale a core at a time
+        let peak = self.cpu_usage_history.iter().fold(100.0, |peak: f32, b| peak.max(b.max));
+        let max_value = (peak / 100.0).ceil() * 100.0;
+        let min_value = 0.0;
+
+        let step_x = bounds.width / (self.cpu_usage_history.len() - 1) as f32;
+        let scale_y = bounds.height / (max_value - min_value);
+
+        // Draw grid lines
+        for i in 0..=5 {
+            let y = i as f32 * bounds.height / 5.0;
+            frame.stroke(
+                &Path::line(
+                    iced::Point::new(0.0, y),
+                    iced::Point::new(bounds.width, y),
+                ),
+                Stroke {
+                    style: Style::Solid(self.style.grid),
+                    width: 1.0,
+                    ..Stroke::default()
+                },
+            );
+        }
+
+        // Draw axes
+        frame.stroke(
+            &Path::line(
+                iced::Point::new(0.0, bounds.height),
+                iced::Point::new(bounds.width, bounds.height),
+            ),
+            Stroke::default().with_color(self.style.axis).with_width(1.0),
+        );
+        frame.stroke(
+            &Path::line(
+                iced::Point::new(0.0, 0.0),
+                iced::Point::new(0.0, bounds.height),
+            ),
+            Stroke::default().with_color(self.style.axis).with_width(1.0),
+        );
+
+        // Draw labels
+        frame.fill_text(CanvasText {
+            content: self.title.clone(),
+            position: iced::Point::new(5.0, 20.0),
+            color: self.style.label,
+            size: self.style.label_size,
+            ..CanvasText::default()
+        });
+
+        let to_y = |value| bounds.height - (value - min_value) * scale_y;
+        draw_bucket_ranges(&mut frame, &self.cpu_usage_history, step_x, self.style.range, to_y);
+        draw_event_markers(&mut frame, &self.events, step_x, bounds.height, &self.style);
+
+        // Initialize previous point and value
+        let mut previous_value = self.cpu_usage_history[0].avg;
+        let mut previous_point = iced::Point::new(
+            0.0,
+            bounds.height - (previous_value - min_value) * scale_y,
+        );
+
+        for (i, bucket) in self.cpu_usage_history.iter().enumerate().skip(1) {
+            let current_value = bucket.avg;
+            let x = i as f32 * step_x;
+            let y = bounds.height - (current_value - min_value) * scale_y;
+            let current_point = iced::Point::new(x, y);
+
+            // Calculate percentage change
+            let percentage_change = if previous_value.abs() > f32::EPSILON {
+                ((current_value - previous_value) / previous_value.abs()) * 100.0
+            } else {
+                0.0
+            };
+
+            // Set line color based on spike detection
+            let line_color = if percentage_change.abs() > self.spike_threshold {
+                self.style.spike
+            } else {
+                self.style.cpu_line
+            };
+
+            // Draw line segment
+            frame.stroke(
+                &Path::line(previous_point, current_point),
+                Stroke {
+                    style: Style::Solid(line_color),
+                    width: self.style.line_width,
+                    ..Stroke::default()
+                },
+            );
+
+            previous_value = current_value;
+            previous_point = current_point;
         }
+
+        vec![frame.into_geometry()]
     }
 }
 
-// CPU Usage Chart with Spike Detection
-struct CpuUsageChart {
-    cpu_usage_history: Vec<f32>,
+
+// CPU and memory usage on one shared 0-100% chart, for when space is tight
+struct OverlayChart {
+    cpu_usage_history: Vec<ChartBucket>,
+    memory_usage_history: Vec<ChartBucket>,
+    events: Vec<(usize, String)>,
+    style: VisualStyle,
 }
 
-impl CpuUsageChart {
-    fn new(cpu_usage_history: Vec<f32>) -> Self {
-        Self { cpu_usage_history }
+impl OverlayChart {
+    fn new(
+        cpu_usage_history: &[f32],
+        cpu_range_history: &[(f32, f32)],
+        memory_usage_history: &[f32],
+        events: &[(usize, String)],
+        render_budget: usize,
+        style: VisualStyle,
+    ) -> Self {
+        Self {
+            cpu_usage_history: downsample_with_ranges(
+                cpu_usage_history,
+                cpu_range_history,
+                render_budget,
+            ),
+            memory_usage_history: downsample(memory_usage_history, render_budget),
+            events: bucket_events(events, cpu_usage_history.len(), render_budget),
+            style,
+        }
     }
 }
 
-impl<Message> canvas::Program<Message> for CpuUsageChart {
+impl<Message> canvas::Program<Message> for OverlayChart {
     type State = ();
 
     fn draw(
@@ -376,16 +3202,6 @@ impl<Message> canvas::Program<Message> for CpuUsageChart {
     ) -> Vec<Geometry> {
         let mut frame = Frame::new(renderer, bounds.size());
 
-        if self.cpu_usage_history.len() < 2 {
-            return vec![frame.into_geometry()];
-        }
-
-        let max_value = 100.0;
-        let min_value = 0.0;
-
-        let step_x = bounds.width / (self.cpu_usage_history.len() - 1) as f32;
-        let scale_y = bounds.height / (max_value - min_value);
-
         // Draw grid lines
         for i in 0..=5 {
             let y = i as f32 * bounds.height / 5.0;
@@ -395,7 +3211,7 @@ impl<Message> canvas::Program<Message> for CpuUsageChart {
                     iced::Point::new(bounds.width, y),
                 ),
                 Stroke {
-                    style: Style::Solid(iced::Color::from_rgb(0.9, 0.9, 0.9)),
+                    style: Style::Solid(self.style.grid),
                     width: 1.0,
                     ..Stroke::default()
                 },
@@ -408,77 +3224,203 @@ impl<Message> canvas::Program<Message> for CpuUsageChart {
                 iced::Point::new(0.0, bounds.height),
                 iced::Point::new(bounds.width, bounds.height),
             ),
-            Stroke::default().with_width(1.0),
+            Stroke::default().with_color(self.style.axis).with_width(1.0),
         );
         frame.stroke(
             &Path::line(
                 iced::Point::new(0.0, 0.0),
                 iced::Point::new(0.0, bounds.height),
             ),
-            Stroke::default().with_width(1.0),
+            Stroke::default().with_color(self.style.axis).with_width(1.0),
         );
 
-        // Draw labels
+        // Title on the left and legend on the right, so they can't overlap
         frame.fill_text(CanvasText {
-            content: "CPU Usage (%)".to_string(),
+            content: "Usage (%)".to_string(),
             position: iced::Point::new(5.0, 20.0),
-            color: iced::Color::from_rgb(0.2, 0.2, 0.2),
-            size: 18.0,
+            color: self.style.label,
+            size: self.style.label_size,
             ..CanvasText::default()
         });
+        let legend = [("CPU", self.style.cpu_line), ("Memory", self.style.memory_line)];
+        for (row, (label, color)) in legend.into_iter().enumerate() {
+            let y = 8.0 + row as f32 * 18.0;
+            frame.fill_rectangle(
+                iced::Point::new(bounds.width - 90.0, y + 4.0),
+                iced::Size::new(12.0, 4.0),
+                color,
+            );
+            frame.fill_text(CanvasText {
+                content: label.to_string(),
+                position: iced::Point::new(bounds.width - 72.0, y),
+                color: self.style.label,
+                size: self.style.label_size - 4.0,
+                ..CanvasText::default()
+            });
+        }
 
-        // Initialize previous point and value
-        let mut previous_value = self.cpu_usage_history[0];
-        let mut previous_point = iced::Point::new(
-            0.0,
-            bounds.height - (previous_value - min_value) * scale_y,
-        );
+        let to_y = |value: f32| bounds.height - value / 100.0 * bounds.height;
+        if self.cpu_usage_history.len() >= 2 {
+            let step_x = bounds.width / (self.cpu_usage_history.len() - 1) as f32;
+            draw_event_markers(&mut frame, &self.events, step_x, bounds.height, &self.style);
+        }
+        for (buckets, color) in [
+            (&self.cpu_usage_history, self.style.cpu_line),
+            (&self.memory_usage_history, self.style.memory_line),
+        ] {
+            if buckets.len() < 2 {
+                continue;
+            }
+            let step_x = bounds.width / (buckets.len() - 1) as f32;
+            for (i, pair) in buckets.windows(2).enumerate() {
+                frame.stroke(
+                    &Path::line(
+                        iced::Point::new(i as f32 * step_x, to_y(pair[0].avg)),
+                        iced::Point::new((i + 1) as f32 * step_x, to_y(pair[1].avg)),
+                    ),
+                    Stroke {
+                        style: Style::Solid(color),
+                        width: self.style.line_width,
+                        ..Stroke::default()
+                    },
+                );
+            }
+        }
 
-        for (i, &current_value) in self.cpu_usage_history.iter().enumerate().skip(1) {
-            let x = i as f32 * step_x;
-            let y = bounds.height - (current_value - min_value) * scale_y;
-            let current_point = iced::Point::new(x, y);
+        vec![frame.into_geometry()]
+    }
+}
 
-            // Calculate percentage change
-            let percentage_change = if previous_value.abs() > std::f32::EPSILON {
-                ((current_value - previous_value) / previous_value.abs()) * 100.0
-            } else {
-                0.0
-            };
+/// Summary of consecutive samples drawn as a single chart point.
+#[derive(Debug, Clone, Copy)]
+struct ChartBucket {
+    min: f32,
+    max: f32,
+    avg: f32,
+}
 
-            // Set line color based on spike detection
-            let line_color = if percentage_change.abs() > SPIKE_THRESHOLD {
-                iced::Color::from_rgb(1.0, 0.0, 0.0) // Red color for spikes
-            } else {
-                iced::Color::from_rgb(0.0, 0.5, 0.5) // Normal color
-            };
+/// Groups `values` into at most `budget` buckets of consecutive samples, so long
+/// histories draw a bounded number of segments. Histories within the budget map
+/// to one bucket per sample.
+fn downsample(values: &[f32], budget: usize) -> Vec<ChartBucket> {
+    downsample_with_ranges(values, &[], budget)
+}
 
-            // Draw line segment
-            frame.stroke(
-                &Path::line(previous_point, current_point),
-                Stroke {
-                    style: Style::Solid(line_color),
-                    width: 2.0,
-                    ..Stroke::default()
-                },
-            );
+/// Like `downsample`, with each bucket's min and max also covering the `ranges` of its
+/// samples, e.g. what the fine CPU sampler saw between refreshes. `ranges` lines up
+/// with the end of `values`, since it may have started later.
+fn downsample_with_ranges(
+    values: &[f32],
+    ranges: &[(f32, f32)],
+    budget: usize,
+) -> Vec<ChartBucket> {
+    let bucket_size = values.len().div_ceil(budget.max(1)).max(1);
+    let offset = values.len().saturating_sub(ranges.len());
+    values
+        .chunks(bucket_size)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let start = index * bucket_size;
+            let (min, max) = (start..start + chunk.len())
+                .filter_map(|i| i.checked_sub(offset).and_then(|i| ranges.get(i)).copied())
+                .chain(chunk.iter().map(|&value| (value, value)))
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), (low, high)| {
+                    (min.min(low), max.max(high))
+                });
+            ChartBucket { min, max, avg: chunk.iter().sum::<f32>() / chunk.len() as f32 }
+        })
+        .collect()
+}
 
-            previous_value = current_value;
-            previous_point = current_point;
-        }
+/// Draws a faint vertical bar from each bucket's min to max, so short spikes that
+/// averaging flattens stay visible.
+fn draw_bucket_ranges(
+    frame: &mut Frame,
+    buckets: &[ChartBucket],
+    step_x: f32,
+    color: iced::Color,
+    to_y: impl Fn(f32) -> f32,
+) {
+    for (i, bucket) in buckets.iter().enumerate().filter(|(_, b)| b.max > b.min) {
+        let x = i as f32 * step_x;
+        frame.stroke(
+            &Path::line(
+                iced::Point::new(x, to_y(bucket.min)),
+                iced::Point::new(x, to_y(bucket.max)),
+            ),
+            Stroke {
+                style: Style::Solid(color),
+                width: 1.0,
+                ..Stroke::default()
+            },
+        );
+    }
+}
 
-        vec![frame.into_geometry()]
+/// Chart events moved to the bucket `downsample` puts their sample in.
+fn bucket_events(
+    events: &[(usize, String)],
+    samples: usize,
+    budget: usize,
+) -> Vec<(usize, String)> {
+    let bucket_size = samples.div_ceil(budget.max(1)).max(1);
+    events.iter().map(|(index, label)| (index / bucket_size, label.clone())).collect()
+}
+
+/// Draws each event as a dashed vertical line, labelled at the top.
+fn draw_event_markers(
+    frame: &mut Frame,
+    events: &[(usize, String)],
+    step_x: f32,
+    height: f32,
+    style: &VisualStyle,
+) {
+    for (index, label) in events {
+        let x = *index as f32 * step_x;
+        frame.stroke(
+            &Path::line(iced::Point::new(x, 0.0), iced::Point::new(x, height)),
+            Stroke {
+                style: Style::Solid(style.label),
+                width: 1.0,
+                line_dash: LineDash { segments: &[4.0, 4.0], offset: 0 },
+                ..Stroke::default()
+            },
+        );
+        frame.fill_text(CanvasText {
+            content: label.clone(),
+            position: iced::Point::new(x + 3.0, 2.0),
+            color: style.label,
+            size: style.label_size - 6.0,
+            ..CanvasText::default()
+        });
     }
 }
 
 // Memory Usage Chart with Spike Detection
 struct MemoryUsageChart {
-    memory_usage_history: Vec<f32>,
+    title: String,
+    memory_usage_history: Vec<ChartBucket>,
+    events: Vec<(usize, String)>,
+    spike_threshold: f32,
+    style: VisualStyle,
 }
 
 impl MemoryUsageChart {
-    fn new(memory_usage_history: Vec<f32>) -> Self {
-        Self { memory_usage_history }
+    fn new(
+        title: &str,
+        memory_usage_history: &[f32],
+        events: &[(usize, String)],
+        spike_threshold: f32,
+        render_budget: usize,
+        style: VisualStyle,
+    ) -> Self {
+        Self {
+            title: title.to_string(),
+            memory_usage_history: downsample(memory_usage_history, render_budget),
+            events: bucket_events(events, memory_usage_history.len(), render_budget),
+            spike_threshold,
+            style,
+        }
     }
 }
 
@@ -514,7 +3456,7 @@ impl<Message> canvas::Program<Message> for MemoryUsageChart {
                     iced::Point::new(bounds.width, y),
                 ),
                 Stroke {
-                    style: Style::Solid(iced::Color::from_rgb(0.9, 0.9, 0.9)),
+                    style: Style::Solid(self.style.grid),
                     width: 1.0,
                     ..Stroke::default()
                 },
@@ -527,49 +3469,54 @@ impl<Message> canvas::Program<Message> for MemoryUsageChart {
                 iced::Point::new(0.0, bounds.height),
                 iced::Point::new(bounds.width, bounds.height),
             ),
-            Stroke::default().with_width(1.0),
+            Stroke::default().with_color(self.style.axis).with_width(1.0),
         );
         frame.stroke(
             &Path::line(
                 iced::Point::new(0.0, 0.0),
                 iced::Point::new(0.0, bounds.height),
             ),
-            Stroke::default().with_width(1.0),
+            Stroke::default().with_color(self.style.axis).with_width(1.0),
         );
 
         // Draw labels
         frame.fill_text(CanvasText {
-            content: "Memory Usage (%)".to_string(),
+            content: self.title.clone(),
             position: iced::Point::new(5.0, 20.0),
-            color: iced::Color::from_rgb(0.2, 0.2, 0.2),
-            size: 18.0,
+            color: self.style.label,
+            size: self.style.label_size,
             ..CanvasText::default()
         });
 
+        let to_y = |value| bounds.height - (value - min_value) * scale_y;
+        draw_bucket_ranges(&mut frame, &self.memory_usage_history, step_x, self.style.range, to_y);
+        draw_event_markers(&mut frame, &self.events, step_x, bounds.height, &self.style);
+
         // Initialize previous point and value
-        let mut previous_value = self.memory_usage_history[0];
+        let mut previous_value = self.memory_usage_history[0].avg;
         let mut previous_point = iced::Point::new(
             0.0,
             bounds.height - (previous_value - min_value) * scale_y,
         );
 
-        for (i, &current_value) in self.memory_usage_history.iter().enumerate().skip(1) {
+        for (i, bucket) in self.memory_usage_history.iter().enumerate().skip(1) {
+            let current_value = bucket.avg;
             let x = i as f32 * step_x;
             let y = bounds.height - (current_value - min_value) * scale_y;
             let current_point = iced::Point::new(x, y);
 
             // Calculate percentage change
-            let percentage_change = if previous_value.abs() > std::f32::EPSILON {
+            let percentage_change = if previous_value.abs() > f32::EPSILON {
                 ((current_value - previous_value) / previous_value.abs()) * 100.0
             } else {
                 0.0
             };
 
             // Set line color based on spike detection
-            let line_color = if percentage_change.abs() > SPIKE_THRESHOLD {
-                iced::Color::from_rgb(1.0, 0.0, 0.0) // Red color for spikes
+            let line_color = if percentage_change.abs() > self.spike_threshold {
+                self.style.spike
             } else {
-                iced::Color::from_rgb(0.5, 0.0, 0.5) // Normal color
+                self.style.memory_line
             };
 
             // Draw line segment
@@ -577,7 +3524,7 @@ impl<Message> canvas::Program<Message> for MemoryUsageChart {
                 &Path::line(previous_point, current_point),
                 Stroke {
                     style: Style::Solid(line_color),
-                    width: 2.0,
+                    width: self.style.line_width,
                     ..Stroke::default()
                 },
             );
@@ -589,3 +3536,561 @@ impl<Message> canvas::Program<Message> for MemoryUsageChart {
         vec![frame.into_geometry()]
     }
 }
+
+fn handle_event(event: Event, status: event::Status) -> Option<Message> {
+    match event {
+        Event::Window(window::Event::Resized { width, height }) => {
+            return Some(Message::WindowResized(width, height));
+        }
+        Event::Window(window::Event::CloseRequested) => return Some(Message::CloseRequested),
+        Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+            return Some(Message::ModifiersChanged(modifiers));
+        }
+        _ => {}
+    }
+
+    // Keys consumed by a widget (e.g. typing in the search box) aren't shortcuts
+    if status == event::Status::Captured {
+        return None;
+    }
+
+    match event {
+        Event::Keyboard(keyboard::Event::KeyPressed { key_code, .. }) => match key_code {
+            KeyCode::Up => Some(Message::SelectPrevious),
+            KeyCode::Down => Some(Message::SelectNext),
+            KeyCode::Enter => Some(Message::ActivateSelection),
+            KeyCode::Delete => Some(Message::RequestKillSelected),
+            KeyCode::Escape => Some(Message::Dismiss),
+            KeyCode::Space => Some(Message::TogglePause),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Input and focus changes that count as the user being present, including
+/// events already consumed by a widget. Cursor movement is left out since it
+/// arrives far too often to turn each one into a message.
+fn activity_event(event: Event, _status: event::Status) -> Option<Message> {
+    match event {
+        Event::Window(window::Event::Focused) => Some(Message::FocusChanged(true)),
+        Event::Window(window::Event::Unfocused) => Some(Message::FocusChanged(false)),
+        Event::Keyboard(keyboard::Event::KeyPressed { .. })
+        | Event::Mouse(mouse::Event::ButtonPressed(_))
+        | Event::Mouse(mouse::Event::WheelScrolled { .. }) => Some(Message::UserActivity),
+        _ => None,
+    }
+}
+
+/// Runs `work` with the handler locked on a blocking thread, since the lock can be held
+/// for a whole refresh and waiting for it must not stall an executor thread.
+fn with_handler(
+    handler: Arc<Mutex<ProcessHandler>>,
+    work: impl FnOnce(&mut ProcessHandler) -> Message + Send + 'static,
+) -> Command<Message> {
+    Command::perform(
+        async move {
+            tokio::task::spawn_blocking(move || work(&mut handler.lock().unwrap()))
+                .await
+                .expect("handler task panicked")
+        },
+        |msg| msg,
+    )
+}
+
+/// Status bar text for a failed kill, with what to do about the common causes.
+fn kill_error_message(error: ProcessError) -> String {
+    match error {
+        ProcessError::NotFound(pid) => format!("PID {} had already exited", pid),
+        ProcessError::PermissionDenied { pid, .. } => format!(
+            "PID {} belongs to another user; restart the task manager as root to kill it",
+            pid
+        ),
+        ProcessError::PolicyDenied { .. } => {
+            format!("{}; change kill_policy in the config to allow it", error)
+        }
+        _ => error.to_string(),
+    }
+}
+
+/// `text` with the byte ranges in `matched` bold and colored, as a row of pieces.
+fn highlighted_text<'a>(
+    text: &str,
+    matched: &[Range<usize>],
+    size: f32,
+    color: iced::Color,
+) -> Element<'a, Message> {
+    if matched.is_empty() {
+        return Text::new(text.to_string()).size(size).into();
+    }
+    let bold = iced::Font { weight: iced::font::Weight::Bold, ..iced::Font::DEFAULT };
+    let mut row = Row::new();
+    let mut end = 0;
+    for range in matched {
+        if range.start > end {
+            row = row.push(Text::new(text[end..range.start].to_string()).size(size));
+        }
+        row = row.push(
+            Text::new(text[range.clone()].to_string()).size(size).font(bold).style(color),
+        );
+        end = range.end;
+    }
+    if end < text.len() {
+        row = row.push(Text::new(text[end..].to_string()).size(size));
+    }
+    row.into()
+}
+
+/// Shortens `text` to at most `max_chars` characters, ending in an ellipsis if cut.
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Linear blend from `from` (at 0) to `to` (at 1).
+fn mix(from: iced::Color, to: iced::Color, amount: f32) -> iced::Color {
+    let lerp = |a: f32, b: f32| a + (b - a) * amount;
+    iced::Color::from_rgba(
+        lerp(from.r, to.r),
+        lerp(from.g, to.g),
+        lerp(from.b, to.b),
+        lerp(from.a, to.a),
+    )
+}
+
+fn bytes_to_mb(bytes: u64) -> f32 {
+    bytes as f32 / (1024.0 * 1024.0)
+}
+
+fn privilege_warning<'a>() -> Element<'a, Message> {
+    Row::new()
+        .padding(5)
+        .spacing(20)
+        .align_items(Alignment::Center)
+        .push(
+            Text::new(
+                "Not running as root: only your own processes can be killed or reniced.",
+            )
+            .style(iced::Color::from_rgb(0.8, 0.5, 0.0)),
+        )
+        .push(
+            Button::new(Text::new("Dismiss"))
+                .on_press(Message::DismissPrivilegeWarning)
+                .padding(5),
+        )
+        .into()
+}
+
+fn sort_button(label: &str, field: SortField, width: f32) -> Button<'_, Message> {
+    Button::new(Text::new(label))
+        .on_press(Message::SortBy(field))
+        .padding(5)
+        .width(Length::Fixed(width))
+}
+
+fn striped_row_appearance(theme: &Theme) -> container::Appearance {
+    container::Appearance {
+        background: Some(Background::Color(theme.extended_palette().background.weak.color)),
+        border_radius: 4.0.into(),
+        ..Default::default()
+    }
+}
+
+fn outlined_row_appearance(theme: &Theme) -> container::Appearance {
+    container::Appearance {
+        border_radius: 4.0.into(),
+        border_width: 1.0,
+        border_color: theme.palette().text,
+        ..Default::default()
+    }
+}
+
+fn selected_row_appearance(theme: &Theme) -> container::Appearance {
+    container::Appearance {
+        background: Some(Background::Color(theme.extended_palette().primary.weak.color)),
+        text_color: Some(theme.extended_palette().primary.weak.text),
+        border_radius: 4.0.into(),
+        ..Default::default()
+    }
+}
+
+fn details_panel<'a>(
+    process: &'a ProcessInfo,
+    extras: Option<&DetailsExtras>,
+    style: VisualStyle,
+) -> Element<'a, Message> {
+    let cgroup = extras.and_then(|extras| extras.cgroup.as_ref());
+    let details = extras.and_then(|extras| extras.details.as_ref());
+    let environment: Element<'a, Message> = match details.map(|details| &details.environment) {
+        Some(Ok(variables)) => Column::new()
+            .push(Text::new(format!("Environment ({} variables):", variables.len())))
+            .push(
+                Scrollable::new(variables.iter().fold(
+                    Column::new().padding([0, 10]),
+                    |column, (key, value)| {
+                        column.push(Text::new(format!("{}={}", key, value)).size(14))
+                    },
+                ))
+                .height(Length::Fixed(120.0)),
+            )
+            .into(),
+        Some(Err(e)) => Text::new(format!("Environment: unavailable ({})", e)).into(),
+        None => Text::new("Environment: reading...").into(),
+    };
+    // Disabled until the first refresh tells whether it's quarantined
+    let quarantined = extras.map(|extras| extras.quarantined);
+    let mut quarantine_button = Button::new(Text::new(if quarantined == Some(true) {
+        "Release"
+    } else {
+        "Quarantine"
+    }))
+    .padding(5);
+    if let Some(quarantined) = quarantined {
+        quarantine_button =
+            quarantine_button.on_press(Message::ToggleQuarantine(process.pid, quarantined));
+    }
+    Column::new()
+        .padding(10)
+        .spacing(5)
+        .push(
+            Row::new()
+                .spacing(20)
+                .align_items(Alignment::Center)
+                .push(Text::new(format!("Process {}", process.pid)).size(20))
+                .push(quarantine_button)
+                .push(
+                    Button::new(Text::new("Close"))
+                        .on_press(Message::CloseDetails)
+                        .padding(5),
+                ),
+        )
+        .push(process_sparklines(extras.and_then(|extras| extras.history.as_ref()), style))
+        .push(Text::new(match (process.ppid, process.orphaned_from) {
+            (Some(ppid), Some(original)) => {
+                format!("Parent: {} (orphaned, originally {})", ppid, original)
+            }
+            (Some(ppid), None) => format!("Parent: {}", ppid),
+            (None, _) => "Parent: none".to_string(),
+        }))
+        .push(Text::new(format!("User: {}", process.user)))
+        .push(Text::new(match details.and_then(|details| details.started_at) {
+            Some(started_at) => format!(
+                "Started: {} ({} ago)",
+                humantime::format_rfc3339_seconds(started_at),
+                humantime::format_duration(Duration::from_secs(process.run_time_secs))
+            ),
+            None => format!("Started: {}s ago", process.run_time_secs),
+        }))
+        .push(Text::new(format!("Threads: {}", process.threads)))
+        .push(Text::new(match details.map(|details| &details.open_fds) {
+            Some(Ok(count)) => format!("Open file descriptors: {}", count),
+            Some(Err(e)) => format!("Open file descriptors: unavailable ({})", e),
+            None => "Open file descriptors: reading...".to_string(),
+        }))
+        .push(Text::new(match process.cpu_usage_of_allowed() {
+            Some(usage) => format!(
+                "CPU: {:.2}% ({:.2}% of {} allowed cores)",
+                process.cpu_usage, usage, process.allowed_cpus
+            ),
+            None => format!("CPU: {:.2}%", process.cpu_usage),
+        }))
+        .push(Text::new(format!("Memory: {}", format_bytes(process.memory_usage))))
+        .push(Text::new(format!("Peak memory: {}", format_bytes(process.peak_memory))))
+        .push(Text::new(format!("Swapped out: {}", format_bytes(process.swap_used))))
+        .push(Text::new(match extras.map(|extras| &extras.sched_policy) {
+            Some(Ok((policy, priority))) => {
+                format!("Scheduling: {}", format_sched_policy(*policy, *priority))
+            }
+            Some(Err(e)) => format!("Scheduling: unavailable ({})", e),
+            None => "Scheduling: reading...".to_string(),
+        }))
+        .push(Text::new(match extras.map(|extras| &extras.oom_score_adj) {
+            Some(Ok(adj)) => format!("OOM score adjustment: {}", adj),
+            Some(Err(e)) => format!("OOM score adjustment: unavailable ({})", e),
+            None => "OOM score adjustment: reading...".to_string(),
+        }))
+        .push(Text::new(match cgroup {
+            Some(limits) => format!(
+                "Cgroup {}: {}, CPU {}",
+                limits.path,
+                format_cgroup_memory(limits),
+                limits.cpu_limit.map_or("unlimited".to_string(), |cpus| {
+                    format!("limited to {:.2} cores", cpus)
+                })
+            ),
+            None => "Cgroup limits: unavailable (needs cgroup v2)".to_string(),
+        }))
+        .push(Text::new(format!(
+            "Lifetime CPU: {:.1}% over {}s",
+            process.efficiency * 100.0,
+            process.run_time_secs
+        )))
+        .push(Text::new(match process.power_watts {
+            Some(watts) => format!("Power (estimate from CPU share): ~{:.2} W", watts),
+            None => "Power: unavailable (no RAPL powercap access)".to_string(),
+        }))
+        .push(Text::new(format!("Command: {}", process.command)))
+        .push(Text::new(if process.exe_deleted {
+            format!("Executable: {} (deleted while running)", process.exe)
+        } else {
+            format!("Executable: {}", process.exe)
+        }))
+        .push(Text::new(if process.cmdline.is_empty() {
+            "Command line: none (kernel thread)".to_string()
+        } else {
+            format!("Command line: {}", process.cmdline)
+        }))
+        .push(Text::new(match details.map(|details| &details.cwd) {
+            Some(Ok(cwd)) => format!("Working directory: {}", cwd),
+            Some(Err(e)) => format!("Working directory: unavailable ({})", e),
+            None => "Working directory: reading...".to_string(),
+        }))
+        .push(environment)
+        .into()
+}
+
+/// CPU and memory of the process over its recorded history, as two sparklines.
+fn process_sparklines<'a>(
+    history: Option<&ProcessHistory>,
+    style: VisualStyle,
+) -> Element<'a, Message> {
+    let Some(history) = history.filter(|history| history.cpu_usage.len() >= 2) else {
+        return Text::new("Recent usage: collecting samples...").into();
+    };
+    let cpu: Vec<f32> = history.cpu_usage.iter().copied().collect();
+    let memory: Vec<f32> =
+        history.memory_usage.iter().map(|&bytes| bytes as f32 / (1024.0 * 1024.0)).collect();
+    let peak_memory = history.memory_usage.iter().copied().max().unwrap_or(0);
+    let sparkline = |values: &[f32], floor, color| {
+        Canvas::new(Sparkline::new(values, floor, color, style.line_width))
+            .width(Length::Fixed(200.0))
+            .height(Length::Fixed(30.0))
+    };
+    Row::new()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(Text::new(format!("CPU (last {} samples)", cpu.len())))
+        .push(sparkline(&cpu, 100.0, style.cpu_line))
+        .push(Text::new(format!("Memory (peak {})", format_bytes(peak_memory))))
+        // Scaled to its own peak, so growth shows even in a small process
+        .push(sparkline(&memory, 1.0, style.memory_line))
+        .into()
+}
+
+// A bare line without axes or labels, small enough to sit next to text
+struct Sparkline {
+    values: Vec<ChartBucket>,
+    max_value: f32,
+    color: iced::Color,
+    line_width: f32,
+}
+
+impl Sparkline {
+    /// The scale reaches at least `floor`, so a flat line near zero stays near the bottom.
+    fn new(values: &[f32], floor: f32, color: iced::Color, line_width: f32) -> Self {
+        let values = downsample(values, SPARKLINE_POINTS);
+        let max_value = values.iter().fold(floor, |max, bucket| max.max(bucket.avg));
+        Self { values, max_value, color, line_width }
+    }
+}
+
+impl<Message> canvas::Program<Message> for Sparkline {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        if self.values.len() < 2 {
+            return vec![frame.into_geometry()];
+        }
+
+        let step_x = bounds.width / (self.values.len() - 1) as f32;
+        let to_point = |i: usize, value: f32| {
+            iced::Point::new(i as f32 * step_x, bounds.height * (1.0 - value / self.max_value))
+        };
+        for (i, pair) in self.values.windows(2).enumerate() {
+            frame.stroke(
+                &Path::line(to_point(i, pair[0].avg), to_point(i + 1, pair[1].avg)),
+                Stroke::default().with_color(self.color).with_width(self.line_width),
+            );
+        }
+        vec![frame.into_geometry()]
+    }
+}
+
+// One bar split into a segment per process state, as wide as its share of processes
+struct StateBreakdownChart {
+    counts: Vec<(String, usize)>,
+    style: VisualStyle,
+}
+
+impl StateBreakdownChart {
+    fn new(counts: &HashMap<String, usize>, style: VisualStyle) -> Self {
+        let counts = most_common_first(counts)
+            .into_iter()
+            .map(|(status, count)| (status.to_string(), count))
+            .collect();
+        Self { counts, style }
+    }
+
+    fn state_color(&self, status: &str) -> iced::Color {
+        match status {
+            "Runnable" => self.style.ok,
+            "Sleeping" | "Idle" => self.style.info,
+            "Stopped" | "Tracing" | "UninterruptibleDiskSleep" => self.style.warning,
+            "Zombie" | "Dead" => self.style.danger,
+            _ => self.style.range,
+        }
+    }
+}
+
+impl<Message> canvas::Program<Message> for StateBreakdownChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let total: usize = self.counts.iter().map(|(_, count)| count).sum();
+        if total == 0 {
+            return vec![frame.into_geometry()];
+        }
+
+        let mut x = 0.0;
+        for (status, count) in &self.counts {
+            let width = *count as f32 / total as f32 * bounds.width;
+            frame.fill_rectangle(
+                iced::Point::new(x, 0.0),
+                iced::Size::new(width, bounds.height),
+                self.state_color(status),
+            );
+            x += width;
+        }
+        frame.stroke(
+            &Path::rectangle(iced::Point::ORIGIN, bounds.size()),
+            Stroke::default().with_color(self.style.axis).with_width(1.0),
+        );
+
+        vec![frame.into_geometry()]
+    }
+}
+
+// Overlays one line per compared process on a shared scale
+struct ComparisonChart {
+    title: String,
+    series: Vec<(Vec<f32>, iced::Color)>,
+    max_value: f32,
+    style: VisualStyle,
+}
+
+impl ComparisonChart {
+    /// `min_max` is the smallest top of the y axis; it grows to fit the data.
+    fn new(
+        title: &str,
+        series: Vec<(Vec<f32>, iced::Color)>,
+        min_max: f32,
+        render_budget: usize,
+        style: VisualStyle,
+    ) -> Self {
+        // Only the averages are drawn; two overlaid min/max bands would be unreadable
+        let series: Vec<(Vec<f32>, iced::Color)> = series
+            .into_iter()
+            .map(|(values, color)| {
+                let averages = downsample(&values, render_budget).iter().map(|b| b.avg).collect();
+                (averages, color)
+            })
+            .collect();
+        let observed_max = series
+            .iter()
+            .flat_map(|(values, _)| values.iter().copied())
+            .fold(0.0, f32::max);
+        Self {
+            title: title.to_string(),
+            series,
+            max_value: min_max.max(observed_max * 1.1),
+            style,
+        }
+    }
+}
+
+impl<Message> canvas::Program<Message> for ComparisonChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+
+        // Draw grid lines
+        for i in 0..=5 {
+            let y = i as f32 * bounds.height / 5.0;
+            frame.stroke(
+                &Path::line(
+                    iced::Point::new(0.0, y),
+                    iced::Point::new(bounds.width, y),
+                ),
+                Stroke {
+                    style: Style::Solid(self.style.grid),
+                    width: 1.0,
+                    ..Stroke::default()
+                },
+            );
+        }
+
+        // Draw labels
+        frame.fill_text(CanvasText {
+            content: format!("{} (max {:.1})", self.title, self.max_value),
+            position: iced::Point::new(5.0, 20.0),
+            color: self.style.label,
+            size: self.style.label_size,
+            ..CanvasText::default()
+        });
+
+        let scale_y = bounds.height / self.max_value;
+        for (values, color) in &self.series {
+            if values.len() < 2 {
+                continue;
+            }
+            let step_x = bounds.width / (values.len() - 1) as f32;
+            let points: Vec<iced::Point> = values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    iced::Point::new(i as f32 * step_x, bounds.height - value * scale_y)
+                })
+                .collect();
+            for segment in points.windows(2) {
+                frame.stroke(
+                    &Path::line(segment[0], segment[1]),
+                    Stroke {
+                        style: Style::Solid(*color),
+                        width: self.style.line_width,
+                        ..Stroke::default()
+                    },
+                );
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
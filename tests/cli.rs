@@ -0,0 +1,113 @@
+// tests/cli.rs
+
+//! The binary runs commands headless instead of opening the GUI.
+
+use std::process::Command;
+
+#[test]
+fn list_prints_the_table_header() {
+    // An empty config dir, so a developer's own config can't change the output
+    let config_home = std::env::temp_dir().join(format!("ltm-cli-test-{}", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_linux_task_manager"))
+        .args(["list", "--sort-by", "cpu", "--order", "desc"])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("failed to run the binary");
+
+    assert!(output.status.success(), "list failed: {}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let header = stdout.lines().next().unwrap_or_default();
+    assert!(header.starts_with("PID"), "unexpected header: {:?}", header);
+    assert!(header.trim_end().ends_with("Command"), "unexpected header: {:?}", header);
+}
+
+#[test]
+fn command_flags_without_a_command_are_rejected() {
+    for flag in [&["--daemonize"][..], &["--pid-file", "ltm.pid"], &["--dry-run"]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_linux_task_manager"))
+            .args(flag)
+            // Without a display, a GUI started anyway fails with a different error
+            .env_remove("DISPLAY")
+            .env_remove("WAYLAND_DISPLAY")
+            .output()
+            .expect("failed to run the binary");
+
+        assert!(!output.status.success(), "{:?} opened the GUI", flag);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("need a command"), "{:?}: unexpected error: {}", flag, stderr);
+    }
+}
+
+#[test]
+fn kill_refused_by_the_policy_fails() {
+    let dir = std::env::temp_dir().join(format!("ltm-cli-kill-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("config.toml");
+    std::fs::write(&config, "[kill_policy]\ndeny = [\"sleep\"]\n").unwrap();
+    let mut sleep = Command::new("sleep").arg("30").spawn().unwrap();
+    let pid = sleep.id().to_string();
+
+    for dry_run in [false, true] {
+        let mut args = vec!["--config", config.to_str().unwrap(), "kill", "--pid", &pid];
+        if dry_run {
+            args.push("--dry-run");
+        }
+        let output = Command::new(env!("CARGO_BIN_EXE_linux_task_manager"))
+            .args(&args)
+            .output()
+            .expect("failed to run the binary");
+
+        assert_eq!(output.status.code(), Some(1), "dry run: {}", dry_run);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("not allowed by the kill policy"), "unexpected error: {}", stderr);
+    }
+    assert!(sleep.try_wait().unwrap().is_none(), "the process was killed anyway");
+
+    sleep.kill().unwrap();
+    sleep.wait().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn kill_of_a_missing_process_fails() {
+    let mut sleep = Command::new("sleep").arg("0").spawn().unwrap();
+    let pid = sleep.id().to_string();
+    sleep.wait().unwrap();
+
+    let config_home = std::env::temp_dir().join(format!("ltm-cli-test-{}", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_linux_task_manager"))
+        .args(["kill", "--pid", &pid])
+        .env("XDG_CONFIG_HOME", &config_home)
+        .output()
+        .expect("failed to run the binary");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No process with PID"));
+}
+
+#[test]
+fn export_uses_the_list_filter_options() {
+    let dir = std::env::temp_dir().join(format!("ltm-cli-export-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("config.toml");
+    std::fs::write(&config, "[presets.sleepers]\nquery = \"sleep\"\n").unwrap();
+    let mut sleep = Command::new("sleep").arg("30").spawn().unwrap();
+    let row_start = format!("\n{},", sleep.id());
+
+    let export = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_linux_task_manager"))
+            .args(["--config", config.to_str().unwrap(), "export", "--format", "csv"])
+            .args(args)
+            .output()
+            .expect("failed to run the binary");
+        assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    assert!(export(&["--preset", "sleepers"]).contains(&row_start));
+    assert!(export(&["-f", "no-such-command sleep", "--any"]).contains(&row_start));
+    assert!(!export(&["-f", "no-such-command sleep", "--all"]).contains(&row_start));
+
+    sleep.kill().unwrap();
+    sleep.wait().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+}